@@ -0,0 +1,109 @@
+use std::fs::{self, File};
+use std::io::BufReader;
+use std::path::Path;
+use std::rc::Rc;
+
+use anyhow::{bail, Context, Result};
+use ion_rs::{
+    AnyEncoding, Catalog, Decoder, ElementReader, IonInput, IonResult, MapCatalog, Reader,
+    SharedSymbolTable, SystemReader,
+};
+
+/// The annotation that marks a value as a shared symbol table.
+const SHARED_SYMBOL_TABLE_ANNOTATION: &str = "$ion_shared_symbol_table";
+
+/// The shared symbol tables loaded with `--catalog`, which readers use to resolve the symbols of
+/// streams that import them. Clones share the same tables.
+#[derive(Clone, Default)]
+pub struct CommandCatalog {
+    tables: Rc<MapCatalog>,
+}
+
+impl CommandCatalog {
+    /// Loads every shared symbol table in the given files, and in the files directly inside the
+    /// given directories. Values that are not shared symbol tables are ignored.
+    pub fn load<'a>(paths: impl IntoIterator<Item = &'a str>) -> Result<Self> {
+        let mut tables = MapCatalog::new();
+        for path in paths {
+            let path = Path::new(path);
+            if path.is_dir() {
+                let mut files = fs::read_dir(path)
+                    .with_context(|| {
+                        format!("could not read catalog directory '{}'", path.display())
+                    })?
+                    .map(|entry| Ok(entry?.path()))
+                    .collect::<Result<Vec<_>>>()?;
+                // Load files in a predictable order, in case more than one defines a table.
+                files.sort();
+                for file in files.iter().filter(|file| file.is_file()) {
+                    load_file(&mut tables, file)?;
+                }
+            } else {
+                load_file(&mut tables, path)?;
+            }
+        }
+        Ok(Self {
+            tables: Rc::new(tables),
+        })
+    }
+
+    /// Returns the shared symbol table named by `spec`, which is either `name:version` or just
+    /// `name` for the table's latest version.
+    pub fn table(&self, spec: &str) -> Result<&SharedSymbolTable> {
+        let table = match spec.rsplit_once(':') {
+            Some((name, version)) => match version.parse::<usize>() {
+                Ok(version) => self.get_table_with_version(name, version),
+                Err(_) => self.get_table(spec),
+            },
+            None => self.get_table(spec),
+        };
+        match table {
+            Some(table) => Ok(table),
+            None => bail!("the catalog does not have a shared symbol table '{}'", spec),
+        }
+    }
+
+    /// Constructs a [`Reader`] that resolves imports using this catalog.
+    pub fn reader<I: IonInput>(&self, input: I) -> IonResult<Reader<AnyEncoding, I>> {
+        Reader::new(AnyEncoding.with_catalog(self.clone()), input)
+    }
+
+    /// Constructs a [`SystemReader`] that resolves imports using this catalog.
+    pub fn system_reader<I: IonInput>(&self, input: I) -> SystemReader<AnyEncoding, I> {
+        SystemReader::new(AnyEncoding.with_catalog(self.clone()), input)
+    }
+}
+
+impl Catalog for CommandCatalog {
+    fn get_table(&self, name: &str) -> Option<&SharedSymbolTable> {
+        self.tables.get_table(name)
+    }
+
+    fn get_table_with_version(&self, name: &str, version: usize) -> Option<&SharedSymbolTable> {
+        self.tables.get_table_with_version(name, version)
+    }
+}
+
+fn load_file(tables: &mut MapCatalog, path: &Path) -> Result<()> {
+    let file = File::open(path)
+        .with_context(|| format!("could not open catalog file '{}'", path.display()))?;
+    let mut reader = Reader::new(AnyEncoding, BufReader::new(file))?;
+    for element in reader.elements() {
+        let element = element
+            .with_context(|| format!("catalog file '{}' was not valid Ion", path.display()))?;
+        if !element
+            .annotations()
+            .contains(SHARED_SYMBOL_TABLE_ANNOTATION)
+        {
+            continue;
+        }
+        let table = SharedSymbolTable::try_from(element).with_context(|| {
+            format!(
+                "catalog file '{}' has an invalid shared symbol table",
+                path.display()
+            )
+        })?;
+        tables.insert_table(table);
+    }
+    Ok(())
+}
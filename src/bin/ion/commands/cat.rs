@@ -37,9 +37,8 @@ impl IonCliCommand for CatCommand {
     fn run(&self, _command_path: &mut Vec<String>, args: &ArgMatches) -> Result<()> {
         let transform = None::<fn(Element) -> Result<Element>>;
         CommandIo::new(args)?.for_each_input(|output, input| {
-            let mut reader = Reader::new(AnyEncoding, input.into_source())?;
-            let (encoding, format) = (*output.encoding(), *output.format());
-            write_all_as(&mut reader, output, encoding, format, transform)?;
+            let mut reader = input.into_reader()?;
+            write_all_as(&mut reader, output, transform)?;
             Ok(())
         })
     }
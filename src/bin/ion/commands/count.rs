@@ -0,0 +1,360 @@
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+
+use anyhow::{Context, Result};
+use bigdecimal::{BigDecimal, ToPrimitive};
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use ion_rs::{Element, ElementReader, Int, IonData, IonType, Struct, Value};
+
+use crate::commands::jq::ion_math::DecimalMath;
+use crate::commands::ordering::{compare, compare_optional};
+use crate::commands::path::IonPath;
+use crate::commands::timestamp_conversion::{timestamp_from_epoch, timestamp_to_epoch};
+use crate::commands::{CommandIo, IonCliCommand, WithIonCliArgument};
+
+/// The most significant digits that a mean is written with, as in a 128-bit decimal.
+const MEAN_PRECISION: u64 = 34;
+
+const NANOSECONDS_PER_SECOND: i128 = 1_000_000_000;
+
+pub struct CountCommand;
+
+impl IonCliCommand for CountCommand {
+    fn name(&self) -> &'static str {
+        "count"
+    }
+
+    fn about(&self) -> &'static str {
+        "Counts top-level values, optionally in groups, and aggregates the values at paths."
+    }
+
+    fn long_about(&self) -> Option<&'static str> {
+        Some(
+            "Counts the top-level values of all inputs, which are treated as a single stream. With \
+            one or more `--group-by` paths, values are grouped by the values at those paths and \
+            each group is counted separately. The result for each group is written as a struct \
+            like `{status: error, count: 12, sum: {bytes: 4096}, max: {time: 2024-01-02T}}`, \
+            which has a field for each key (named by its path without the leading `.`, and \
+            absent if the group's values do not have the key), the count, and a struct for each \
+            kind of aggregate that was requested, with a field for each of its paths. Groups are \
+            written in the order of their keys. `--sum` and `--mean` add up the numbers at a \
+            path, ignoring other values; the result is an int if every number is an int, a \
+            float if any is a float, and a decimal otherwise. `--mean` also averages timestamps \
+            if there are no numbers at the path. `--min` and `--max` compare values of any type \
+            as `ion sort` does, and `--distinct` counts the distinct values at a path.",
+        )
+    }
+
+    fn is_stable(&self) -> bool {
+        false
+    }
+
+    fn is_porcelain(&self) -> bool {
+        false
+    }
+
+    fn configure_args(&self, command: Command) -> Command {
+        let mut command = command.arg(
+            Arg::new("group-by")
+                .long("group-by")
+                .short('g')
+                .action(ArgAction::Append)
+                .value_name("PATH")
+                .help("A path to the value to group by, like `.status`. May be repeated."),
+        );
+        for aggregate in Aggregate::ALL {
+            command = command.arg(
+                Arg::new(aggregate.name())
+                    .long(aggregate.name())
+                    .action(ArgAction::Append)
+                    .value_name("PATH")
+                    .help(aggregate.help()),
+            );
+        }
+        command
+            .with_input()
+            .with_output()
+            .with_format()
+            .with_ion_version()
+    }
+
+    fn run(&self, _command_path: &mut Vec<String>, args: &ArgMatches) -> Result<()> {
+        let parse_paths = |id: &str| {
+            args.get_many::<String>(id)
+                .unwrap_or_default()
+                .map(|path| IonPath::parse(path))
+                .collect::<Result<Vec<_>>>()
+        };
+        let keys = parse_paths("group-by")?;
+        let mut aggregations = Vec::new();
+        for aggregate in Aggregate::ALL {
+            for path in parse_paths(aggregate.name())? {
+                aggregations.push((aggregate, path));
+            }
+        }
+
+        CommandIo::new(args)?.for_all_inputs(|output, inputs| {
+            let mut groups: HashMap<Vec<Option<IonData<Element>>>, Group> = HashMap::new();
+            if keys.is_empty() {
+                // Without keys, there is a single group even if there are no values.
+                groups.insert(Vec::new(), Group::new(&aggregations));
+            }
+            for input in inputs {
+                let input_name = input.name().to_owned();
+                let mut reader = input
+                    .into_reader()
+                    .with_context(|| format!("Input file '{}' was not valid Ion.", input_name))?;
+                for element in reader.elements() {
+                    let element = element?;
+                    let key = keys
+                        .iter()
+                        .map(|path| path.select(&element).cloned().map(IonData::from))
+                        .collect();
+                    groups
+                        .entry(key)
+                        .or_insert_with(|| Group::new(&aggregations))
+                        .add(&aggregations, &element)?;
+                }
+            }
+
+            let mut groups: Vec<_> = groups.into_iter().collect();
+            groups.sort_by(|(left, _), (right, _)| compare_keys(left, right));
+            let mut writer = output.as_writer()?;
+            for (key, group) in groups {
+                writer.write(group.into_element(&keys, key, &aggregations)?)?;
+            }
+            writer.close()?;
+            Ok(())
+        })
+    }
+}
+
+#[derive(Copy, Clone, PartialEq)]
+enum Aggregate {
+    Sum,
+    Min,
+    Max,
+    Mean,
+    Distinct,
+}
+
+impl Aggregate {
+    /// Every kind of aggregate, in the order they are written.
+    const ALL: [Aggregate; 5] = [
+        Aggregate::Sum,
+        Aggregate::Min,
+        Aggregate::Max,
+        Aggregate::Mean,
+        Aggregate::Distinct,
+    ];
+
+    fn name(&self) -> &'static str {
+        match self {
+            Aggregate::Sum => "sum",
+            Aggregate::Min => "min",
+            Aggregate::Max => "max",
+            Aggregate::Mean => "mean",
+            Aggregate::Distinct => "distinct",
+        }
+    }
+
+    fn help(&self) -> &'static str {
+        match self {
+            Aggregate::Sum => "Add up the numbers at this path. May be repeated.",
+            Aggregate::Min => "Find the smallest value at this path. May be repeated.",
+            Aggregate::Max => "Find the largest value at this path. May be repeated.",
+            Aggregate::Mean => "Average the numbers or timestamps at this path. May be repeated.",
+            Aggregate::Distinct => "Count the distinct values at this path. May be repeated.",
+        }
+    }
+}
+
+/// The count of a group of values and the state of each of its aggregations.
+struct Group {
+    count: usize,
+    states: Vec<AggregateState>,
+}
+
+enum AggregateState {
+    Total(Total),
+    Extreme(Option<Element>),
+    Distinct(HashSet<IonData<Element>>),
+}
+
+impl Group {
+    fn new(aggregations: &[(Aggregate, IonPath)]) -> Self {
+        let states = aggregations
+            .iter()
+            .map(|(aggregate, _)| match aggregate {
+                Aggregate::Sum | Aggregate::Mean => AggregateState::Total(Total::default()),
+                Aggregate::Min | Aggregate::Max => AggregateState::Extreme(None),
+                Aggregate::Distinct => AggregateState::Distinct(HashSet::new()),
+            })
+            .collect();
+        Self { count: 0, states }
+    }
+
+    fn add(&mut self, aggregations: &[(Aggregate, IonPath)], element: &Element) -> Result<()> {
+        self.count += 1;
+        for ((aggregate, path), state) in aggregations.iter().zip(self.states.iter_mut()) {
+            let Some(value) = path.select(element) else {
+                continue;
+            };
+            match state {
+                AggregateState::Total(total) => total.add(value)?,
+                AggregateState::Extreme(extreme) => {
+                    let replace = match extreme {
+                        None => true,
+                        Some(current) if *aggregate == Aggregate::Min => {
+                            compare(value, current).is_lt()
+                        }
+                        Some(current) => compare(value, current).is_gt(),
+                    };
+                    if replace {
+                        *extreme = Some(value.clone());
+                    }
+                }
+                AggregateState::Distinct(values) => {
+                    values.insert(IonData::from(value.clone()));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn into_element(
+        self,
+        keys: &[IonPath],
+        key: Vec<Option<IonData<Element>>>,
+        aggregations: &[(Aggregate, IonPath)],
+    ) -> Result<Element> {
+        let mut fields: Vec<(String, Element)> = keys
+            .iter()
+            .zip(key)
+            .filter_map(|(path, value)| Some((field_name(path), value?.into_inner())))
+            .collect();
+        fields.push(("count".to_owned(), Element::from(self.count as i64)));
+        for aggregate in Aggregate::ALL {
+            let mut results = Struct::builder();
+            let mut has_results = false;
+            for ((kind, path), state) in aggregations.iter().zip(self.states.iter()) {
+                if *kind != aggregate {
+                    continue;
+                }
+                let result = match state {
+                    AggregateState::Total(total) if aggregate == Aggregate::Sum => total.sum(),
+                    AggregateState::Total(total) => total.mean()?,
+                    AggregateState::Extreme(extreme) => {
+                        extreme.clone().unwrap_or(Element::null(IonType::Null))
+                    }
+                    AggregateState::Distinct(values) => Element::from(values.len() as i64),
+                };
+                results = results.with_field(field_name(path), result);
+                has_results = true;
+            }
+            if has_results {
+                fields.push((aggregate.name().to_owned(), results.build().into()));
+            }
+        }
+        Ok(Struct::from_iter(fields).into())
+    }
+}
+
+/// Orders groups by the value of each key in turn.
+fn compare_keys(left: &[Option<IonData<Element>>], right: &[Option<IonData<Element>>]) -> Ordering {
+    left.iter()
+        .zip(right.iter())
+        .map(|(left, right)| {
+            compare_optional(
+                left.as_ref().map(AsRef::as_ref),
+                right.as_ref().map(AsRef::as_ref),
+            )
+        })
+        .find(|ordering| ordering.is_ne())
+        .unwrap_or(Ordering::Equal)
+}
+
+/// Returns the name of the field that holds the result for `path`.
+fn field_name(path: &IonPath) -> String {
+    if path.steps().is_empty() {
+        return "value".to_owned();
+    }
+    let text = path.to_string();
+    match text.strip_prefix('.') {
+        Some(name) => name.to_owned(),
+        None => text,
+    }
+}
+
+/// A running total of the numbers, and of the timestamps, at a path.
+#[derive(Default)]
+struct Total {
+    /// The sum of the ints and decimals.
+    exact: BigDecimal,
+    /// The sum of the floats.
+    inexact: f64,
+    has_decimals: bool,
+    has_floats: bool,
+    numbers: usize,
+    epoch_nanoseconds: i128,
+    timestamps: usize,
+}
+
+impl Total {
+    fn add(&mut self, value: &Element) -> Result<()> {
+        match value.value() {
+            Value::Int(i) => self.exact += i.clone().into_big_decimal(),
+            Value::Decimal(d) => {
+                self.exact += d.clone().into_big_decimal();
+                self.has_decimals = true;
+            }
+            Value::Float(f) => {
+                self.inexact += f;
+                self.has_floats = true;
+            }
+            Value::Timestamp(timestamp) => {
+                let (seconds, nanoseconds) = timestamp_to_epoch(timestamp)?;
+                self.epoch_nanoseconds +=
+                    seconds as i128 * NANOSECONDS_PER_SECOND + nanoseconds as i128;
+                self.timestamps += 1;
+                return Ok(());
+            }
+            _ => return Ok(()),
+        }
+        self.numbers += 1;
+        Ok(())
+    }
+
+    fn sum(&self) -> Element {
+        if self.has_floats {
+            return Element::from(self.inexact + self.exact.to_f64().unwrap_or_default());
+        }
+        match self.exact.to_i128() {
+            Some(sum) if !self.has_decimals => Element::from(Int::from(sum)),
+            _ => Element::from(self.exact.clone().into_decimal()),
+        }
+    }
+
+    fn mean(&self) -> Result<Element> {
+        if self.numbers > 0 {
+            if self.has_floats {
+                let sum = self.inexact + self.exact.to_f64().unwrap_or_default();
+                return Ok(Element::from(sum / self.numbers as f64));
+            }
+            let mean = &self.exact / BigDecimal::from(self.numbers as u64);
+            let mean = if mean.digits() > MEAN_PRECISION {
+                mean.with_prec(MEAN_PRECISION)
+            } else {
+                mean
+            };
+            return Ok(Element::from(mean.into_decimal()));
+        }
+        if self.timestamps > 0 {
+            let mean = self.epoch_nanoseconds / self.timestamps as i128;
+            let seconds = mean.div_euclid(NANOSECONDS_PER_SECOND) as i64;
+            let nanoseconds = mean.rem_euclid(NANOSECONDS_PER_SECOND) as u32;
+            return Ok(Element::from(timestamp_from_epoch(seconds, nanoseconds)?));
+        }
+        Ok(Element::null(IonType::Null))
+    }
+}
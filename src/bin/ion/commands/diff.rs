@@ -0,0 +1,727 @@
+use std::collections::HashMap;
+use std::io::Write;
+
+use anyhow::{anyhow, Result};
+use clap::{arg, ArgMatches, Command};
+use ion_rs::{
+    AnyEncoding, Element, IonData, IonResult, LazyValue, Struct, Symbol, Value, ValueRef,
+};
+use termcolor::{Color, ColorSpec, WriteColor};
+
+use crate::commands::path::IonPath;
+use crate::commands::to::json::field_name_id;
+use crate::commands::{CommandIo, IonCliCommand, WithIonCliArgument};
+use crate::input::CommandInput;
+use crate::output::CommandOutput;
+
+pub struct DiffCommand;
+
+impl IonCliCommand for DiffCommand {
+    fn name(&self) -> &'static str {
+        "diff"
+    }
+
+    fn about(&self) -> &'static str {
+        "Compares two Ion streams and reports how they differ."
+    }
+
+    fn long_about(&self) -> Option<&'static str> {
+        Some(
+            "Compares two Ion streams using the Ion data model, so differences in encoding, \
+            whitespace or the order of struct fields are ignored. Top-level values (and the \
+            elements of lists and s-expressions) are aligned before being compared, so an inserted \
+            or removed value is reported as such rather than as a change to every value after it. \
+            Each difference is reported with the path of the value, for example `[0].items[2]`, \
+            where the first step is the index of the top-level value; a field name with unknown \
+            text is shown as its symbol ID, like `$10`. With `--patch`, the \
+            differences are written as an Ion patch that `ion patch` can apply to the first input \
+            to produce the second; unlike the summary, the patch is meant to be machine-readable. \
+            If the values that differ are too many to align, they are compared by position \
+            instead, with a warning.",
+        )
+    }
+
+    fn is_stable(&self) -> bool {
+        false
+    }
+
+    fn is_porcelain(&self) -> bool {
+        // The summary is the default output; only the output of `--patch` is meant to be read by
+        // other programs.
+        true
+    }
+
+    fn configure_args(&self, command: Command) -> Command {
+        command
+            .arg(arg!(--patch "Write the differences as an Ion patch instead of a human-readable summary"))
+            .with_input()
+            .with_output()
+            .with_format()
+            .with_ion_version()
+            .with_syntax_highlighting()
+    }
+
+    fn run(&self, _command_path: &mut Vec<String>, args: &ArgMatches) -> Result<()> {
+        let as_patch = args.get_flag("patch");
+        let command_io = CommandIo::new(args)?;
+        let mut command_io = if as_patch {
+            command_io
+        } else {
+            command_io.with_plain_colors()
+        };
+        command_io.for_all_inputs(|output, inputs| {
+            let [left, right] = <[_; 2]>::try_from(inputs).map_err(|inputs: Vec<_>| {
+                anyhow!("'diff' requires exactly 2 inputs, found {}", inputs.len())
+            })?;
+            let (left, left_ids) = read_values(left)?;
+            let (right, right_ids) = read_values(right)?;
+
+            let mut diff = Diff::default();
+            diff_sequences(
+                &IonPath::default(),
+                &left,
+                &right,
+                &left_ids,
+                &right_ids,
+                &mut diff,
+            );
+
+            if as_patch {
+                // The patch is meant for other programs, so the warnings go to STDERR instead.
+                for unaligned in &diff.unaligned {
+                    eprintln!("WARNING: {}", unaligned.warning());
+                }
+                let mut writer = output.as_writer()?;
+                for change in &diff.changes {
+                    writer.write(change.to_patch_operation())?;
+                }
+                writer.close()?;
+            } else {
+                write_summary(output, &diff)?;
+            }
+            Ok(())
+        })
+    }
+}
+
+// The names of the patch operations and their fields, which `ion patch` also understands.
+
+pub(crate) const OP_FIELD: &str = "op";
+pub(crate) const PATH_FIELD: &str = "path";
+pub(crate) const VALUE_FIELD: &str = "value";
+pub(crate) const ANNOTATIONS_FIELD: &str = "annotations";
+/// Replaces the value at a path.
+pub(crate) const SET_OP: &str = "set";
+/// Inserts a value before the specified index, or adds a field with the specified name (even if
+/// the struct already has one).
+pub(crate) const INSERT_OP: &str = "insert";
+/// Removes the value at a path. For a struct field, every field with that name is removed.
+pub(crate) const REMOVE_OP: &str = "remove";
+/// Replaces the annotations of the value at a path.
+pub(crate) const SET_ANNOTATIONS_OP: &str = "set_annotations";
+
+/// Reads every top-level value of `input`, along with the symbol IDs of its field names with
+/// unknown text, which reading it as elements does not keep.
+fn read_values(input: CommandInput) -> Result<(Vec<Element>, FieldIds)> {
+    let mut reader = input.into_reader()?;
+    let mut elements = Vec::new();
+    let mut ids = Vec::new();
+    while let Some(value) = reader.next()? {
+        ids.push(FieldIds::of(value)?);
+        elements.push(Element::try_from(value)?);
+    }
+    Ok((elements, FieldIds::Sequence(ids)))
+}
+
+/// The symbol IDs of the field names with unknown text in a value, so that their paths can show
+/// them as `$N`. A value without any such field names is `None`.
+#[derive(Debug)]
+enum FieldIds {
+    None,
+    /// The IDs within each element of a list or s-expression.
+    Sequence(Vec<FieldIds>),
+    /// For each field of a struct, the symbol ID of its name if its text is unknown, and the IDs
+    /// within its value.
+    Struct(Vec<(Option<usize>, FieldIds)>),
+}
+
+impl FieldIds {
+    fn of(value: LazyValue<AnyEncoding>) -> Result<Self> {
+        let ids = match value.read()? {
+            ValueRef::List(list) => FieldIds::sequence(list.iter())?,
+            ValueRef::SExp(sexp) => FieldIds::sequence(sexp.iter())?,
+            ValueRef::Struct(strukt) => {
+                let mut fields = Vec::new();
+                for field in strukt {
+                    let field = field?;
+                    let sid = match field.name()?.text() {
+                        Some(_) => None,
+                        None => Some(field_name_id(&field)?),
+                    };
+                    fields.push((sid, FieldIds::of(field.value())?));
+                }
+                let has_ids = fields
+                    .iter()
+                    .any(|(sid, ids)| sid.is_some() || !matches!(ids, FieldIds::None));
+                if has_ids {
+                    FieldIds::Struct(fields)
+                } else {
+                    FieldIds::None
+                }
+            }
+            _ => FieldIds::None,
+        };
+        Ok(ids)
+    }
+
+    fn sequence<'a>(
+        values: impl Iterator<Item = IonResult<LazyValue<'a, AnyEncoding>>>,
+    ) -> Result<Self> {
+        let elements = values
+            .map(|value| FieldIds::of(value?))
+            .collect::<Result<Vec<_>>>()?;
+        if elements.iter().all(|ids| matches!(ids, FieldIds::None)) {
+            Ok(FieldIds::None)
+        } else {
+            Ok(FieldIds::Sequence(elements))
+        }
+    }
+
+    /// The IDs within the element of a sequence at `index`.
+    fn element(&self, index: usize) -> &FieldIds {
+        match self {
+            FieldIds::Sequence(elements) => &elements[index],
+            _ => &FieldIds::None,
+        }
+    }
+
+    /// The symbol ID of the name of the struct field at `index`, if its text is unknown, and the IDs
+    /// within its value.
+    fn field(&self, index: usize) -> (Option<usize>, &FieldIds) {
+        match self {
+            FieldIds::Struct(fields) => (fields[index].0, &fields[index].1),
+            _ => (None, &FieldIds::None),
+        }
+    }
+}
+
+/// The differences between two Ion streams.
+#[derive(Debug, Default)]
+struct Diff {
+    changes: Vec<Change>,
+    /// The sequences that were compared by position because their differing values were too many
+    /// to align.
+    unaligned: Vec<Unaligned>,
+}
+
+/// A sequence whose differing values were compared by position.
+#[derive(Debug)]
+struct Unaligned {
+    path: IonPath,
+    /// The number of values that differ in each stream.
+    left_len: usize,
+    right_len: usize,
+}
+
+impl Unaligned {
+    fn warning(&self) -> String {
+        let location = if self.path.steps().is_empty() {
+            "at the top level".to_owned()
+        } else {
+            format!("in {}", self.path)
+        };
+        format!(
+            "the {} and {} values that differ {location} are too many to align, so they were \
+            compared by position",
+            self.left_len, self.right_len
+        )
+    }
+}
+
+/// A single difference between two Ion streams.
+#[derive(Debug)]
+enum Change {
+    /// The value at `path` was replaced by `new`.
+    Set {
+        path: IonPath,
+        old: Element,
+        new: Element,
+    },
+    /// `value` was inserted at `path`.
+    Insert { path: IonPath, value: Element },
+    /// `old` was removed from `path`.
+    Remove { path: IonPath, old: Element },
+    /// The annotations of the value at `path` changed from `old` to `new`.
+    SetAnnotations {
+        path: IonPath,
+        old: Vec<Symbol>,
+        new: Vec<Symbol>,
+    },
+}
+
+impl Change {
+    fn to_patch_operation(&self) -> Element {
+        let (op, path) = match self {
+            Change::Set { path, .. } => (SET_OP, path),
+            Change::Insert { path, .. } => (INSERT_OP, path),
+            Change::Remove { path, .. } => (REMOVE_OP, path),
+            Change::SetAnnotations { path, .. } => (SET_ANNOTATIONS_OP, path),
+        };
+        let builder = Struct::builder()
+            .with_field(OP_FIELD, Element::symbol(op))
+            .with_field(PATH_FIELD, path.to_element());
+        let builder = match self {
+            Change::Set { new: value, .. } | Change::Insert { value, .. } => {
+                builder.with_field(VALUE_FIELD, value.clone())
+            }
+            Change::Remove { .. } => builder,
+            Change::SetAnnotations { new, .. } => builder.with_field(
+                ANNOTATIONS_FIELD,
+                ion_rs::List::from(new.iter().cloned().map(Element::symbol).collect::<Vec<_>>()),
+            ),
+        };
+        builder.build().into()
+    }
+}
+
+/// Writes one line per change, colored when the output is a terminal, followed by a warning for
+/// each sequence that was compared by position.
+fn write_summary(output: &mut CommandOutput, diff: &Diff) -> Result<()> {
+    for change in &diff.changes {
+        let (marker, color, path, description) = match change {
+            Change::Set { path, old, new } => ("~", Color::Yellow, path, format!("{old} => {new}")),
+            Change::Insert { path, value } => ("+", Color::Green, path, value.to_string()),
+            Change::Remove { path, old } => ("-", Color::Red, path, old.to_string()),
+            Change::SetAnnotations { path, old, new } => (
+                "~",
+                Color::Yellow,
+                path,
+                format!(
+                    "annotations {} => {}",
+                    annotations_text(old),
+                    annotations_text(new)
+                ),
+            ),
+        };
+        output.set_color(ColorSpec::new().set_fg(Some(color)))?;
+        write!(output, "{marker} {path}")?;
+        output.reset()?;
+        writeln!(output, ": {description}")?;
+    }
+    for unaligned in &diff.unaligned {
+        output.set_color(ColorSpec::new().set_fg(Some(Color::Yellow)).set_bold(true))?;
+        write!(output, "WARNING:")?;
+        output.reset()?;
+        writeln!(output, " {}", unaligned.warning())?;
+    }
+    Ok(())
+}
+
+fn annotations_text(annotations: &[Symbol]) -> String {
+    let texts: Vec<_> = annotations
+        .iter()
+        .map(|a| Element::symbol(a.clone()).to_string())
+        .collect();
+    format!("[{}]", texts.join(", "))
+}
+
+fn ion_eq(left: &Element, right: &Element) -> bool {
+    IonData::eq(left, right)
+}
+
+/// Compares two values at `path`, recording any changes.
+fn diff_elements(
+    path: &IonPath,
+    left: &Element,
+    right: &Element,
+    left_ids: &FieldIds,
+    right_ids: &FieldIds,
+    diff: &mut Diff,
+) {
+    if ion_eq(left, right) {
+        return;
+    }
+    let same_container_type = left.ion_type() == right.ion_type()
+        && !left.is_null()
+        && !right.is_null()
+        && left.ion_type().is_container();
+    if !same_container_type {
+        diff.changes.push(Change::Set {
+            path: path.clone(),
+            old: left.clone(),
+            new: right.clone(),
+        });
+        return;
+    }
+
+    let (old_annotations, new_annotations) = (
+        left.annotations().iter().cloned().collect::<Vec<_>>(),
+        right.annotations().iter().cloned().collect::<Vec<_>>(),
+    );
+    if old_annotations != new_annotations {
+        diff.changes.push(Change::SetAnnotations {
+            path: path.clone(),
+            old: old_annotations,
+            new: new_annotations,
+        });
+    }
+    match (left.value(), right.value()) {
+        (Value::List(l), Value::List(r)) | (Value::SExp(l), Value::SExp(r)) => {
+            let left: Vec<_> = l.elements().cloned().collect();
+            let right: Vec<_> = r.elements().cloned().collect();
+            diff_sequences(path, &left, &right, left_ids, right_ids, diff)
+        }
+        (Value::Struct(l), Value::Struct(r)) => diff_structs(path, l, r, left_ids, right_ids, diff),
+        _ => unreachable!("both values are containers of the same type"),
+    }
+}
+
+/// Compares the fields of two structs without regard to their order.
+fn diff_structs(
+    path: &IonPath,
+    left: &Struct,
+    right: &Struct,
+    left_ids: &FieldIds,
+    right_ids: &FieldIds,
+    diff: &mut Diff,
+) {
+    let left_fields = group_fields(left, left_ids);
+    let right_fields = group_fields(right, right_ids);
+    let right_by_name: HashMap<&str, &Vec<FieldValue>> =
+        right_fields.iter().map(|(n, v)| (n.as_str(), v)).collect();
+    let left_by_name: HashMap<&str, &Vec<FieldValue>> =
+        left_fields.iter().map(|(n, v)| (n.as_str(), v)).collect();
+
+    for (name, left_values) in &left_fields {
+        let field_path = path.field(name);
+        match right_by_name.get(name.as_str()) {
+            None => diff.changes.push(Change::Remove {
+                path: field_path,
+                old: field_value(left_values),
+            }),
+            Some(right_values) => match (left_values.as_slice(), right_values.as_slice()) {
+                ([(left_value, left_ids)], [(right_value, right_ids)]) => diff_elements(
+                    &field_path,
+                    left_value,
+                    right_value,
+                    left_ids,
+                    right_ids,
+                    diff,
+                ),
+                _ if same_multiset(left_values, right_values) => {}
+                // A repeated field name can't be addressed by a path, so all of the fields with
+                // that name are replaced.
+                _ => {
+                    diff.changes.push(Change::Remove {
+                        path: field_path.clone(),
+                        old: field_value(left_values),
+                    });
+                    for (value, _) in right_values.iter() {
+                        diff.changes.push(Change::Insert {
+                            path: field_path.clone(),
+                            value: (*value).clone(),
+                        });
+                    }
+                }
+            },
+        }
+    }
+    for (name, right_values) in &right_fields {
+        if left_by_name.contains_key(name.as_str()) {
+            continue;
+        }
+        for (value, _) in right_values {
+            diff.changes.push(Change::Insert {
+                path: path.field(name),
+                value: (*value).clone(),
+            });
+        }
+    }
+}
+
+/// Returns the struct's fields grouped by name, in the order in which each name first appears.
+/// Names with unknown text are written as `$N`, where `N` is their symbol ID.
+fn group_fields<'a>(strukt: &'a Struct, ids: &'a FieldIds) -> Vec<(String, Vec<FieldValue<'a>>)> {
+    let mut groups: Vec<(String, Vec<FieldValue>)> = Vec::new();
+    let mut positions: HashMap<String, usize> = HashMap::new();
+    for (index, (name, value)) in strukt.fields().enumerate() {
+        let (sid, value_ids) = ids.field(index);
+        let name = match name.text() {
+            Some(text) => text.to_owned(),
+            None => format!("${}", sid.unwrap_or(0)),
+        };
+        match positions.get(&name) {
+            Some(&position) => groups[position].1.push((value, value_ids)),
+            None => {
+                positions.insert(name.clone(), groups.len());
+                groups.push((name, vec![(value, value_ids)]));
+            }
+        }
+    }
+    groups
+}
+
+/// The value of a field and the symbol IDs within it.
+type FieldValue<'a> = (&'a Element, &'a FieldIds);
+
+/// The value reported for a (possibly repeated) field: the value itself, or a list of the values.
+fn field_value(values: &[FieldValue]) -> Element {
+    match values {
+        [(value, _)] => (*value).clone(),
+        values => {
+            ion_rs::List::from(values.iter().map(|(v, _)| (*v).clone()).collect::<Vec<_>>()).into()
+        }
+    }
+}
+
+fn same_multiset(left: &[FieldValue], right: &[FieldValue]) -> bool {
+    fn sorted<'a>(values: &[FieldValue<'a>]) -> Vec<IonData<&'a Element>> {
+        let mut values: Vec<_> = values.iter().map(|(v, _)| IonData::from(*v)).collect();
+        values.sort();
+        values
+    }
+    left.len() == right.len() && sorted(left) == sorted(right)
+}
+
+/// An edit that transforms one sequence into another.
+enum Edit {
+    Keep,
+    Remove(usize),
+    Insert(usize),
+}
+
+/// Compares two sequences of values (the top-level values of a stream, or the elements of a list
+/// or s-expression), recording any changes. The indexes in the recorded paths account for the
+/// changes that precede them, so the changes can be applied in order.
+fn diff_sequences(
+    path: &IonPath,
+    left: &[Element],
+    right: &[Element],
+    left_ids: &FieldIds,
+    right_ids: &FieldIds,
+    diff: &mut Diff,
+) {
+    let (edits, unaligned) = align(left, right);
+    if let Some((left_len, right_len)) = unaligned {
+        diff.unaligned.push(Unaligned {
+            path: path.clone(),
+            left_len,
+            right_len,
+        });
+    }
+    let mut position = 0;
+    let mut removed = Vec::new();
+    let mut inserted = Vec::new();
+    for edit in edits.into_iter().chain(std::iter::once(Edit::Keep)) {
+        match edit {
+            Edit::Remove(index) => removed.push(index),
+            Edit::Insert(index) => inserted.push(index),
+            Edit::Keep => {
+                // Values removed and inserted at the same position are treated as changes.
+                let paired = removed.len().min(inserted.len());
+                for (&l, &r) in removed.iter().zip(&inserted) {
+                    diff_elements(
+                        &path.index(position),
+                        &left[l],
+                        &right[r],
+                        left_ids.element(l),
+                        right_ids.element(r),
+                        diff,
+                    );
+                    position += 1;
+                }
+                for &l in &removed[paired..] {
+                    diff.changes.push(Change::Remove {
+                        path: path.index(position),
+                        old: left[l].clone(),
+                    });
+                }
+                for &r in &inserted[paired..] {
+                    diff.changes.push(Change::Insert {
+                        path: path.index(position),
+                        value: right[r].clone(),
+                    });
+                    position += 1;
+                }
+                removed.clear();
+                inserted.clear();
+                position += 1;
+            }
+        }
+    }
+}
+
+/// Finds a minimal sequence of edits that transforms `left` into `right`. If the values that differ
+/// are too many to align, they are paired by position instead, and their numbers are returned
+/// with the edits.
+fn align(left: &[Element], right: &[Element]) -> (Vec<Edit>, Option<(usize, usize)>) {
+    // Sequences whose differing parts would take too long to align are compared positionally.
+    const MAX_COMPARISONS: usize = 100_000_000;
+
+    let prefix = left
+        .iter()
+        .zip(right)
+        .take_while(|(l, r)| ion_eq(l, r))
+        .count();
+    let suffix = left[prefix..]
+        .iter()
+        .rev()
+        .zip(right[prefix..].iter().rev())
+        .take_while(|(l, r)| ion_eq(l, r))
+        .count();
+    let (l_end, r_end) = (left.len() - suffix, right.len() - suffix);
+    let (l_mid, r_mid) = (&left[prefix..l_end], &right[prefix..r_end]);
+
+    let mut edits: Vec<Edit> = (0..prefix).map(|_| Edit::Keep).collect();
+    let mut unaligned = None;
+    if l_mid.len().saturating_mul(r_mid.len()) > MAX_COMPARISONS {
+        // `diff_sequences` pairs up the removed and inserted values by position.
+        edits.extend((prefix..l_end).map(Edit::Remove));
+        edits.extend((prefix..r_end).map(Edit::Insert));
+        unaligned = Some((l_mid.len(), r_mid.len()));
+    } else {
+        align_lcs(l_mid, r_mid, prefix, prefix, &mut edits);
+    }
+    edits.extend((0..suffix).map(|_| Edit::Keep));
+    (edits, unaligned)
+}
+
+/// Appends the edits that align `left` and `right` along a longest common subsequence, using
+/// Hirschberg's algorithm so that memory use is linear in the length of `right`. The indexes of
+/// the values in the input are `left_start` and `right_start` plus their indexes in the slices.
+fn align_lcs(
+    left: &[Element],
+    right: &[Element],
+    left_start: usize,
+    right_start: usize,
+    edits: &mut Vec<Edit>,
+) {
+    if left.is_empty() || right.is_empty() {
+        edits.extend((left_start..left_start + left.len()).map(Edit::Remove));
+        edits.extend((right_start..right_start + right.len()).map(Edit::Insert));
+        return;
+    }
+    if left.len() == 1 {
+        match right.iter().position(|value| ion_eq(&left[0], value)) {
+            Some(found) => {
+                edits.extend((right_start..right_start + found).map(Edit::Insert));
+                edits.push(Edit::Keep);
+                edits
+                    .extend((right_start + found + 1..right_start + right.len()).map(Edit::Insert));
+            }
+            None => {
+                edits.push(Edit::Remove(left_start));
+                edits.extend((right_start..right_start + right.len()).map(Edit::Insert));
+            }
+        }
+        return;
+    }
+    // Split `left` in half, and `right` where the halves' common subsequences are longest.
+    let middle = left.len() / 2;
+    let forward = lcs_lengths(left[..middle].iter(), right.iter());
+    let mut backward = lcs_lengths(left[middle..].iter().rev(), right.iter().rev());
+    backward.reverse();
+    let split = (0..=right.len())
+        .max_by_key(|&j| (forward[j] + backward[j], std::cmp::Reverse(j)))
+        .unwrap();
+    align_lcs(
+        &left[..middle],
+        &right[..split],
+        left_start,
+        right_start,
+        edits,
+    );
+    align_lcs(
+        &left[middle..],
+        &right[split..],
+        left_start + middle,
+        right_start + split,
+        edits,
+    );
+}
+
+/// Returns, for each `j`, the length of the longest common subsequence of `left` and the first
+/// `j` values of `right`.
+fn lcs_lengths<'a>(
+    left: impl Iterator<Item = &'a Element>,
+    right: impl Iterator<Item = &'a Element> + Clone,
+) -> Vec<usize> {
+    let mut lengths = vec![0; right.clone().count() + 1];
+    for l in left {
+        let mut diagonal = 0;
+        for (j, r) in right.clone().enumerate() {
+            let above = lengths[j + 1];
+            lengths[j + 1] = if ion_eq(l, r) {
+                diagonal + 1
+            } else {
+                above.max(lengths[j])
+            };
+            diagonal = above;
+        }
+    }
+    lengths
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Asserts that diffing `left` and `right` produces the patch operations in `expected`.
+    fn assert_diff(left: &str, right: &str, expected: &str) {
+        let left: Vec<_> = Element::read_all(left).unwrap().into_iter().collect();
+        let right: Vec<_> = Element::read_all(right).unwrap().into_iter().collect();
+        let mut diff = Diff::default();
+        let ids = FieldIds::None;
+        diff_sequences(&IonPath::default(), &left, &right, &ids, &ids, &mut diff);
+        let actual: Vec<_> = diff
+            .changes
+            .iter()
+            .map(Change::to_patch_operation)
+            .collect();
+        let expected: Vec<_> = Element::read_all(expected).unwrap().into_iter().collect();
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn identical_streams_have_no_changes() {
+        assert_diff("{a: 1, b: [1, 2]} 3", "{b: [1, 2], a: 1} 3", "");
+    }
+
+    #[test]
+    fn inserted_and_removed_values_are_aligned() {
+        assert_diff(
+            "1 2 3 4",
+            "0 1 3 4 5",
+            "{op: insert, path: [0], value: 0} {op: remove, path: [2]} {op: insert, path: [4], value: 5}",
+        );
+    }
+
+    #[test]
+    fn longer_sequences_are_aligned_minimally() {
+        assert_diff(
+            "a 1 2 3 4 5 6 7 8 b",
+            "a 2 3 9 5 6 8 0 b",
+            r#"
+            {op: remove, path: [1]}
+            {op: set, path: [3], value: 9}
+            {op: remove, path: [6]}
+            {op: insert, path: [7], value: 0}
+            "#,
+        );
+    }
+
+    #[test]
+    fn nested_changes_are_reported_by_path() {
+        assert_diff(
+            "{a: {b: 1, c: 2}, l: [1, 2]}",
+            "a::{a: {b: 5}, l: [1, 2, 3], d: x}",
+            r#"
+            {op: set_annotations, path: [0], annotations: [a]}
+            {op: set, path: [0, a, b], value: 5}
+            {op: remove, path: [0, a, c]}
+            {op: insert, path: [0, l, 2], value: 3}
+            {op: insert, path: [0, d], value: x}
+            "#,
+        );
+    }
+}
@@ -0,0 +1,288 @@
+use anyhow::{bail, Context, Result};
+use clap::{Arg, ArgMatches, Command};
+use ion_rs::{Element, ElementReader, List, Struct, Symbol, Value};
+
+use crate::commands::path::PathStep;
+use crate::commands::{CommandIo, IonCliCommand, WithIonCliArgument};
+
+pub struct FlattenCommand;
+
+impl IonCliCommand for FlattenCommand {
+    fn name(&self) -> &'static str {
+        "flatten"
+    }
+
+    fn about(&self) -> &'static str {
+        "Flattens nested structs and lists into single-level structs."
+    }
+
+    fn long_about(&self) -> Option<&'static str> {
+        Some(
+            "Rewrites each top-level struct or list as a struct with a field for every scalar \
+            value it contains, named by the path to the value: `{a: {b: 1, c: [x, y]}}` becomes \
+            `{a.b: 1, 'a.c[0]': x, 'a.c[1]': y}` (with quotes where needed). Empty structs and \
+            lists, null containers and s-expressions are kept as values. `--separator` sets the \
+            text between field names and `--index-style separator` writes list indexes like field \
+            names (`a.c.0`). Annotations on scalar values are kept, and annotations on the \
+            top-level value stay on the flattened struct; the annotations of nested structs and \
+            lists are dropped unless `--annotations FIELD` is used, which writes all nested \
+            annotations to a struct in that field, like `FIELD: {a: [x], 'a.c[0]': [y]}`. \
+            `ion unflatten` reverses this, as long as no field names contain the separator or \
+            look like list indexes.",
+        )
+    }
+
+    fn is_stable(&self) -> bool {
+        false
+    }
+
+    fn is_porcelain(&self) -> bool {
+        false
+    }
+
+    fn configure_args(&self, command: Command) -> Command {
+        KeyStyle::configure_args(command)
+            .with_input()
+            .with_output()
+            .with_format()
+            .with_ion_version()
+    }
+
+    fn run(&self, _command_path: &mut Vec<String>, args: &ArgMatches) -> Result<()> {
+        let key_style = KeyStyle::from_args(args)?;
+        let annotations_field = args.get_one::<String>("annotations").map(String::as_str);
+        CommandIo::new(args)?.for_each_input(|output, input| {
+            let input_name = input.name().to_owned();
+            let mut reader = input
+                .into_reader()
+                .with_context(|| format!("Input file '{}' was not valid Ion.", input_name))?;
+            let mut writer = output.as_writer()?;
+            for element in reader.elements() {
+                writer.write(flatten(element?, &key_style, annotations_field))?;
+            }
+            writer.close()?;
+            Ok(())
+        })
+    }
+}
+
+/// How list indexes are written in flattened field names.
+#[derive(Copy, Clone, PartialEq)]
+pub(crate) enum IndexStyle {
+    /// `a[0]`
+    Brackets,
+    /// `a.0`, using the separator
+    Separator,
+}
+
+/// How paths are encoded in the field names of flattened structs.
+pub(crate) struct KeyStyle {
+    separator: String,
+    index_style: IndexStyle,
+}
+
+impl KeyStyle {
+    /// Adds the arguments shared by `flatten` and `unflatten`.
+    pub(crate) fn configure_args(command: Command) -> Command {
+        command
+            .arg(
+                Arg::new("separator")
+                    .long("separator")
+                    .short('s')
+                    .default_value(".")
+                    .help("The text written between the steps of a path."),
+            )
+            .arg(
+                Arg::new("index-style")
+                    .long("index-style")
+                    .value_parser(["brackets", "separator"])
+                    .default_value("brackets")
+                    .help("Write list indexes like `a[0]` (brackets) or `a.0` (separator)."),
+            )
+            .arg(
+                Arg::new("annotations")
+                    .long("annotations")
+                    .short('a')
+                    .value_name("FIELD")
+                    .help("The field that holds the annotations of nested values."),
+            )
+    }
+
+    pub(crate) fn from_args(args: &ArgMatches) -> Result<Self> {
+        let separator = args.get_one::<String>("separator").unwrap().to_owned();
+        if separator.is_empty() {
+            bail!("the separator must not be empty");
+        }
+        let index_style = match args.get_one::<String>("index-style").unwrap().as_str() {
+            "separator" => IndexStyle::Separator,
+            _ => IndexStyle::Brackets,
+        };
+        Ok(Self {
+            separator,
+            index_style,
+        })
+    }
+
+    /// Returns the field name for `step` within the value whose field name is `prefix`, which is
+    /// empty for the top-level value.
+    pub(crate) fn child_key(&self, prefix: &str, step: &PathStep) -> String {
+        match (step, self.index_style) {
+            (PathStep::Index(index), IndexStyle::Brackets) => format!("{prefix}[{index}]"),
+            _ if prefix.is_empty() => step_text(step),
+            _ => format!("{prefix}{}{}", self.separator, step_text(step)),
+        }
+    }
+
+    /// Returns the steps of the path encoded in a flattened field name.
+    pub(crate) fn parse_key(&self, key: &str) -> Vec<PathStep> {
+        let mut steps = Vec::new();
+        for segment in key.split(self.separator.as_str()) {
+            match self.index_style {
+                IndexStyle::Separator => match segment.parse::<usize>() {
+                    Ok(index) if segment.bytes().all(|b| b.is_ascii_digit()) => {
+                        steps.push(PathStep::Index(index))
+                    }
+                    _ => steps.push(PathStep::Field(segment.to_owned())),
+                },
+                IndexStyle::Brackets => {
+                    let (name, indexes) = split_indexes(segment);
+                    if !name.is_empty() || indexes.is_empty() {
+                        steps.push(PathStep::Field(name.to_owned()));
+                    }
+                    steps.extend(indexes.into_iter().map(PathStep::Index));
+                }
+            }
+        }
+        steps
+    }
+}
+
+fn step_text(step: &PathStep) -> String {
+    match step {
+        PathStep::Field(name) => name.to_owned(),
+        PathStep::Index(index) => index.to_string(),
+    }
+}
+
+/// Splits a segment like `name[1][2]` into its field name and indexes. If the segment does not end
+/// with indexes, all of it is the field name.
+fn split_indexes(segment: &str) -> (&str, Vec<usize>) {
+    let mut indexes = Vec::new();
+    let mut rest = segment;
+    while let Some(without_bracket) = rest.strip_suffix(']') {
+        let Some(open) = without_bracket.rfind('[') else {
+            break;
+        };
+        let Ok(index) = without_bracket[open + 1..].parse::<usize>() else {
+            break;
+        };
+        indexes.push(index);
+        rest = &without_bracket[..open];
+    }
+    indexes.reverse();
+    (rest, indexes)
+}
+
+/// Returns `true` if `element` is a container whose values are written as separate fields.
+fn is_flattened(element: &Element) -> bool {
+    match element.value() {
+        Value::Struct(strukt) => !strukt.is_empty(),
+        Value::List(list) => !list.is_empty(),
+        _ => false,
+    }
+}
+
+/// Returns the steps to and values of the children of a container.
+fn children(element: &Element) -> Vec<(PathStep, Element)> {
+    match element.value() {
+        Value::Struct(strukt) => strukt
+            .fields()
+            .map(|(name, value)| {
+                let name = name.text().unwrap_or("$0").to_owned();
+                (PathStep::Field(name), value.clone())
+            })
+            .collect(),
+        Value::List(list) => list
+            .elements()
+            .enumerate()
+            .map(|(index, value)| (PathStep::Index(index), value.clone()))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Flattens a top-level value. Values other than non-empty structs and lists are returned as-is.
+fn flatten(root: Element, key_style: &KeyStyle, annotations_field: Option<&str>) -> Element {
+    if !is_flattened(&root) {
+        return root;
+    }
+    let mut fields: Vec<(String, Element)> = Vec::new();
+    let mut nested_annotations: Vec<(String, Element)> = Vec::new();
+    // Pre-order traversal; children are pushed in reverse so that they are visited in order.
+    let mut stack: Vec<(String, Element)> = children(&root)
+        .into_iter()
+        .rev()
+        .map(|(step, value)| (key_style.child_key("", &step), value))
+        .collect();
+    while let Some((key, value)) = stack.pop() {
+        let value = match annotations_field {
+            Some(_) if !value.annotations().is_empty() => {
+                let (annotations, value) = value.into_parts();
+                let annotations: Vec<Element> =
+                    annotations.iter().cloned().map(Element::symbol).collect();
+                nested_annotations.push((key.clone(), List::from(annotations).into()));
+                Element::from(value)
+            }
+            _ => value,
+        };
+        if is_flattened(&value) {
+            for (step, child) in children(&value).into_iter().rev() {
+                stack.push((key_style.child_key(&key, &step), child));
+            }
+        } else {
+            fields.push((key, value));
+        }
+    }
+    if let Some(annotations_field) = annotations_field {
+        if !nested_annotations.is_empty() {
+            let annotations = Struct::from_iter(nested_annotations);
+            fields.push((annotations_field.to_owned(), annotations.into()));
+        }
+    }
+    let annotations: Vec<Symbol> = root.annotations().iter().cloned().collect();
+    Element::from(Struct::from_iter(fields)).with_annotations(annotations)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keys_round_trip() {
+        let brackets = KeyStyle {
+            separator: ".".to_owned(),
+            index_style: IndexStyle::Brackets,
+        };
+        let separator = KeyStyle {
+            separator: "/".to_owned(),
+            index_style: IndexStyle::Separator,
+        };
+        let steps = vec![
+            PathStep::Field("a".to_owned()),
+            PathStep::Index(0),
+            PathStep::Index(12),
+            PathStep::Field("b".to_owned()),
+        ];
+        for (key_style, expected) in [(&brackets, "a[0][12].b"), (&separator, "a/0/12/b")] {
+            let key = steps
+                .iter()
+                .fold(String::new(), |key, step| key_style.child_key(&key, step));
+            assert_eq!(key, expected);
+            assert_eq!(key_style.parse_key(&key), steps);
+        }
+        assert_eq!(
+            brackets.parse_key("[1].x[y]"),
+            vec![PathStep::Index(1), PathStep::Field("x[y]".to_owned())]
+        );
+    }
+}
@@ -0,0 +1,176 @@
+use std::io::BufRead;
+
+use anyhow::{bail, Context, Result};
+use bigdecimal::num_bigint::{BigInt, Sign};
+use ciborium::value::Value as CborValue;
+use clap::{ArgMatches, Command};
+use ion_rs::{Decimal, Element, Int, IonType, List, Struct, Symbol, TimestampPrecision};
+
+use crate::commands::timestamp_conversion::timestamp_from_epoch;
+use crate::commands::to::cbor::{
+    is_type_tag, DATE_TIME_TEXT_TAG, DECIMAL_FRACTION_TAG, EPOCH_DATE_TIME_TAG, FULL_DATE_TEXT_TAG,
+    NEGATIVE_BIGNUM_TAG, POSITIVE_BIGNUM_TAG, SELF_DESCRIBED_CBOR_TAG,
+};
+use crate::commands::{CommandIo, IonCliCommand, WithIonCliArgument};
+
+pub struct FromCborCommand;
+
+impl IonCliCommand for FromCborCommand {
+    fn name(&self) -> &'static str {
+        "cbor"
+    }
+
+    fn about(&self) -> &'static str {
+        "Converts data from CBOR to Ion."
+    }
+
+    fn long_about(&self) -> Option<&'static str> {
+        Some(
+            "Converts a CBOR sequence (one or more concatenated CBOR data items) to Ion, writing \
+            each data item as a top-level value. Byte strings become blobs. Date/time tags (0 and \
+            1) and full-date tags (1004) become timestamps, bignums (tags 2 and 3) become integers and decimal fractions \
+            (tag 4) become decimals. Any other tag becomes an annotation whose text is the tag \
+            number, as does one of the tags above when it encloses a self-described CBOR tag \
+            (55799).",
+        )
+    }
+
+    fn is_stable(&self) -> bool {
+        false
+    }
+
+    fn is_porcelain(&self) -> bool {
+        false
+    }
+
+    fn configure_args(&self, command: Command) -> Command {
+        command
+            .with_input()
+            .with_output()
+            .with_format()
+            .with_ion_version()
+    }
+
+    fn run(&self, _command_path: &mut Vec<String>, args: &ArgMatches) -> Result<()> {
+        CommandIo::new(args)?.for_each_input(|output, input| {
+            let input_name = input.name().to_owned();
+            let mut source = input.into_source();
+            let mut writer = output.as_writer()?;
+            // A CBOR sequence has no delimiters or length prefix; it ends when the input does.
+            while !source.fill_buf()?.is_empty() {
+                let cbor: CborValue = ciborium::from_reader(&mut source)
+                    .with_context(|| format!("Input file '{}' was not valid CBOR.", input_name))?;
+                writer.write(from_cbor_value(cbor)?)?;
+            }
+            writer.close()?;
+            Ok(())
+        })
+    }
+}
+
+/// Converts a CBOR data item to an Ion `Element`.
+fn from_cbor_value(cbor: CborValue) -> Result<Element> {
+    let element = match cbor {
+        CborValue::Integer(i) => Int::from(i128::from(i)).into(),
+        CborValue::Bytes(bytes) => Element::blob(bytes),
+        CborValue::Float(f) => f.into(),
+        CborValue::Text(text) => text.into(),
+        CborValue::Bool(b) => b.into(),
+        CborValue::Null => Element::null(IonType::Null),
+        CborValue::Tag(tag, value) => from_tagged_value(tag, *value)?,
+        CborValue::Array(values) => List::from(
+            values
+                .into_iter()
+                .map(from_cbor_value)
+                .collect::<Result<Vec<_>>>()?,
+        )
+        .into(),
+        CborValue::Map(pairs) => {
+            let mut strukt = Struct::builder();
+            for (key, value) in pairs {
+                strukt = strukt.with_field(field_name(key)?, from_cbor_value(value)?);
+            }
+            strukt.build().into()
+        }
+        other => bail!("unsupported CBOR value: {other:?}"),
+    };
+    Ok(element)
+}
+
+fn from_tagged_value(tag: u64, value: CborValue) -> Result<Element> {
+    let element = match (tag, value) {
+        // `ion to cbor` writes an annotation that is a type's tag number this way.
+        (tag, CborValue::Tag(SELF_DESCRIBED_CBOR_TAG, value)) if is_type_tag(tag) => {
+            annotate(tag, from_cbor_value(*value)?)
+        }
+        (DATE_TIME_TEXT_TAG, CborValue::Text(text)) => Element::read_one(text.as_bytes())
+            .ok()
+            .filter(|e| e.ion_type() == IonType::Timestamp && e.annotations().is_empty())
+            .with_context(|| format!("'{text}' is not a valid date/time string"))?,
+        (FULL_DATE_TEXT_TAG, CborValue::Text(text)) => Element::read_one(text.as_bytes())
+            .ok()
+            .filter(|e| {
+                e.as_timestamp()
+                    .is_some_and(|t| t.precision() == TimestampPrecision::Day)
+                    && e.annotations().is_empty()
+                    && !text.ends_with('T')
+            })
+            .with_context(|| format!("'{text}' is not a valid full-date string"))?,
+        (EPOCH_DATE_TIME_TAG, CborValue::Integer(seconds)) => {
+            let seconds =
+                i64::try_from(seconds).with_context(|| "epoch-based date/time is out of range")?;
+            timestamp_from_epoch(seconds, 0)?.into()
+        }
+        (EPOCH_DATE_TIME_TAG, CborValue::Float(seconds)) if seconds.is_finite() => {
+            let whole_seconds = seconds.floor();
+            let nanoseconds = ((seconds - whole_seconds) * 1e9).round().min(999_999_999.0);
+            timestamp_from_epoch(whole_seconds as i64, nanoseconds as u32)?.into()
+        }
+        (POSITIVE_BIGNUM_TAG, CborValue::Bytes(bytes)) => {
+            bigint_to_int(BigInt::from_bytes_be(Sign::Plus, &bytes)).into()
+        }
+        (NEGATIVE_BIGNUM_TAG, CborValue::Bytes(bytes)) => {
+            bigint_to_int(-BigInt::from_bytes_be(Sign::Plus, &bytes) - 1).into()
+        }
+        (DECIMAL_FRACTION_TAG, CborValue::Array(parts)) => {
+            let Ok([exponent, mantissa]) = <[CborValue; 2]>::try_from(parts) else {
+                bail!("a decimal fraction must be an [exponent, mantissa] array");
+            };
+            let exponent = exponent
+                .as_integer()
+                .and_then(|e| i64::try_from(e).ok())
+                .with_context(|| "a decimal fraction's exponent must be an integer")?;
+            let mantissa = from_cbor_value(mantissa)?;
+            let mantissa = mantissa
+                .as_int()
+                .with_context(|| "a decimal fraction's mantissa must be an integer")?;
+            Decimal::new(mantissa.clone(), exponent).into()
+        }
+        // Any other tag (or a well-known tag applied to an unexpected type) is preserved as an
+        // annotation so that `ion to cbor` can restore it.
+        (tag, value) => annotate(tag, from_cbor_value(value)?),
+    };
+    Ok(element)
+}
+
+/// Adds the text of `tag` to the front of the element's annotations.
+fn annotate(tag: u64, element: Element) -> Element {
+    let annotations: Vec<Symbol> = std::iter::once(Symbol::from(tag.to_string()))
+        .chain(element.annotations().iter().cloned())
+        .collect();
+    element.with_annotations(annotations)
+}
+
+fn bigint_to_int(big: BigInt) -> Int {
+    Int::from_le_signed_bytes(&big.to_signed_bytes_le())
+}
+
+/// Converts a CBOR map key to a field name. CBOR allows keys of any type, but only text and
+/// integers have an unambiguous text representation.
+fn field_name(key: CborValue) -> Result<String> {
+    match key {
+        CborValue::Text(text) => Ok(text),
+        CborValue::Integer(i) => Ok(i128::from(i).to_string()),
+        other => bail!("unsupported CBOR map key: {other:?}"),
+    }
+}
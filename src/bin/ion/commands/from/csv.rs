@@ -0,0 +1,183 @@
+use anyhow::{Context, Result};
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use ion_rs::{Element, IonType, Struct};
+
+use crate::commands::timestamp_conversion::as_timestamp;
+use crate::commands::{CommandIo, IonCliCommand, WithIonCliArgument};
+
+pub struct FromCsvCommand;
+
+impl IonCliCommand for FromCsvCommand {
+    fn name(&self) -> &'static str {
+        "csv"
+    }
+
+    fn about(&self) -> &'static str {
+        "Converts data from CSV (or another delimiter-separated format) to Ion."
+    }
+
+    fn long_about(&self) -> Option<&'static str> {
+        Some(
+            "Converts each row of a CSV file to an Ion struct whose field names are taken from the \
+            header row. Unless `--no-infer` is specified, the type of each cell is inferred from its \
+            text: empty cells become `null`, `true`/`false` become bools, numbers become ints, \
+            decimals or floats, and timestamp-like text becomes an Ion timestamp. Any other text \
+            becomes an Ion string.",
+        )
+    }
+
+    fn is_stable(&self) -> bool {
+        false
+    }
+
+    fn is_porcelain(&self) -> bool {
+        false
+    }
+
+    fn configure_args(&self, command: Command) -> Command {
+        command
+            .arg(
+                Arg::new("delimiter")
+                    .long("delimiter")
+                    .short('d')
+                    .default_value(",")
+                    .value_parser(parse_single_byte)
+                    .help("The field delimiter. Use '\\t' for tab-separated input."),
+            )
+            .arg(
+                Arg::new("quote")
+                    .long("quote")
+                    .default_value("\"")
+                    .value_parser(parse_single_byte)
+                    .help("The character used to quote fields."),
+            )
+            .arg(
+                Arg::new("no-quoting")
+                    .long("no-quoting")
+                    .action(ArgAction::SetTrue)
+                    .help("Treat quote characters as ordinary text."),
+            )
+            .arg(
+                Arg::new("no-header")
+                    .long("no-header")
+                    .action(ArgAction::SetTrue)
+                    .help("Treat the first row as data. Fields are named 'column_1', 'column_2', etc."),
+            )
+            .arg(
+                Arg::new("no-infer")
+                    .long("no-infer")
+                    .action(ArgAction::SetTrue)
+                    .help("Convert every cell to an Ion string instead of inferring its type."),
+            )
+            .with_input()
+            .with_output()
+            .with_format()
+            .with_ion_version()
+    }
+
+    fn run(&self, _command_path: &mut Vec<String>, args: &ArgMatches) -> Result<()> {
+        let delimiter = *args.get_one::<u8>("delimiter").unwrap();
+        let quote = *args.get_one::<u8>("quote").unwrap();
+        let quoting = !args.get_flag("no-quoting");
+        let has_headers = !args.get_flag("no-header");
+        let infer_types = !args.get_flag("no-infer");
+
+        CommandIo::new(args)?.for_each_input(|output, input| {
+            let input_name = input.name().to_owned();
+            let mut csv_reader = csv::ReaderBuilder::new()
+                .delimiter(delimiter)
+                .quote(quote)
+                .quoting(quoting)
+                .has_headers(has_headers)
+                // Rows with more (or fewer) cells than the header are tolerated; see `field_name`.
+                .flexible(true)
+                .from_reader(input.into_source());
+
+            let headers: Vec<String> = if has_headers {
+                csv_reader
+                    .headers()
+                    .with_context(|| format!("could not read CSV header from '{input_name}'"))?
+                    .iter()
+                    .map(str::to_owned)
+                    .collect()
+            } else {
+                Vec::new()
+            };
+
+            let mut writer = output.as_writer()?;
+            for (row_index, record) in csv_reader.records().enumerate() {
+                let record = record.with_context(|| {
+                    format!("could not read row {} of '{input_name}'", row_index + 1)
+                })?;
+                let mut strukt = Struct::builder();
+                for (index, cell) in record.iter().enumerate() {
+                    let value = if infer_types {
+                        infer_element(cell)
+                    } else {
+                        Element::from(cell)
+                    };
+                    strukt = strukt.with_field(field_name(&headers, index), value);
+                }
+                writer.write(Element::from(strukt.build()))?;
+            }
+            writer.close()?;
+            Ok(())
+        })
+    }
+}
+
+/// Parses a command line argument that must be a single byte, allowing `\t` as a spelling of tab.
+pub(crate) fn parse_single_byte(text: &str) -> Result<u8, String> {
+    match text {
+        "\\t" | "tab" => Ok(b'\t'),
+        _ if text.len() == 1 => Ok(text.as_bytes()[0]),
+        _ => Err(format!("expected a single ASCII character, found '{text}'")),
+    }
+}
+
+/// Returns the name of the field at `index`. Cells without a corresponding header get a
+/// generated, 1-based name like `column_3`.
+fn field_name(headers: &[String], index: usize) -> String {
+    headers
+        .get(index)
+        .cloned()
+        .unwrap_or_else(|| format!("column_{}", index + 1))
+}
+
+/// Infers the Ion type of a single CSV cell from its text.
+fn infer_element(cell: &str) -> Element {
+    if cell.is_empty() {
+        return Element::null(IonType::Null);
+    }
+    if cell.eq_ignore_ascii_case("true") {
+        return Element::from(true);
+    }
+    if cell.eq_ignore_ascii_case("false") {
+        return Element::from(false);
+    }
+    if looks_numeric(cell) {
+        // Ion's own number syntax rejects ambiguous text like leading zeros ("007"), so values
+        // like zip codes or identifiers are left as strings.
+        if let Some(number) = Element::read_one(cell.as_bytes()).ok().filter(|e| {
+            matches!(
+                e.ion_type(),
+                IonType::Int | IonType::Decimal | IonType::Float
+            )
+        }) {
+            return number;
+        }
+    }
+    as_timestamp(cell).unwrap_or_else(|| Element::from(cell))
+}
+
+/// A cheap pre-check that avoids invoking the Ion parser on text that cannot be a number.
+fn looks_numeric(cell: &str) -> bool {
+    let starts_like_number = cell
+        .bytes()
+        .next()
+        .is_some_and(|b| b.is_ascii_digit() || b == b'-');
+    starts_like_number
+        && cell
+            .bytes()
+            .all(|b| b.is_ascii_digit() || matches!(b, b'-' | b'+' | b'.' | b'e' | b'E'))
+}
@@ -1,8 +1,11 @@
-use anyhow::Result;
+use std::str::FromStr;
+
+use anyhow::{bail, Context, Result};
 use clap::{arg, ArgMatches, Command};
-use ion_rs::{AnyEncoding, Reader};
+use ion_rs::{Element, IonType, List, SExp, Struct, Symbol, Value};
 
 use crate::commands::timestamp_conversion::convert_timestamps;
+use crate::commands::to::json::{ANNOTATIONS_KEY, TYPE_KEY, UNKNOWN_SYMBOL_TYPE, VALUE_KEY};
 use crate::commands::{CommandIo, IonCliCommand, WithIonCliArgument};
 use crate::transcribe::write_all_as;
 
@@ -28,6 +31,10 @@ impl IonCliCommand for FromJsonCommand {
     fn configure_args(&self, command: Command) -> Command {
         command
             .arg(arg!(-t --"detect-timestamps" "Preserve Ion timestamps when going from Ion to JSON to Ion"))
+            .arg(
+                arg!(--typed "Decode the {\"$type\": ..., \"value\": ...} objects written by 'ion to json --typed'")
+                    .conflicts_with("detect-timestamps"),
+            )
             .with_input()
             .with_output()
             .with_format()
@@ -36,14 +43,181 @@ impl IonCliCommand for FromJsonCommand {
 
     fn run(&self, _command_path: &mut Vec<String>, args: &ArgMatches) -> Result<()> {
         // Because JSON data is valid Ion, the `cat` command may be reused for converting JSON.
-        let detect_timestamps = args.get_flag("detect-timestamps");
+        let mapper: Option<fn(Element) -> Result<Element>> = if args.get_flag("typed") {
+            Some(from_typed_json)
+        } else if args.get_flag("detect-timestamps") {
+            Some(convert_timestamps)
+        } else {
+            None
+        };
 
         CommandIo::new(args)?.for_each_input(|output, input| {
-            let mut reader = Reader::new(AnyEncoding, input.into_source())?;
-            let mapper = detect_timestamps.then_some(convert_timestamps);
-            let (encoding, format) = (*output.encoding(), *output.format());
-            write_all_as(&mut reader, output, encoding, format, mapper)?;
+            let mut reader = input.into_reader()?;
+            write_all_as(&mut reader, output, mapper)?;
             Ok(())
         })
     }
 }
+
+/// Replaces each `$type` object written by `ion to json --typed` with the Ion value it represents.
+fn from_typed_json(element: Element) -> Result<Element> {
+    let element = match element.value() {
+        Value::List(list) => List::from(
+            list.elements()
+                .cloned()
+                .map(from_typed_json)
+                .collect::<Result<Vec<_>>>()?,
+        )
+        .into(),
+        Value::Struct(strukt) if strukt.get(TYPE_KEY).is_some() => decode_typed_value(strukt)?,
+        Value::Struct(strukt) => decode_struct_fields(strukt)?,
+        _ => element,
+    };
+    Ok(element)
+}
+
+fn decode_typed_value(typed: &Struct) -> Result<Element> {
+    let type_name = typed
+        .get(TYPE_KEY)
+        .and_then(Element::as_text)
+        .with_context(|| format!("'{TYPE_KEY}' must be a string"))?;
+    let value = typed
+        .get(VALUE_KEY)
+        .cloned()
+        .unwrap_or_else(|| Element::null(IonType::Null));
+
+    let element = if type_name == UNKNOWN_SYMBOL_TYPE {
+        if value.as_int().is_none() {
+            bail!("'{value}' is not a symbol ID");
+        }
+        Element::symbol(Symbol::unknown_text())
+    } else {
+        decode_value_as(parse_ion_type(type_name)?, value)?
+    };
+
+    let annotations = match typed.get(ANNOTATIONS_KEY) {
+        None => Vec::new(),
+        Some(annotations) => annotations
+            .as_sequence()
+            .with_context(|| format!("'{ANNOTATIONS_KEY}' must be a list of symbols"))?
+            .elements()
+            .map(decode_symbol)
+            .collect::<Result<Vec<_>>>()?,
+    };
+    Ok(element.with_annotations(annotations))
+}
+
+/// Decodes the `value` of a `$type` object whose type is `ion_type`.
+fn decode_value_as(ion_type: IonType, value: Element) -> Result<Element> {
+    let element = if value.is_null() {
+        Element::null(ion_type)
+    } else {
+        match (ion_type, value.value()) {
+            (IonType::Bool, Value::Bool(_))
+            | (IonType::Int, Value::Int(_))
+            | (IonType::String, Value::String(_)) => value,
+            (IonType::Int | IonType::Decimal | IonType::Timestamp, Value::String(text)) => {
+                parse_ion_text(text.text(), ion_type)?
+            }
+            (IonType::Float, Value::String(text)) => f64::from_str(text.text())
+                .with_context(|| format!("'{}' is not a valid float", text.text()))?
+                .into(),
+            (IonType::Symbol, Value::String(text)) => Element::symbol(text.text()),
+            (IonType::Blob, Value::String(text)) => Element::blob(decode_base64(text.text())?),
+            (IonType::Clob, Value::String(text)) => Element::clob(decode_base64(text.text())?),
+            (IonType::List, Value::List(_)) => from_typed_json(value)?,
+            (IonType::SExp, Value::List(list)) => SExp::from(
+                list.elements()
+                    .cloned()
+                    .map(from_typed_json)
+                    .collect::<Result<Vec<_>>>()?,
+            )
+            .into(),
+            (IonType::Struct, Value::Struct(strukt)) => decode_struct_fields(strukt)?,
+            (IonType::Struct, Value::List(pairs)) => decode_struct_pairs(pairs.elements())?,
+            (ion_type, _) => bail!("'{value}' is not a valid {ion_type} value"),
+        }
+    };
+
+    Ok(element)
+}
+
+/// Decodes a field name or annotation, which is either a string or an `unknown_symbol` object.
+fn decode_symbol(element: &Element) -> Result<Symbol> {
+    if let Some(text) = element.as_text() {
+        return Ok(text.into());
+    }
+    let is_unknown_symbol = element
+        .as_struct()
+        .and_then(|strukt| strukt.get(TYPE_KEY))
+        .and_then(Element::as_text)
+        == Some(UNKNOWN_SYMBOL_TYPE);
+    if !is_unknown_symbol {
+        bail!("'{element}' is not a string or a symbol with unknown text");
+    }
+    decode_typed_value(element.as_struct().unwrap())?
+        .as_symbol()
+        .cloned()
+        .with_context(|| format!("'{element}' is not a symbol"))
+}
+
+fn decode_struct_fields(strukt: &Struct) -> Result<Element> {
+    let mut builder = Struct::builder();
+    for (name, value) in strukt.fields() {
+        builder = builder.with_field(name.clone(), from_typed_json(value.clone())?);
+    }
+    Ok(builder.build().into())
+}
+
+/// Decodes a struct with repeated field names, which is written as a list of `[name, value]` pairs.
+fn decode_struct_pairs<'a>(pairs: impl Iterator<Item = &'a Element>) -> Result<Element> {
+    let mut builder = Struct::builder();
+    for pair in pairs {
+        let (name, value) = pair
+            .as_sequence()
+            .and_then(
+                |pair| match pair.elements().collect::<Vec<_>>().as_slice() {
+                    [name, value] => Some((*name, *value)),
+                    _ => None,
+                },
+            )
+            .context("a struct with repeated field names must be a list of [name, value] pairs")?;
+        builder = builder.with_field(decode_symbol(name)?, from_typed_json(value.clone())?);
+    }
+    Ok(builder.build().into())
+}
+
+fn parse_ion_type(type_name: &str) -> Result<IonType> {
+    let ion_type = match type_name {
+        "null" => IonType::Null,
+        "bool" => IonType::Bool,
+        "int" => IonType::Int,
+        "float" => IonType::Float,
+        "decimal" => IonType::Decimal,
+        "timestamp" => IonType::Timestamp,
+        "symbol" => IonType::Symbol,
+        "string" => IonType::String,
+        "clob" => IonType::Clob,
+        "blob" => IonType::Blob,
+        "list" => IonType::List,
+        "sexp" => IonType::SExp,
+        "struct" => IonType::Struct,
+        other => bail!("'{other}' is not an Ion type"),
+    };
+    Ok(ion_type)
+}
+
+/// Reads `text` as a single Ion value of the expected type.
+fn parse_ion_text(text: &str, expected: IonType) -> Result<Element> {
+    Element::read_one(text.as_bytes())
+        .ok()
+        .filter(|e| e.ion_type() == expected && e.annotations().is_empty())
+        .with_context(|| format!("'{text}' is not a valid Ion {expected}"))
+}
+
+fn decode_base64(text: &str) -> Result<Vec<u8>> {
+    use base64::{engine::general_purpose as base64_decoder, Engine as _};
+    base64_decoder::STANDARD
+        .decode(text)
+        .with_context(|| format!("'{text}' is not valid base64"))
+}
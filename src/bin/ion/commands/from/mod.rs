@@ -1,9 +1,17 @@
 use crate::commands::command_namespace::IonCliNamespace;
 use crate::commands::IonCliCommand;
 
+use crate::commands::from::cbor::FromCborCommand;
+use crate::commands::from::csv::FromCsvCommand;
 use crate::commands::from::json::FromJsonCommand;
+use crate::commands::from::msgpack::FromMsgPackCommand;
+use crate::commands::from::yaml::FromYamlCommand;
 
+pub mod cbor;
+pub mod csv;
 pub mod json;
+pub mod msgpack;
+pub mod yaml;
 
 pub struct FromNamespace;
 
@@ -17,6 +25,12 @@ impl IonCliNamespace for FromNamespace {
     }
 
     fn subcommands(&self) -> Vec<Box<dyn IonCliCommand>> {
-        vec![Box::new(FromJsonCommand)]
+        vec![
+            Box::new(FromCborCommand),
+            Box::new(FromCsvCommand),
+            Box::new(FromJsonCommand),
+            Box::new(FromMsgPackCommand),
+            Box::new(FromYamlCommand),
+        ]
     }
 }
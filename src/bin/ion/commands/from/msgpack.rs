@@ -0,0 +1,132 @@
+use std::io::BufRead;
+
+use anyhow::{bail, Context, Result};
+use clap::{ArgMatches, Command};
+use ion_rs::{Element, Int, IonType, List, Struct};
+use rmpv::Value as MsgPackValue;
+
+use crate::commands::timestamp_conversion::timestamp_from_epoch;
+use crate::commands::to::msgpack::TIMESTAMP_EXT_TYPE;
+use crate::commands::{CommandIo, IonCliCommand, WithIonCliArgument};
+
+pub struct FromMsgPackCommand;
+
+impl IonCliCommand for FromMsgPackCommand {
+    fn name(&self) -> &'static str {
+        "msgpack"
+    }
+
+    fn about(&self) -> &'static str {
+        "Converts data from MessagePack to Ion."
+    }
+
+    fn long_about(&self) -> Option<&'static str> {
+        Some(
+            "Converts a stream of concatenated MessagePack objects to Ion, writing each object as a \
+            top-level value as soon as it has been read. Binary values become blobs and timestamp \
+            extension values become UTC timestamps. Any other extension value becomes a blob \
+            annotated with its extension type (for example, `'5'::{{AQI=}}`).",
+        )
+    }
+
+    fn is_stable(&self) -> bool {
+        false
+    }
+
+    fn is_porcelain(&self) -> bool {
+        false
+    }
+
+    fn configure_args(&self, command: Command) -> Command {
+        command
+            .with_input()
+            .with_output()
+            .with_format()
+            .with_ion_version()
+    }
+
+    fn run(&self, _command_path: &mut Vec<String>, args: &ArgMatches) -> Result<()> {
+        CommandIo::new(args)?.for_each_input(|output, input| {
+            let input_name = input.name().to_owned();
+            let mut source = input.into_source();
+            let mut writer = output.as_writer()?;
+            // Concatenated MessagePack objects have no delimiters; the stream ends with the input.
+            while !source.fill_buf()?.is_empty() {
+                let msgpack = rmpv::decode::read_value(&mut source).with_context(|| {
+                    format!("Input file '{}' was not valid MessagePack.", input_name)
+                })?;
+                writer.write(from_msgpack_value(msgpack)?)?;
+            }
+            writer.close()?;
+            Ok(())
+        })
+    }
+}
+
+/// Converts a MessagePack value to an Ion `Element`.
+fn from_msgpack_value(msgpack: MsgPackValue) -> Result<Element> {
+    let element = match msgpack {
+        MsgPackValue::Nil => Element::null(IonType::Null),
+        MsgPackValue::Boolean(b) => b.into(),
+        MsgPackValue::Integer(i) => match i.as_i64() {
+            Some(i) => i.into(),
+            None => Int::from(i.as_u64().expect("MessagePack integers are i64 or u64")).into(),
+        },
+        MsgPackValue::F32(f) => f64::from(f).into(),
+        MsgPackValue::F64(f) => f.into(),
+        MsgPackValue::String(s) => match s.into_str() {
+            Some(text) => text.into(),
+            None => bail!("MessagePack string was not valid UTF-8"),
+        },
+        MsgPackValue::Binary(bytes) => Element::blob(bytes),
+        MsgPackValue::Array(values) => List::from(
+            values
+                .into_iter()
+                .map(from_msgpack_value)
+                .collect::<Result<Vec<_>>>()?,
+        )
+        .into(),
+        MsgPackValue::Map(pairs) => {
+            let mut strukt = Struct::builder();
+            for (key, value) in pairs {
+                strukt = strukt.with_field(field_name(key)?, from_msgpack_value(value)?);
+            }
+            strukt.build().into()
+        }
+        MsgPackValue::Ext(TIMESTAMP_EXT_TYPE, data) => decode_timestamp(&data)?,
+        MsgPackValue::Ext(ext_type, data) => {
+            Element::blob(data).with_annotations([ext_type.to_string()])
+        }
+    };
+    Ok(element)
+}
+
+/// Decodes any of the three layouts of the timestamp extension type.
+fn decode_timestamp(data: &[u8]) -> Result<Element> {
+    let (seconds, nanoseconds) = match data.len() {
+        4 => (u32::from_be_bytes(data.try_into()?) as i64, 0),
+        8 => {
+            let value = u64::from_be_bytes(data.try_into()?);
+            ((value & ((1 << 34) - 1)) as i64, (value >> 34) as u32)
+        }
+        12 => (
+            i64::from_be_bytes(data[4..].try_into()?),
+            u32::from_be_bytes(data[..4].try_into()?),
+        ),
+        length => bail!("timestamp extension value has invalid length {length}"),
+    };
+    Ok(timestamp_from_epoch(seconds, nanoseconds)?.into())
+}
+
+/// Converts a MessagePack map key to a field name. MessagePack allows keys of any type, but only
+/// scalars have an unambiguous text representation.
+fn field_name(key: MsgPackValue) -> Result<String> {
+    match key {
+        MsgPackValue::String(s) => s
+            .into_str()
+            .context("MessagePack map key was not valid UTF-8"),
+        MsgPackValue::Integer(i) => Ok(i.to_string()),
+        MsgPackValue::Boolean(b) => Ok(b.to_string()),
+        other => bail!("unsupported MessagePack map key: {other}"),
+    }
+}
@@ -0,0 +1,213 @@
+use anyhow::{bail, Context, Result};
+use clap::{ArgMatches, Command};
+use ion_rs::{Element, Int, IonType, List, SExp, Struct, Symbol};
+use serde::Deserialize;
+use serde_yaml::value::{Tag, TaggedValue};
+use serde_yaml::Value as YamlValue;
+
+use crate::commands::to::yaml::{
+    ANNOTATED_TAG, BLOB_TAG, CLOB_TAG, DECIMAL_TAG, INT_TAG, NULL_TAG, SEXP_TAG, STRUCT_TAG,
+    SYMBOL_TAG, TIMESTAMP_TAG, UNKNOWN_SYMBOL_TAG,
+};
+use crate::commands::{CommandIo, IonCliCommand, WithIonCliArgument};
+
+pub struct FromYamlCommand;
+
+impl IonCliCommand for FromYamlCommand {
+    fn name(&self) -> &'static str {
+        "yaml"
+    }
+
+    fn about(&self) -> &'static str {
+        "Converts data from YAML to Ion."
+    }
+
+    fn long_about(&self) -> Option<&'static str> {
+        Some(
+            "Converts data from YAML to Ion, writing each document in a multi-document YAML stream \
+            as a separate top-level value. YAML tags become Ion annotations, except for the tags in \
+            the `ion/` namespace written by `ion to yaml`, which are decoded as the Ion types they \
+            represent.",
+        )
+    }
+
+    fn is_stable(&self) -> bool {
+        false
+    }
+
+    fn is_porcelain(&self) -> bool {
+        false
+    }
+
+    fn configure_args(&self, command: Command) -> Command {
+        command
+            .with_input()
+            .with_output()
+            .with_format()
+            .with_ion_version()
+    }
+
+    fn run(&self, _command_path: &mut Vec<String>, args: &ArgMatches) -> Result<()> {
+        CommandIo::new(args)?.for_each_input(|output, input| {
+            let input_name = input.name().to_owned();
+            let mut writer = output.as_writer()?;
+            for document in serde_yaml::Deserializer::from_reader(input.into_source()) {
+                let mut yaml = YamlValue::deserialize(document)
+                    .with_context(|| format!("Input file '{}' was not valid YAML.", input_name))?;
+                // Resolve `<<` merge keys so that the Ion data reflects what YAML readers see.
+                yaml.apply_merge()?;
+                writer.write(from_yaml_value(yaml)?)?;
+            }
+            writer.close()?;
+            Ok(())
+        })
+    }
+}
+
+/// Converts a YAML value to an Ion `Element`.
+fn from_yaml_value(yaml: YamlValue) -> Result<Element> {
+    let element = match yaml {
+        YamlValue::Null => Element::null(IonType::Null),
+        YamlValue::Bool(b) => b.into(),
+        YamlValue::Number(n) => {
+            if let Some(i) = n.as_i64() {
+                i.into()
+            } else if let Some(u) = n.as_u64() {
+                Int::from(u).into()
+            } else {
+                n.as_f64().expect("YAML numbers are i64, u64 or f64").into()
+            }
+        }
+        YamlValue::String(s) => s.into(),
+        YamlValue::Sequence(values) => List::from(from_yaml_values(values)?).into(),
+        YamlValue::Mapping(mapping) => {
+            let mut strukt = Struct::builder();
+            for (name, value) in mapping {
+                strukt = strukt.with_field(symbol(name)?, from_yaml_value(value)?);
+            }
+            strukt.build().into()
+        }
+        YamlValue::Tagged(tagged) => from_tagged_value(*tagged)?,
+    };
+    Ok(element)
+}
+
+fn from_yaml_values(values: Vec<YamlValue>) -> Result<Vec<Element>> {
+    values.into_iter().map(from_yaml_value).collect()
+}
+
+fn from_tagged_value(tagged: TaggedValue) -> Result<Element> {
+    let TaggedValue { tag, value } = tagged;
+    let element = if tag == ANNOTATED_TAG {
+        let YamlValue::Mapping(mut mapping) = value else {
+            bail!("!{ANNOTATED_TAG} must be applied to a mapping");
+        };
+        let annotations = match mapping.remove("annotations") {
+            Some(YamlValue::Sequence(annotations)) => annotations
+                .into_iter()
+                .map(symbol)
+                .collect::<Result<Vec<_>>>()?,
+            _ => bail!("!{ANNOTATED_TAG} requires an 'annotations' sequence"),
+        };
+        let value = mapping
+            .remove("value")
+            .with_context(|| format!("!{ANNOTATED_TAG} requires a 'value'"))?;
+        from_yaml_value(value)?.with_annotations(annotations)
+    } else if tag == NULL_TAG {
+        let ion_type = expect_text(&tag, &value)?;
+        Element::read_one(format!("null.{ion_type}"))
+            .ok()
+            .filter(|e| e.is_null() && e.annotations().is_empty())
+            .with_context(|| format!("'{ion_type}' is not an Ion type"))?
+    } else if tag == INT_TAG {
+        parse_ion_text(expect_text(&tag, &value)?, IonType::Int)?
+    } else if tag == DECIMAL_TAG {
+        parse_ion_text(expect_text(&tag, &value)?, IonType::Decimal)?
+    } else if tag == TIMESTAMP_TAG {
+        parse_ion_text(expect_text(&tag, &value)?, IonType::Timestamp)?
+    } else if tag == SYMBOL_TAG {
+        Element::symbol(expect_text(&tag, &value)?)
+    } else if tag == UNKNOWN_SYMBOL_TAG {
+        Element::symbol(unknown_symbol(&value)?)
+    } else if tag == BLOB_TAG {
+        Element::blob(decode_base64(&tag, &value)?)
+    } else if tag == CLOB_TAG {
+        Element::clob(decode_base64(&tag, &value)?)
+    } else if tag == SEXP_TAG {
+        let YamlValue::Sequence(values) = value else {
+            bail!("!{SEXP_TAG} must be applied to a sequence");
+        };
+        SExp::from(from_yaml_values(values)?).into()
+    } else if tag == STRUCT_TAG {
+        let YamlValue::Sequence(pairs) = value else {
+            bail!("!{STRUCT_TAG} must be applied to a sequence of [name, value] pairs");
+        };
+        let mut strukt = Struct::builder();
+        for pair in pairs {
+            let YamlValue::Sequence(pair) = pair else {
+                bail!("!{STRUCT_TAG} must be applied to a sequence of [name, value] pairs");
+            };
+            let Ok([name, value]) = <[YamlValue; 2]>::try_from(pair) else {
+                bail!("!{STRUCT_TAG} must be applied to a sequence of [name, value] pairs");
+            };
+            strukt = strukt.with_field(symbol(name)?, from_yaml_value(value)?);
+        }
+        strukt.build().into()
+    } else {
+        // Any other tag is treated as an annotation on the tagged value.
+        let annotation = tag.to_string().trim_start_matches('!').to_owned();
+        from_yaml_value(value)?.with_annotations([annotation])
+    };
+
+    Ok(element)
+}
+
+/// Returns the text of a YAML scalar that is expected to hold a string.
+fn expect_text<'a>(tag: &Tag, value: &'a YamlValue) -> Result<&'a str> {
+    value
+        .as_str()
+        .with_context(|| format!("{tag} must be applied to a string"))
+}
+
+/// Decodes the base64 text of a blob or clob, ignoring any line breaks YAML may have inserted.
+fn decode_base64(tag: &Tag, value: &YamlValue) -> Result<Vec<u8>> {
+    use base64::{engine::general_purpose as base64_decoder, Engine as _};
+    let text: String = expect_text(tag, value)?
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect();
+    base64_decoder::STANDARD
+        .decode(text)
+        .with_context(|| format!("{tag} value was not valid base64"))
+}
+
+/// Reads `text` as a single Ion value of the expected type.
+fn parse_ion_text(text: &str, expected: IonType) -> Result<Element> {
+    Element::read_one(text.as_bytes())
+        .ok()
+        .filter(|e| e.ion_type() == expected && e.annotations().is_empty())
+        .with_context(|| format!("'{text}' is not a valid Ion {expected}"))
+}
+
+/// Converts a YAML mapping key (or annotation) to a symbol. YAML allows keys of any type, but only
+/// scalars have an unambiguous text representation.
+fn symbol(key: YamlValue) -> Result<Symbol> {
+    match key {
+        YamlValue::String(s) => Ok(s.into()),
+        YamlValue::Number(n) => Ok(n.to_string().into()),
+        YamlValue::Bool(b) => Ok(b.to_string().into()),
+        YamlValue::Null => Ok("null".into()),
+        YamlValue::Tagged(tagged) if tagged.tag == UNKNOWN_SYMBOL_TAG => {
+            unknown_symbol(&tagged.value)
+        }
+        other => bail!("unsupported YAML mapping key or annotation: {other:?}"),
+    }
+}
+
+/// Returns the symbol with unknown text that an `!ion/unknown_symbol` represents.
+fn unknown_symbol(value: &YamlValue) -> Result<Symbol> {
+    if value.as_u64().is_none() {
+        bail!("!{UNKNOWN_SYMBOL_TAG} must be applied to a symbol ID");
+    }
+    Ok(Symbol::unknown_text())
+}
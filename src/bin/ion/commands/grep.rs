@@ -0,0 +1,424 @@
+use std::cmp::Ordering;
+
+use anyhow::{bail, Context, Result};
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use ion_rs::{AnyEncoding, Decoder, Element, HasRange, IonResult, LazyValue, Reader, ValueRef};
+use regex::{Regex, RegexBuilder};
+
+use crate::commands::ordering::compare_values;
+use crate::commands::path::IonPath;
+use crate::commands::{CommandIo, IonCliCommand, WithIonCliArgument};
+
+pub struct GrepCommand;
+
+impl IonCliCommand for GrepCommand {
+    fn name(&self) -> &'static str {
+        "grep"
+    }
+
+    fn about(&self) -> &'static str {
+        "Prints the top-level values that match a predicate."
+    }
+
+    fn long_about(&self) -> Option<&'static str> {
+        Some(
+            "Prints the top-level values of each input that match a predicate. Predicates test the \
+            value at a path (like `.user.id`, or `.` for the whole value):\n\
+            \n  \
+            .status == error     the value is equal to an Ion value (also !=, <, <=, > and >=)\n  \
+            .user.id =~ /ab+c/i  the value is a string or symbol that matches a regular expression\n  \
+            has(.x)              the value exists\n  \
+            .x @ deprecated      the value has an annotation (`@ name` tests the whole value)\n\
+            \n\
+            and can be combined with `and`, `or`, `not` and parentheses. Comparisons ignore the \
+            types and annotations of the values being compared, so `.a == 1` matches `{a: 1.0}` \
+            and `.a == x` matches `{a: \"x\"}`; values of different kinds (like numbers and text) \
+            are ordered as by `ion sort`. A comparison, regular expression or annotation test of \
+            a value that does not exist is false. Values are read lazily, so only the parts of \
+            each value that a predicate tests are decoded.",
+        )
+    }
+
+    fn is_stable(&self) -> bool {
+        false
+    }
+
+    fn is_porcelain(&self) -> bool {
+        false
+    }
+
+    fn configure_args(&self, command: Command) -> Command {
+        command
+            .arg(
+                Arg::new("predicate")
+                    .required(true)
+                    .help("The predicate that values must match, like `.status == error`."),
+            )
+            .arg(
+                Arg::new("invert-match")
+                    .long("invert-match")
+                    .short('v')
+                    .action(ArgAction::SetTrue)
+                    .help("Print the values that do not match the predicate."),
+            )
+            .arg(
+                Arg::new("count")
+                    .long("count")
+                    .short('c')
+                    .action(ArgAction::SetTrue)
+                    .help("Print the number of matching values in each input instead."),
+            )
+            .arg(
+                Arg::new("files-with-matches")
+                    .long("files-with-matches")
+                    .short('l')
+                    .action(ArgAction::SetTrue)
+                    .conflicts_with("count")
+                    .help("Print the name of each input that has a matching value instead."),
+            )
+            .with_input()
+            .with_output()
+            .with_format()
+            .with_ion_version()
+            .with_syntax_highlighting()
+    }
+
+    fn run(&self, _command_path: &mut Vec<String>, args: &ArgMatches) -> Result<()> {
+        let predicate = Predicate::parse(args.get_one::<String>("predicate").unwrap())?;
+        let invert = args.get_flag("invert-match");
+        let count_only = args.get_flag("count");
+        let names_only = args.get_flag("files-with-matches");
+
+        CommandIo::new(args)?.for_each_input(|output, input| {
+            let input_name = input.name().to_owned();
+            let mut reader = input
+                .into_reader()
+                .with_context(|| format!("Input file '{}' was not valid Ion.", input_name))?;
+            let mut writer = output.as_writer()?;
+            let mut count = 0i64;
+            while let Some(value) = reader.next()? {
+                if predicate.matches(value)? == invert {
+                    continue;
+                }
+                count += 1;
+                if names_only {
+                    writer.write(Element::string(input_name.as_str()))?;
+                    break;
+                }
+                if !count_only {
+                    writer.write(value)?;
+                }
+            }
+            if count_only {
+                writer.write(count)?;
+            }
+            writer.close()?;
+            Ok(())
+        })
+    }
+}
+
+/// A test of a top-level value.
+#[derive(Debug)]
+enum Predicate {
+    Exists(IonPath),
+    Compare(IonPath, Comparison, Element),
+    Matches(IonPath, Regex),
+    Annotated(IonPath, String),
+    Not(Box<Predicate>),
+    And(Box<Predicate>, Box<Predicate>),
+    Or(Box<Predicate>, Box<Predicate>),
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum Comparison {
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+impl Comparison {
+    /// The operators in the order they must be tried when parsing, so that `<=` is not read as `<`.
+    const OPERATORS: [(&'static str, Comparison); 6] = [
+        ("==", Comparison::Equal),
+        ("!=", Comparison::NotEqual),
+        ("<=", Comparison::LessOrEqual),
+        (">=", Comparison::GreaterOrEqual),
+        ("<", Comparison::Less),
+        (">", Comparison::Greater),
+    ];
+
+    fn holds_for(&self, ordering: Ordering) -> bool {
+        match self {
+            Comparison::Equal => ordering.is_eq(),
+            Comparison::NotEqual => ordering.is_ne(),
+            Comparison::Less => ordering.is_lt(),
+            Comparison::LessOrEqual => ordering.is_le(),
+            Comparison::Greater => ordering.is_gt(),
+            Comparison::GreaterOrEqual => ordering.is_ge(),
+        }
+    }
+}
+
+impl Predicate {
+    fn parse(text: &str) -> Result<Self> {
+        let mut parser = PredicateParser { rest: text };
+        let predicate = parser
+            .parse_or()
+            .with_context(|| format!("invalid predicate '{text}'"))?;
+        let rest = parser.rest.trim_start();
+        if !rest.is_empty() {
+            bail!("invalid predicate '{text}': unexpected '{rest}'");
+        }
+        Ok(predicate)
+    }
+
+    fn matches<D: Decoder>(&self, value: LazyValue<D>) -> IonResult<bool> {
+        let matches = match self {
+            Predicate::Exists(path) => path.select_lazy(value)?.is_some(),
+            Predicate::Compare(path, comparison, expected) => match path.select_lazy(value)? {
+                Some(actual) => {
+                    let actual = Element::try_from(actual)?;
+                    comparison.holds_for(compare_values(&actual, expected))
+                }
+                None => false,
+            },
+            Predicate::Matches(path, regex) => match path.select_lazy(value)? {
+                Some(actual) => match actual.read()? {
+                    ValueRef::String(text) => regex.is_match(text.text()),
+                    ValueRef::Symbol(symbol) => symbol.text().is_some_and(|t| regex.is_match(t)),
+                    _ => false,
+                },
+                None => false,
+            },
+            Predicate::Annotated(path, annotation) => match path.select_lazy(value)? {
+                Some(actual) => {
+                    let mut found = false;
+                    for symbol in actual.annotations() {
+                        if symbol?.text() == Some(annotation.as_str()) {
+                            found = true;
+                            break;
+                        }
+                    }
+                    found
+                }
+                None => false,
+            },
+            Predicate::Not(predicate) => !predicate.matches(value)?,
+            Predicate::And(left, right) => left.matches(value)? && right.matches(value)?,
+            Predicate::Or(left, right) => left.matches(value)? || right.matches(value)?,
+        };
+        Ok(matches)
+    }
+}
+
+/// A recursive descent parser for predicates. `or` binds less tightly than `and`, which binds
+/// less tightly than `not`.
+struct PredicateParser<'a> {
+    rest: &'a str,
+}
+
+impl PredicateParser<'_> {
+    fn parse_or(&mut self) -> Result<Predicate> {
+        let mut predicate = self.parse_and()?;
+        while self.eat_keyword("or") {
+            predicate = Predicate::Or(Box::new(predicate), Box::new(self.parse_and()?));
+        }
+        Ok(predicate)
+    }
+
+    fn parse_and(&mut self) -> Result<Predicate> {
+        let mut predicate = self.parse_unary()?;
+        while self.eat_keyword("and") {
+            predicate = Predicate::And(Box::new(predicate), Box::new(self.parse_unary()?));
+        }
+        Ok(predicate)
+    }
+
+    fn parse_unary(&mut self) -> Result<Predicate> {
+        if self.eat_keyword("not") {
+            return Ok(Predicate::Not(Box::new(self.parse_unary()?)));
+        }
+        if self.eat("(") {
+            let predicate = self.parse_or()?;
+            if !self.eat(")") {
+                bail!("expected ')'");
+            }
+            return Ok(predicate);
+        }
+        if self.eat("has(") {
+            let path = self.parse_path()?;
+            if !self.eat(")") {
+                bail!("expected ')' after the path in has(...)");
+            }
+            return Ok(Predicate::Exists(path));
+        }
+        if self.eat("@") {
+            return Ok(Predicate::Annotated(IonPath::default(), self.parse_name()?));
+        }
+        let path = self.parse_path()?;
+        if self.eat("@") {
+            return Ok(Predicate::Annotated(path, self.parse_name()?));
+        }
+        if self.eat("=~") {
+            return Ok(Predicate::Matches(path, self.parse_regex()?));
+        }
+        for (operator, comparison) in Comparison::OPERATORS {
+            if self.eat(operator) {
+                return Ok(Predicate::Compare(path, comparison, self.parse_value()?));
+            }
+        }
+        bail!("expected an operator after '{path}', like '==' or '=~'")
+    }
+
+    /// Skips whitespace and then `token`, if it is next.
+    fn eat(&mut self, token: &str) -> bool {
+        match self.rest.trim_start().strip_prefix(token) {
+            Some(rest) => {
+                self.rest = rest;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Like [`eat`](Self::eat), but only if the keyword is not the beginning of a longer word.
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        let Some(rest) = self.rest.trim_start().strip_prefix(keyword) else {
+            return false;
+        };
+        if rest.starts_with(|c: char| c.is_alphanumeric() || c == '_') {
+            return false;
+        }
+        self.rest = rest;
+        true
+    }
+
+    fn parse_path(&mut self) -> Result<IonPath> {
+        let (path, rest) = IonPath::parse_prefix(self.rest.trim_start())?;
+        self.rest = rest;
+        Ok(path)
+    }
+
+    /// Reads the Ion value at the start of the remaining text.
+    fn parse_value(&mut self) -> Result<Element> {
+        let text = self.rest.trim_start();
+        let mut reader = Reader::new(AnyEncoding, text)?;
+        let value = reader.next()?.context("expected a value")?;
+        let end = value
+            .raw()
+            .context("expected an encoded value")?
+            .range()
+            .end;
+        let element = Element::try_from(value)?;
+        self.rest = &text[end..];
+        Ok(element)
+    }
+
+    /// Reads an annotation, written as a symbol or string.
+    fn parse_name(&mut self) -> Result<String> {
+        let name = self.parse_value()?;
+        match name.as_text() {
+            Some(text) if name.annotations().is_empty() => Ok(text.to_owned()),
+            _ => bail!("expected an annotation, found '{name}'"),
+        }
+    }
+
+    /// Reads a regular expression written like `/ab+c/`, optionally followed by `i` to ignore
+    /// case. A `/` within the expression is written as `\/`.
+    fn parse_regex(&mut self) -> Result<Regex> {
+        let text = self.rest.trim_start();
+        let body = text
+            .strip_prefix('/')
+            .context("expected a regular expression like /abc/")?;
+        let mut pattern = String::new();
+        let mut chars = body.char_indices();
+        let end = loop {
+            match chars.next() {
+                Some((_, '\\')) => match chars.next() {
+                    Some((_, '/')) => pattern.push('/'),
+                    Some((_, c)) => {
+                        pattern.push('\\');
+                        pattern.push(c);
+                    }
+                    None => bail!("unterminated regular expression"),
+                },
+                Some((i, '/')) => break i + 1,
+                Some((_, c)) => pattern.push(c),
+                None => bail!("unterminated regular expression"),
+            }
+        };
+        let mut rest = &body[end..];
+        let ignore_case = match rest.strip_prefix('i') {
+            Some(after_flag) => {
+                rest = after_flag;
+                true
+            }
+            None => false,
+        };
+        self.rest = rest;
+        Ok(RegexBuilder::new(&pattern)
+            .case_insensitive(ignore_case)
+            .build()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches(predicate: &str, value: &str) -> bool {
+        let predicate = Predicate::parse(predicate).unwrap();
+        let mut reader = Reader::new(AnyEncoding, value).unwrap();
+        predicate.matches(reader.next().unwrap().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn predicates() {
+        let value = r#"event::{status: error, code: 503, user: {id: "xABBCy"}, tags: [a, b]}"#;
+        for (predicate, expected) in [
+            (".status == error", true),
+            (".status == \"error\"", true),
+            (".status != error", false),
+            (".code >= 500", true),
+            (".code < 503.0", false),
+            (".user.id =~ /ab+c/", false),
+            (".user.id =~ /ab+c/i", true),
+            ("has(.tags[1])", true),
+            ("has(.tags[2])", false),
+            ("@event", true),
+            ("@ 'other'", false),
+            (".missing == 1", false),
+            ("not .missing == 1", true),
+            (".code == 503 and (.status == ok or .tags[0] == a)", true),
+            (".code == 1 or .code == 2", false),
+            ("has(.code)and.code==503", true),
+        ] {
+            assert_eq!(matches(predicate, value), expected, "{predicate}");
+        }
+    }
+
+    #[test]
+    fn parse_rejects_malformed_predicates() {
+        for predicate in [
+            "",
+            "status == error",
+            ".a",
+            ".a == ",
+            ".a =~ abc",
+            ".a =~ /abc",
+            "(.a == 1",
+            ".a == 1 b",
+            "has(.a",
+            ".a @ x::y",
+        ] {
+            assert!(
+                Predicate::parse(predicate).is_err(),
+                "{predicate} should be rejected"
+            );
+        }
+    }
+}
@@ -13,7 +13,7 @@ use std::io::Write;
 macro_rules! supported_hash_functions {
     ($($name:literal => $hash:ident),+$(,)?) => {
         #[derive(Copy, Clone, PartialEq, Eq, Default, Debug)]
-        enum DigestType {
+        pub(crate) enum DigestType {
             #[default]
             $($hash),+
         }
@@ -22,7 +22,7 @@ macro_rules! supported_hash_functions {
                 $(DigestType::$hash),+
             ];
 
-            fn hash_it(&self, element: &Element) -> IonResult<Vec<u8>> {
+            pub(crate) fn hash_it(&self, element: &Element) -> IonResult<Vec<u8>> {
                 match &self {
                     $(DigestType::$hash => Ok($hash::hash_element(&element)?.to_vec()),)+
                 }
@@ -90,7 +90,7 @@ impl IonCliCommand for HashCommand {
 
     fn run(&self, _command_path: &mut Vec<String>, args: &ArgMatches) -> Result<()> {
         CommandIo::new(args)?.for_each_input(|output, input| {
-            let mut reader = Reader::new(AnyEncoding, input.into_source())?;
+            let mut reader = input.into_reader()?;
 
             let hasher = if let Some(hasher) = args.get_one::<DigestType>("hash") {
                 hasher
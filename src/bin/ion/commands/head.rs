@@ -1,6 +1,6 @@
 use anyhow::Result;
 use clap::{value_parser, Arg, ArgMatches, Command};
-use ion_rs::{AnyEncoding, Element, Reader};
+use ion_rs::Element;
 
 use crate::commands::{CommandIo, IonCliCommand, WithIonCliArgument};
 use crate::transcribe::write_n_as;
@@ -52,9 +52,8 @@ impl IonCliCommand for HeadCommand {
 
         let transform = None::<fn(Element) -> Result<Element>>;
         CommandIo::new(args)?.for_each_input(|output, input| {
-            let mut reader = Reader::new(AnyEncoding, input.into_source())?;
-            let (encoding, format) = (*output.encoding(), *output.format());
-            write_n_as(&mut reader, output, encoding, format, num_values, transform)?;
+            let mut reader = input.into_reader()?;
+            write_n_as(&mut reader, output, num_values, transform)?;
             Ok(())
         })
     }
@@ -2,6 +2,7 @@ use std::fmt::Display;
 use std::io::{Cursor, Write};
 use std::str::FromStr;
 
+use crate::catalog::CommandCatalog;
 use crate::commands::{CommandIo, IonCliCommand, WithIonCliArgument};
 // The `inspect` command uses the `termcolor` crate to colorize its text when STDOUT is a TTY.
 use crate::hex_reader::HexReader;
@@ -151,6 +152,7 @@ impl IonCliCommand for InspectCommand {
                     inspect_input(
                         &byte_string,
                         IonStream::new(HexReader::from(Cursor::new(byte_string.clone()))),
+                        &command_io.catalog,
                         output,
                         bytes_to_skip,
                         limit_bytes,
@@ -162,11 +164,13 @@ impl IonCliCommand for InspectCommand {
 
         command_io.for_each_input(|output, input| {
             let input_name = input.name().to_owned();
+            let catalog = input.catalog().clone();
             let input = input.into_source();
             if read_as_hex_string {
                 inspect_input(
                     &input_name,
                     HexReader::from(input),
+                    &catalog,
                     output,
                     bytes_to_skip,
                     limit_bytes,
@@ -176,6 +180,7 @@ impl IonCliCommand for InspectCommand {
                 inspect_input(
                     &input_name,
                     input,
+                    &catalog,
                     output,
                     bytes_to_skip,
                     limit_bytes,
@@ -191,12 +196,13 @@ impl IonCliCommand for InspectCommand {
 fn inspect_input<Input: IonInput>(
     input_name: &str,
     input: Input,
+    catalog: &CommandCatalog,
     output: &mut CommandOutput,
     bytes_to_skip: usize,
     limit_bytes: usize,
     hide_expansion: bool,
 ) -> Result<()> {
-    let mut reader = SystemReader::new(AnyEncoding, input);
+    let mut reader = catalog.system_reader(input);
     let mut inspector = IonInspector::new(output, bytes_to_skip, limit_bytes, hide_expansion)?;
     // This inspects all values at the top level, recursing as necessary.
     inspector
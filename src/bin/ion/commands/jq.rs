@@ -8,9 +8,7 @@ use crate::output::{CommandOutput, CommandOutputWriter};
 use anyhow::bail;
 use bigdecimal::ToPrimitive;
 use clap::{arg, ArgMatches, Command};
-use ion_rs::{
-    AnyEncoding, Element, ElementReader, IonData, IonType, List, Reader, Sequence, Value,
-};
+use ion_rs::{Element, ElementReader, IonData, IonType, List, Sequence, Value};
 use itertools::Itertools;
 use jaq_core::path::Opt;
 use jaq_core::val::Range;
@@ -95,7 +93,7 @@ fn evaluate_jq_expr(
     filter: &Filter<Native<JaqElement>>,
     slurp: bool,
 ) -> anyhow::Result<()> {
-    let mut reader = Reader::new(AnyEncoding, input.into_source())?;
+    let mut reader = input.into_reader()?;
     let mut writer = output.as_writer()?;
 
     if slurp {
@@ -0,0 +1,175 @@
+use std::io::{BufRead, Write};
+
+use anyhow::{bail, Context, Result};
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use ion_rs::{
+    AnyEncoding, Element, ElementReader, HasSpan, IonEncoding, IonInput, RawVersionMarker,
+    SystemReader, SystemStreamItem,
+};
+
+use crate::commands::ordering::{Run, SortOrder};
+use crate::commands::path::IonPath;
+use crate::commands::{CommandIo, IonCliCommand, WithIonCliArgument};
+use crate::input::CommandInput;
+use crate::output::CommandOutput;
+use crate::transcribe::write_all_as;
+
+/// The Ion version marker that begins a binary Ion 1.0 stream.
+const BINARY_1_0_IVM: [u8; 4] = [0xE0, 0x01, 0x00, 0xEA];
+
+pub struct MergeCommand;
+
+impl IonCliCommand for MergeCommand {
+    fn name(&self) -> &'static str {
+        "merge"
+    }
+
+    fn about(&self) -> &'static str {
+        "Concatenates or interleaves the top-level values of multiple inputs."
+    }
+
+    fn long_about(&self) -> Option<&'static str> {
+        Some(
+            "Writes the top-level values of each input in turn. When both an input and the output \
+            are binary Ion 1.0, the input's encoded bytes are copied to the output without being \
+            decoded and re-encoded: its version markers, symbol tables and values are copied \
+            as-is, so each input's values are read using its own symbol tables. Other inputs \
+            (text, Ion 1.1 or binary input for a text output) are re-encoded, each beginning with \
+            a new symbol table where the output format has one. With `--sorted`, the inputs must \
+            each already be sorted (as by `ion sort` with the same `--key` and `--reverse` \
+            options), and their values are interleaved so that the output is sorted too. \
+            Interleaved values are always re-encoded.",
+        )
+    }
+
+    fn is_stable(&self) -> bool {
+        false
+    }
+
+    fn is_porcelain(&self) -> bool {
+        false
+    }
+
+    fn configure_args(&self, command: Command) -> Command {
+        command
+            .arg(
+                Arg::new("sorted")
+                    .long("sorted")
+                    .short('s')
+                    .action(ArgAction::SetTrue)
+                    .help("Interleave the values of the sorted inputs so the output is sorted."),
+            )
+            .arg(
+                Arg::new("key")
+                    .long("key")
+                    .short('k')
+                    .action(ArgAction::Append)
+                    .value_name("PATH")
+                    .requires("sorted")
+                    .help("A path to the value the inputs are sorted by. May be repeated."),
+            )
+            .arg(
+                Arg::new("reverse")
+                    .long("reverse")
+                    .short('r')
+                    .action(ArgAction::SetTrue)
+                    .requires("sorted")
+                    .help("The inputs are sorted in descending order."),
+            )
+            .with_input()
+            .with_output()
+            .with_format()
+            .with_ion_version()
+    }
+
+    fn run(&self, _command_path: &mut Vec<String>, args: &ArgMatches) -> Result<()> {
+        if !args.get_flag("sorted") {
+            return CommandIo::new(args)?.for_each_input(concatenate);
+        }
+        let keys = args
+            .get_many::<String>("key")
+            .unwrap_or_default()
+            .map(|key| IonPath::parse(key))
+            .collect::<Result<Vec<_>>>()?;
+        let order = SortOrder::new(keys, args.get_flag("reverse"));
+
+        CommandIo::new(args)?.for_all_inputs(|output, inputs| {
+            let runs = inputs
+                .into_iter()
+                .map(|input| {
+                    let input_name = input.name().to_owned();
+                    let mut reader = input.into_reader().with_context(|| {
+                        format!("Input file '{}' was not valid Ion.", input_name)
+                    })?;
+                    let run: Run = Box::new(std::iter::from_fn(move || {
+                        reader.read_next_element().transpose()
+                    }));
+                    Ok(run)
+                })
+                .collect::<Result<Vec<_>>>()?;
+            let mut writer = output.as_writer()?;
+            order.merge(runs, |element: Element| {
+                writer.write(element)?;
+                Ok(())
+            })?;
+            writer.close()?;
+            Ok(())
+        })
+    }
+}
+
+/// Writes all of the values in `input` to `output`, copying their encoded bytes if possible.
+fn concatenate(output: &mut CommandOutput, input: CommandInput) -> Result<()> {
+    let input_name = input.name().to_owned();
+    let catalog = input.catalog().clone();
+    let mut source = input.into_source();
+    // If the first read returns fewer bytes than the version marker, the input is re-encoded,
+    // which is slower but produces the same values.
+    let is_binary_1_0 = source.fill_buf()?.starts_with(&BINARY_1_0_IVM);
+    // Copied values keep their original symbol IDs, which would be wrong if the output imports a
+    // shared symbol table.
+    let imports = output.spec().import.is_some();
+    if is_binary_1_0 && *output.encoding() == IonEncoding::Binary_1_0 && !imports {
+        let mut reader = catalog.system_reader(source);
+        return splice(&mut reader, output)
+            .with_context(|| format!("Input file '{}' was not valid Ion.", input_name));
+    }
+    let mut reader = catalog
+        .reader(source)
+        .with_context(|| format!("Input file '{}' was not valid Ion.", input_name))?;
+    write_all_as(&mut reader, output, None::<fn(Element) -> Result<Element>>)?;
+    Ok(())
+}
+
+/// Copies the encoded bytes of every version marker, symbol table and value in a binary Ion 1.0
+/// stream to the output. Because the stream begins with a version marker, which resets the symbol
+/// table, its values are read using only its own symbol tables.
+fn splice(
+    reader: &mut SystemReader<AnyEncoding, impl IonInput>,
+    output: &mut CommandOutput,
+) -> Result<()> {
+    loop {
+        match reader.next_item()? {
+            SystemStreamItem::VersionMarker(marker) => {
+                if marker.major_minor() != (1, 0) {
+                    bail!("cannot copy a stream that switches to Ion 1.1");
+                }
+                output.write_all(marker.span().bytes())?;
+            }
+            SystemStreamItem::SymbolTable(symtab) => {
+                let Some(raw_value) = symtab.as_value().raw() else {
+                    bail!("found an ephemeral symbol table, which is not yet supported")
+                };
+                output.write_all(raw_value.span().bytes())?;
+            }
+            SystemStreamItem::Value(value) => {
+                let Some(raw_value) = value.raw() else {
+                    bail!("found an ephemeral value, which is not yet supported")
+                };
+                output.write_all(raw_value.span().bytes())?;
+            }
+            SystemStreamItem::EndOfStream(_) => return Ok(()),
+            _ => unreachable!("#[non_exhaustive] enum, current variants covered"),
+        }
+    }
+}
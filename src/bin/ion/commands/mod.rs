@@ -1,12 +1,14 @@
+use crate::catalog::CommandCatalog;
 use crate::file_writer::FileWriter;
 use crate::input::CommandInput;
 use crate::output::{CommandOutput, CommandOutputSpec, HighlightedStreamWriter};
 use anyhow::Result;
 use anyhow::{bail, Context};
-use clap::builder::ValueParser;
+use clap::builder::{ArgPredicate, ValueParser};
 use clap::{crate_authors, crate_version, Arg, ArgAction, ArgMatches, Command as ClapCommand};
-use ion_rs::{IonEncoding, TextFormat};
+use ion_rs::{IonEncoding, SharedSymbolTable, TextFormat};
 use std::fs::File;
+use std::rc::Rc;
 
 /// Local replacement for the `Format` enum that was removed from ion-rs in 1.0.0.
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
@@ -21,19 +23,34 @@ use termcolor::{ColorChoice, StandardStream, StandardStreamLock};
 pub mod cat;
 mod command_namespace;
 pub mod complaint;
+pub mod count;
+pub mod diff;
+pub mod flatten;
 pub mod from;
 pub mod generate;
+pub mod grep;
 pub mod hash;
 pub mod head;
 pub mod inspect;
 pub mod jq;
+pub mod merge;
+pub mod ordering;
+pub mod patch;
+pub mod path;
 pub mod primitive;
+pub mod sample;
 pub mod schema;
+pub mod slice;
+pub mod sort;
+pub mod split;
 pub mod stats;
 pub mod structural_recursion;
 pub mod symtab;
+pub mod tail;
 pub mod timestamp_conversion;
 pub mod to;
+pub mod unflatten;
+pub mod uniq;
 
 pub(crate) use command_namespace::IonCliNamespace;
 
@@ -84,6 +101,7 @@ pub trait IonCliCommand {
             .version(crate_version!())
             .author(crate_authors!())
             .with_decompression_control()
+            .with_catalog()
             .arg(
                 Arg::new(UNSTABLE_FLAG)
                     .short('X')
@@ -143,6 +161,8 @@ const ION_VERSION_ARG_ID: &str = "ion-version";
 pub trait WithIonCliArgument {
     fn with_input(self) -> Self;
     fn with_output(self) -> Self;
+    /// Adds `--format` for Ion output, and `--import`, which makes the output binary Ion that
+    /// imports a shared symbol table from the catalog.
     fn with_format(self) -> Self;
     /// Adds `--color` and `--no-color` flags.
     ///
@@ -151,6 +171,8 @@ pub trait WithIonCliArgument {
     fn with_syntax_highlighting(self) -> Self;
     fn with_ion_version(self) -> Self;
     fn with_decompression_control(self) -> Self;
+    /// Adds `--catalog`, which loads shared symbol tables for readers to resolve imports with.
+    fn with_catalog(self) -> Self;
     fn show_unstable_flag(self) -> Self;
 }
 
@@ -179,8 +201,18 @@ impl WithIonCliArgument for ClapCommand {
                 .short('f')
                 .default_value("pretty")
                 .value_parser(["binary", "text", "pretty", "lines"])
+                .default_value_if("import", ArgPredicate::IsPresent, "binary")
                 .help("Output format"),
         )
+        .arg(
+            Arg::new("import")
+                .long("import")
+                .value_name("NAME[:VERSION]")
+                .requires("catalog")
+                .help(
+                    "Write binary Ion 1.0 that imports this shared symbol table from the catalog.",
+                ),
+        )
     }
 
     fn with_syntax_highlighting(self) -> Self {
@@ -233,6 +265,16 @@ impl WithIonCliArgument for ClapCommand {
         )
     }
 
+    fn with_catalog(self) -> Self {
+        self.arg(
+            Arg::new("catalog")
+                .long("catalog")
+                .action(ArgAction::Append)
+                .value_name("PATH")
+                .help("A file or directory of shared symbol tables that inputs may import."),
+        )
+    }
+
     /// All commands automatically have the "unstable" opt-in flag. This makes it visible.
     fn show_unstable_flag(self) -> Self {
         self.mut_arg(UNSTABLE_FLAG, |arg| arg.hide(false))
@@ -245,6 +287,12 @@ pub struct CommandIo<'a> {
     format: Format,
     encoding: IonEncoding,
     color: ColorChoice,
+    /// Whether colored output should be produced by highlighting Ion syntax (as opposed to the
+    /// command setting colors itself).
+    highlight_ion: bool,
+    catalog: CommandCatalog,
+    /// The shared symbol table that binary output imports, if any.
+    import: Option<Rc<SharedSymbolTable>>,
 }
 
 impl CommandIo<'_> {
@@ -291,14 +339,40 @@ impl CommandIo<'_> {
             resolve_color_choice(default_use_color, args)
         };
 
+        // --catalog PATH --import NAME[:VERSION]
+        let catalog = CommandCatalog::load(
+            args.try_get_many::<String>("catalog")
+                .ok()
+                .flatten()
+                .unwrap_or_default()
+                .map(String::as_str),
+        )?;
+        let import = match args.try_get_one::<String>("import").ok().flatten() {
+            Some(_) if encoding != IonEncoding::Binary_1_0 => {
+                bail!("--import requires binary Ion 1.0 output")
+            }
+            Some(spec) => Some(Rc::new(catalog.table(spec)?.clone())),
+            None => None,
+        };
+
         Ok(CommandIo {
             args,
             format,
             encoding,
             color,
+            highlight_ion: true,
+            catalog,
+            import,
         })
     }
 
+    /// For commands whose output is not Ion, makes the output stream honor colors set through
+    /// `termcolor`'s [`WriteColor`](termcolor::WriteColor) instead of highlighting Ion syntax.
+    fn with_plain_colors(mut self) -> Self {
+        self.highlight_ion = false;
+        self
+    }
+
     /// Returns `true` if the user has not explicitly disabled auto decompression.
     fn auto_decompression_enabled(&self) -> bool {
         if let Some(is_disabled) = self.args.get_one::<bool>("no-auto-decompress") {
@@ -312,21 +386,23 @@ impl CommandIo<'_> {
     fn command_input_for_stdin(&self) -> Result<CommandInput> {
         const STDIN_NAME: &str = "-";
         let stdin = std::io::stdin().lock();
-        if self.auto_decompression_enabled() {
-            CommandInput::decompress(STDIN_NAME, stdin)
+        let input = if self.auto_decompression_enabled() {
+            CommandInput::decompress(STDIN_NAME, stdin)?
         } else {
-            CommandInput::without_decompression(STDIN_NAME, stdin)
-        }
+            CommandInput::without_decompression(STDIN_NAME, stdin)?
+        };
+        Ok(input.with_catalog(self.catalog.clone()))
     }
 
     /// Constructs a new [`CommandInput`] representing the specified file.
     fn command_input_for_file_name(&self, name: &str) -> Result<CommandInput> {
         let stream = File::open(name)?;
-        if self.auto_decompression_enabled() {
-            CommandInput::decompress(name, stream)
+        let input = if self.auto_decompression_enabled() {
+            CommandInput::decompress(name, stream)?
         } else {
-            CommandInput::without_decompression(name, stream)
-        }
+            CommandInput::without_decompression(name, stream)?
+        };
+        Ok(input.with_catalog(self.catalog.clone()))
     }
 
     /// Calls the provided closure once for each input source specified by the user.
@@ -335,9 +411,49 @@ impl CommandIo<'_> {
         &mut self,
         mut f: impl FnMut(&mut CommandOutput, CommandInput) -> Result<()>,
     ) -> Result<()> {
+        self.with_stream_output(|output| {
+            if let Some(input_file_names) = self.args.get_many::<String>("input") {
+                // Input files were specified, run the converter on each of them in turn
+                for input_file_name in input_file_names {
+                    let input = self.command_input_for_file_name(input_file_name)?;
+                    f(output, input)?;
+                }
+            } else {
+                let input = self.command_input_for_stdin()?;
+                f(output, input)?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Opens every input source specified by the user (or STDIN if none were specified) and calls
+    /// the provided closure once with all of them. This is useful for commands whose output
+    /// depends on more than one input at a time, like comparing or merging streams.
+    fn for_all_inputs(
+        &mut self,
+        f: impl FnOnce(&mut CommandOutput, Vec<CommandInput>) -> Result<()>,
+    ) -> Result<()> {
+        self.with_stream_output(|output| f(output, self.inputs()?))
+    }
+
+    /// Opens every input source specified by the user, or STDIN if none were specified.
+    fn inputs(&self) -> Result<Vec<CommandInput>> {
+        if let Some(input_file_names) = self.args.get_many::<String>("input") {
+            input_file_names
+                .map(|name| self.command_input_for_file_name(name))
+                .collect()
+        } else {
+            Ok(vec![self.command_input_for_stdin()?])
+        }
+    }
+
+    /// Constructs the configured output stream (highlighting it if appropriate), passes it to the
+    /// provided closure, and then flushes it.
+    fn with_stream_output(&self, f: impl FnOnce(&mut CommandOutput) -> Result<()>) -> Result<()> {
         let spec = CommandOutputSpec {
             format: self.format,
             encoding: self.encoding,
+            import: self.import.clone(),
         };
 
         let stdout: StandardStream;
@@ -358,14 +474,21 @@ impl CommandIo<'_> {
             // it's a TTY that understands formatting escape codes. These variables are declared here so
             // the lifetime will extend through the remainder of the function. Unlike `io::StdoutLock`,
             // the `StandardStreamLock` does not have a static lifetime.
-            stdout = StandardStream::stdout(self.color);
-            stdout_lock = stdout.lock();
-
             let stdout_tty = std::io::stdout().is_terminal();
 
+            // `termcolor` does not check whether STDOUT is a TTY, so plain colors must be disabled
+            // explicitly when the output is being piped elsewhere.
+            let color = match self.color {
+                ColorChoice::Auto if !self.highlight_ion && !stdout_tty => ColorChoice::Never,
+                color => color,
+            };
+            stdout = StandardStream::stdout(color);
+            stdout_lock = stdout.lock();
+
             match self.color {
                 ColorChoice::Never => CommandOutput::StdOut(stdout_lock, spec),
                 ColorChoice::Auto if !stdout_tty => CommandOutput::StdOut(stdout_lock, spec),
+                _ if !self.highlight_ion => CommandOutput::StdOut(stdout_lock, spec),
                 _ => CommandOutput::HighlightedOut(
                     Box::new(HighlightedStreamWriter::new(stdout_lock)),
                     spec,
@@ -373,16 +496,7 @@ impl CommandIo<'_> {
             }
         };
 
-        if let Some(input_file_names) = self.args.get_many::<String>("input") {
-            // Input files were specified, run the converter on each of them in turn
-            for input_file_name in input_file_names {
-                let input = self.command_input_for_file_name(input_file_name)?;
-                f(&mut output, input)?;
-            }
-        } else {
-            let input = self.command_input_for_stdin()?;
-            f(&mut output, input)?;
-        }
+        f(&mut output)?;
         output.flush()?;
         Ok(())
     }
@@ -391,6 +505,7 @@ impl CommandIo<'_> {
         let spec = CommandOutputSpec {
             format: self.format,
             encoding: self.encoding,
+            import: self.import.clone(),
         };
 
         // These types are provided by the `termcolor` crate. They wrap the normal `io::Stdout` and
@@ -0,0 +1,236 @@
+//! A total order over Ion values, used by commands that sort or merge streams.
+//!
+//! Values are first ordered by the kind of data they hold:
+//!
+//! 1. nulls of any type (`null` first, then the other typed nulls in the order below)
+//! 2. bools (`false` before `true`)
+//! 3. numbers (ints, decimals and floats, compared by their numeric value; `-inf` sorts before
+//!    every other number, `+inf` after every other number and `nan` after `+inf`)
+//! 4. timestamps (compared by the instant they represent)
+//! 5. text (strings and symbols, compared by their Unicode code points; symbols with unknown text
+//!    sort first)
+//! 6. lobs (clobs and blobs, compared byte by byte)
+//! 7. lists, then s-expressions (compared element by element; a shorter sequence sorts before a
+//!    longer one that it is a prefix of)
+//! 8. structs (compared by their fields, sorted by name and then value)
+//!
+//! Values that are still equal after this, like `1` and `1.0`, `"a"` and `a`, or two values with
+//! different annotations, are ordered by the Ion data model's canonical order. Two values compare
+//! as equal only if they are equivalent in the Ion data model.
+
+use std::cmp::Ordering;
+
+use anyhow::Result;
+use bigdecimal::BigDecimal;
+use ion_rs::{Element, IonData, IonResult, IonType, Sequence, Struct, Value};
+
+use crate::commands::jq::ion_math::DecimalMath;
+use crate::commands::path::IonPath;
+use crate::commands::timestamp_conversion::timestamp_to_epoch;
+
+/// Compares two values using the order described in the module documentation.
+pub(crate) fn compare(left: &Element, right: &Element) -> Ordering {
+    compare_values(left, right).then_with(|| IonData::from(left).cmp(&IonData::from(right)))
+}
+
+/// Compares two values like [`compare`], except that values which only differ in their types or
+/// annotations (like `1` and `1.0`, `"a"` and `a`, or `x::1` and `1`) compare as equal.
+pub(crate) fn compare_values(left: &Element, right: &Element) -> Ordering {
+    rank(left)
+        .cmp(&rank(right))
+        .then_with(|| compare_same_rank(left, right))
+}
+
+/// Compares two optional values, such as the results of looking up a key path; a missing value
+/// sorts before any value.
+pub(crate) fn compare_optional(left: Option<&Element>, right: Option<&Element>) -> Ordering {
+    match (left, right) {
+        (Some(left), Some(right)) => compare(left, right),
+        (left, right) => left.is_some().cmp(&right.is_some()),
+    }
+}
+
+fn rank(element: &Element) -> u8 {
+    if element.is_null() {
+        return 0;
+    }
+    match element.ion_type() {
+        IonType::Null => 0,
+        IonType::Bool => 1,
+        IonType::Int | IonType::Decimal | IonType::Float => 2,
+        IonType::Timestamp => 3,
+        IonType::String | IonType::Symbol => 4,
+        IonType::Clob | IonType::Blob => 5,
+        IonType::List => 6,
+        IonType::SExp => 7,
+        IonType::Struct => 8,
+    }
+}
+
+/// Compares two non-null values of the same rank.
+fn compare_same_rank(left: &Element, right: &Element) -> Ordering {
+    match (left.value(), right.value()) {
+        (Value::Null(left), Value::Null(right)) => left.cmp(right),
+        (Value::Bool(left), Value::Bool(right)) => left.cmp(right),
+        (Value::Timestamp(left), Value::Timestamp(right)) => {
+            match (timestamp_to_epoch(left), timestamp_to_epoch(right)) {
+                (Ok(left), Ok(right)) => left.cmp(&right),
+                _ => Ordering::Equal,
+            }
+        }
+        (Value::String(_) | Value::Symbol(_), _) => left.as_text().cmp(&right.as_text()),
+        (Value::Blob(left) | Value::Clob(left), Value::Blob(right) | Value::Clob(right)) => {
+            left.as_ref().cmp(right.as_ref())
+        }
+        (Value::List(left), Value::List(right)) | (Value::SExp(left), Value::SExp(right)) => {
+            compare_sequences(left, right)
+        }
+        (Value::Struct(left), Value::Struct(right)) => compare_structs(left, right),
+        (left, right) => Number::from(left).cmp(&Number::from(right)),
+    }
+}
+
+fn compare_sequences(left: &Sequence, right: &Sequence) -> Ordering {
+    left.elements()
+        .zip(right.elements())
+        .map(|(left, right)| compare(left, right))
+        .find(|ordering| ordering.is_ne())
+        .unwrap_or_else(|| left.len().cmp(&right.len()))
+}
+
+fn compare_structs(left: &Struct, right: &Struct) -> Ordering {
+    fn sorted_fields(strukt: &Struct) -> Vec<(Option<&str>, &Element)> {
+        let mut fields: Vec<_> = strukt
+            .fields()
+            .map(|(name, value)| (name.text(), value))
+            .collect();
+        fields.sort_by(|(n1, v1), (n2, v2)| n1.cmp(n2).then_with(|| compare(v1, v2)));
+        fields
+    }
+    let (left, right) = (sorted_fields(left), sorted_fields(right));
+    left.iter()
+        .zip(right.iter())
+        .map(|((n1, v1), (n2, v2))| n1.cmp(n2).then_with(|| compare(v1, v2)))
+        .find(|ordering| ordering.is_ne())
+        .unwrap_or_else(|| left.len().cmp(&right.len()))
+}
+
+/// A sorted sequence of values.
+pub(crate) type Run = Box<dyn Iterator<Item = IonResult<Element>>>;
+
+pub(crate) struct SortOrder {
+    keys: Vec<IonPath>,
+    descending: bool,
+}
+
+impl SortOrder {
+    pub(crate) fn new(keys: Vec<IonPath>, descending: bool) -> Self {
+        Self { keys, descending }
+    }
+
+    /// Compares two values by each of the keys in turn or, if there are no keys, as a whole.
+    fn compare(&self, left: &Element, right: &Element) -> Ordering {
+        let ordering = if self.keys.is_empty() {
+            compare(left, right)
+        } else {
+            self.keys
+                .iter()
+                .map(|key| compare_optional(key.select(left), key.select(right)))
+                .find(|ordering| ordering.is_ne())
+                .unwrap_or(Ordering::Equal)
+        };
+        if self.descending {
+            ordering.reverse()
+        } else {
+            ordering
+        }
+    }
+
+    pub(crate) fn sort(&self, values: &mut [Element]) {
+        values.sort_by(|left, right| self.compare(left, right));
+    }
+
+    /// Merges sorted runs, passing each value to `write` in order. Values that compare as equal
+    /// are written in the order of the runs that contain them, so the merge is stable as long as
+    /// the runs are in input order.
+    pub(crate) fn merge(
+        &self,
+        mut runs: Vec<Run>,
+        mut write: impl FnMut(Element) -> Result<()>,
+    ) -> Result<()> {
+        let mut heads = runs
+            .iter_mut()
+            .map(|run| run.next().transpose())
+            .collect::<IonResult<Vec<_>>>()?;
+        loop {
+            let mut smallest: Option<usize> = None;
+            for (index, head) in heads.iter().enumerate() {
+                let Some(head) = head else { continue };
+                let is_smaller = match smallest {
+                    None => true,
+                    Some(s) => self.compare(head, heads[s].as_ref().unwrap()).is_lt(),
+                };
+                if is_smaller {
+                    smallest = Some(index);
+                }
+            }
+            let Some(index) = smallest else {
+                return Ok(());
+            };
+            let next = runs[index].next().transpose()?;
+            write(std::mem::replace(&mut heads[index], next).unwrap())?;
+        }
+    }
+}
+
+/// A numeric value of any Ion type. The order of the variants is the order of the numbers.
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+enum Number {
+    NegativeInfinity,
+    Finite(BigDecimal),
+    PositiveInfinity,
+    NaN,
+}
+
+impl From<&Value> for Number {
+    fn from(value: &Value) -> Self {
+        match value {
+            Value::Int(i) => Number::Finite(i.clone().into_big_decimal()),
+            Value::Decimal(d) => Number::Finite(d.clone().into_big_decimal()),
+            Value::Float(f) if f.is_nan() => Number::NaN,
+            Value::Float(f) if *f == f64::INFINITY => Number::PositiveInfinity,
+            Value::Float(f) if *f == f64::NEG_INFINITY => Number::NegativeInfinity,
+            Value::Float(f) => Number::Finite(BigDecimal::try_from(*f).unwrap_or_default()),
+            other => unreachable!("{other:?} is not a number"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn values_are_totally_ordered() {
+        let sorted = Element::read_all(
+            r#"
+            null null.int null.struct false true
+            -inf -5 -4.5e0 0 0e0 0.0 1.5 2e0 123456789012345678901234567890 +inf nan
+            2000-01-01T01:00:00+02:00 2000T 2000-01-01T00:00:01Z
+            $0 "" a "a" "b"
+            {{ "a" }} {{ YQ== }} {{ Yg== }}
+            [] [1] [1, 2] [2] () {} {a: 1} {a: 1, b: 1} {a: 2}
+            "#,
+        )
+        .unwrap();
+        for (i, left) in sorted.iter().enumerate() {
+            for (j, right) in sorted.iter().enumerate() {
+                assert_eq!(
+                    compare(left, right),
+                    i.cmp(&j),
+                    "comparing {left} and {right}"
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,398 @@
+use std::fs::File;
+
+use anyhow::{bail, Context, Result};
+use bigdecimal::{BigDecimal, ToPrimitive};
+use clap::{Arg, ArgMatches, Command};
+use ion_rs::{
+    AnyEncoding, Element, ElementReader, Int, IonType, List, Reader, SExp, Sequence, Struct,
+    Symbol, Value,
+};
+
+use crate::commands::diff::{
+    ANNOTATIONS_FIELD, INSERT_OP, OP_FIELD, PATH_FIELD, REMOVE_OP, SET_ANNOTATIONS_OP, SET_OP,
+    VALUE_FIELD,
+};
+use crate::commands::jq::ion_math::{DecimalMath, ToFloat};
+use crate::commands::path::{IonPath, PathStep};
+use crate::commands::{CommandIo, IonCliCommand, WithIonCliArgument};
+
+/// Adds annotations to the value at a path, after any that it already has.
+const ADD_ANNOTATIONS_OP: &str = "add_annotations";
+/// Removes each occurrence of the specified annotations from the value at a path.
+const REMOVE_ANNOTATIONS_OP: &str = "remove_annotations";
+/// Converts the value at a path to another Ion type.
+const CHANGE_TYPE_OP: &str = "change_type";
+/// Merges a struct into the value at a path, as in JSON Merge Patch (RFC 7396).
+const MERGE_OP: &str = "merge";
+const TYPE_FIELD: &str = "type";
+
+pub struct PatchCommand;
+
+impl IonCliCommand for PatchCommand {
+    fn name(&self) -> &'static str {
+        "patch"
+    }
+
+    fn about(&self) -> &'static str {
+        "Applies a patch to each input and writes the result."
+    }
+
+    fn long_about(&self) -> Option<&'static str> {
+        Some(
+            "Applies the operations in an Ion patch file to each input, in order, and writes the \
+            result. A patch is a stream of structs like `{op: set, path: [0, items, 2], value: 5}`. \
+            The path may be a list of field names and indexes or a string like `[0].items[2]`; its \
+            first step is always the index of a top-level value. The supported operations are \
+            `set` (replace a value or add a field), `insert` (insert before an index or add a \
+            field), `remove`, `set_annotations`, `add_annotations` and `remove_annotations` (with \
+            an `annotations` list), `change_type` (with a `type` such as `string` or `decimal`) and \
+            `merge` (merge a struct `value` into a struct, removing fields that are set to \
+            `null`). `ion diff --patch` produces patches in this format. When a struct has more \
+            than one field with the selected name, `remove` removes all of them and other \
+            operations apply to the first.",
+        )
+    }
+
+    fn is_stable(&self) -> bool {
+        false
+    }
+
+    fn is_porcelain(&self) -> bool {
+        false
+    }
+
+    fn configure_args(&self, command: Command) -> Command {
+        command
+            .arg(
+                Arg::new("patch")
+                    .long("patch")
+                    .short('p')
+                    .required(true)
+                    .value_name("FILE")
+                    .help("An Ion file containing the patch operations to apply."),
+            )
+            .with_input()
+            .with_output()
+            .with_format()
+            .with_ion_version()
+    }
+
+    fn run(&self, _command_path: &mut Vec<String>, args: &ArgMatches) -> Result<()> {
+        let patch_file = args.get_one::<String>("patch").unwrap();
+        let file = File::open(patch_file)
+            .with_context(|| format!("could not open patch file '{patch_file}'"))?;
+        let operations = Reader::new(AnyEncoding, file)?
+            .read_all_elements()
+            .with_context(|| format!("patch file '{patch_file}' was not valid Ion"))?
+            .iter()
+            .map(PatchOperation::from_element)
+            .collect::<Result<Vec<_>>>()?;
+
+        CommandIo::new(args)?.for_each_input(|output, input| {
+            let input_name = input.name().to_owned();
+            let mut stream: Vec<Element> = input
+                .into_reader()?
+                .read_all_elements()?
+                .into_iter()
+                .collect();
+            for operation in &operations {
+                operation.apply(&mut stream).with_context(|| {
+                    format!("could not apply {} to '{input_name}'", operation.source)
+                })?;
+            }
+            let mut writer = output.as_writer()?;
+            for value in stream {
+                writer.write(value)?;
+            }
+            writer.close()?;
+            Ok(())
+        })
+    }
+}
+
+/// A single operation read from a patch file.
+struct PatchOperation {
+    path: IonPath,
+    edit: Edit,
+    /// The operation as it appeared in the patch file, for use in error messages.
+    source: Element,
+}
+
+enum Edit {
+    Set(Element),
+    Insert(Element),
+    Remove,
+    SetAnnotations(Vec<Symbol>),
+    AddAnnotations(Vec<Symbol>),
+    RemoveAnnotations(Vec<Symbol>),
+    ChangeType(IonType),
+    Merge(Element),
+}
+
+impl PatchOperation {
+    fn from_element(element: &Element) -> Result<Self> {
+        let operation = element
+            .as_struct()
+            .with_context(|| format!("a patch operation must be a struct, found {element}"))?;
+        let field = |name: &str| {
+            operation
+                .get(name)
+                .with_context(|| format!("patch operation {element} has no '{name}' field"))
+        };
+        let op = field(OP_FIELD)?
+            .as_text()
+            .with_context(|| format!("patch operation {element} has a non-text 'op' field"))?;
+        let path = IonPath::from_element(field(PATH_FIELD)?)?;
+        let annotations = || -> Result<Vec<Symbol>> {
+            let annotations = field(ANNOTATIONS_FIELD)?;
+            annotations
+                .as_sequence()
+                .with_context(|| format!("'annotations' must be a list, found {annotations}"))?
+                .elements()
+                .map(|a| {
+                    a.as_text()
+                        .map(Symbol::from)
+                        .with_context(|| format!("'{a}' is not a valid annotation"))
+                })
+                .collect()
+        };
+        let edit = match op {
+            SET_OP => Edit::Set(field(VALUE_FIELD)?.clone()),
+            INSERT_OP => Edit::Insert(field(VALUE_FIELD)?.clone()),
+            REMOVE_OP => Edit::Remove,
+            SET_ANNOTATIONS_OP => Edit::SetAnnotations(annotations()?),
+            ADD_ANNOTATIONS_OP => Edit::AddAnnotations(annotations()?),
+            REMOVE_ANNOTATIONS_OP => Edit::RemoveAnnotations(annotations()?),
+            CHANGE_TYPE_OP => {
+                let ion_type = field(TYPE_FIELD)?;
+                Edit::ChangeType(parse_ion_type(ion_type.as_text().unwrap_or_default())?)
+            }
+            MERGE_OP => Edit::Merge(field(VALUE_FIELD)?.clone()),
+            unrecognized => bail!("unrecognized patch operation '{unrecognized}'"),
+        };
+        if path.steps().is_empty() {
+            bail!("patch operation {element} must have a path that starts with an index");
+        }
+        Ok(Self {
+            path,
+            edit,
+            source: element.clone(),
+        })
+    }
+
+    /// Applies this operation to a stream of top-level values.
+    fn apply(&self, stream: &mut Vec<Element>) -> Result<()> {
+        self.edit_sequence(stream, self.path.steps())
+    }
+
+    /// Applies this operation to the sequence (or stream) `values`, at the location that `steps`
+    /// selects within it.
+    fn edit_sequence(&self, values: &mut Vec<Element>, steps: &[PathStep]) -> Result<()> {
+        let index = match &steps[0] {
+            PathStep::Index(index) => *index,
+            PathStep::Field(name) => bail!("cannot select field '{name}' in a sequence"),
+        };
+        let length = values.len();
+        match (&steps[1..], &self.edit) {
+            ([], Edit::Insert(value)) if index <= length => values.insert(index, value.clone()),
+            ([], Edit::Insert(_)) => bail!("cannot insert at index {index} of {length} values"),
+            (_, _) if index >= length => bail!("index {index} is out of range ({length} values)"),
+            ([], Edit::Remove) => {
+                values.remove(index);
+            }
+            ([], _) => values[index] = self.transform(Some(&values[index]))?,
+            (rest, _) => values[index] = self.edit_element(&values[index], rest)?,
+        }
+        Ok(())
+    }
+
+    /// Applies this operation within `element`, at the location that the (non-empty) `steps`
+    /// select, and returns the modified element.
+    fn edit_element(&self, element: &Element, steps: &[PathStep]) -> Result<Element> {
+        let edited: Element = match element.value() {
+            Value::List(sequence) => {
+                let mut values = sequence.elements().cloned().collect();
+                self.edit_sequence(&mut values, steps)?;
+                List::from(values).into()
+            }
+            Value::SExp(sequence) => {
+                let mut values = sequence.elements().cloned().collect();
+                self.edit_sequence(&mut values, steps)?;
+                SExp::from(values).into()
+            }
+            Value::Struct(strukt) => self.edit_struct(strukt, steps)?.into(),
+            _ => bail!(
+                "cannot select {} in a {}",
+                IonPath::new(steps[..1].to_vec()),
+                element.ion_type()
+            ),
+        };
+        Ok(edited.with_annotations(element.annotations().clone()))
+    }
+
+    fn edit_struct(&self, strukt: &Struct, steps: &[PathStep]) -> Result<Struct> {
+        let name = match &steps[0] {
+            PathStep::Field(name) => name.as_str(),
+            PathStep::Index(index) => bail!("cannot select index {index} in a struct"),
+        };
+        let mut fields: Vec<(Symbol, Element)> = strukt
+            .fields()
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect();
+        let position = fields.iter().position(|(n, _)| n.text() == Some(name));
+        match (&steps[1..], &self.edit, position) {
+            ([], Edit::Insert(value), _) => fields.push((Symbol::from(name), value.clone())),
+            ([], Edit::Remove, Some(_)) => fields.retain(|(n, _)| n.text() != Some(name)),
+            ([], Edit::Set(value), None) => fields.push((Symbol::from(name), value.clone())),
+            ([], _, Some(position)) => {
+                fields[position].1 = self.transform(Some(&fields[position].1))?
+            }
+            ([], Edit::Merge(_), None) => fields.push((Symbol::from(name), self.transform(None)?)),
+            (rest, _, Some(position)) => {
+                fields[position].1 = self.edit_element(&fields[position].1, rest)?
+            }
+            (_, _, None) => bail!("there is no field named '{name}'"),
+        }
+        Ok(Struct::from_iter(fields))
+    }
+
+    /// Returns the result of applying this operation to the value it selects, which is `None` if
+    /// the operation is adding a new field. Only used for operations that replace a single value.
+    fn transform(&self, value: Option<&Element>) -> Result<Element> {
+        let null = Element::null(IonType::Null);
+        let current = value.unwrap_or(&null);
+        let annotations = current.annotations().iter().cloned();
+        let transformed = match &self.edit {
+            Edit::Set(value) => value.clone(),
+            Edit::SetAnnotations(new) => current.clone().with_annotations(new.clone()),
+            Edit::AddAnnotations(new) => current
+                .clone()
+                .with_annotations(annotations.chain(new.iter().cloned()).collect::<Vec<_>>()),
+            Edit::RemoveAnnotations(removed) => current.clone().with_annotations(
+                annotations
+                    .filter(|a| !removed.contains(a))
+                    .collect::<Vec<_>>(),
+            ),
+            Edit::ChangeType(ion_type) => {
+                change_type(current, *ion_type)?.with_annotations(annotations.collect::<Vec<_>>())
+            }
+            Edit::Merge(patch) => merge(value, patch),
+            Edit::Insert(_) | Edit::Remove => unreachable!("not a single-value operation"),
+        };
+        Ok(transformed)
+    }
+}
+
+/// Merges `patch` into `target` following the rules of JSON Merge Patch: if `patch` is a struct,
+/// each of its fields is merged into the corresponding field of `target` (which is treated as an
+/// empty struct if it is anything else), and fields whose value is `null` are removed. Any other
+/// `patch` replaces `target`.
+fn merge(target: Option<&Element>, patch: &Element) -> Element {
+    let Some(patch_struct) = patch.as_struct() else {
+        return patch.clone();
+    };
+    let (mut fields, annotations): (Vec<(Symbol, Element)>, _) = match target {
+        Some(target) if target.ion_type() == IonType::Struct && !target.is_null() => (
+            target
+                .as_struct()
+                .unwrap()
+                .fields()
+                .map(|(name, value)| (name.clone(), value.clone()))
+                .collect(),
+            target.annotations().clone(),
+        ),
+        _ => (Vec::new(), patch.annotations().clone()),
+    };
+    for (name, value) in patch_struct.fields() {
+        let position = fields.iter().position(|(n, _)| n == name);
+        match position {
+            _ if value.ion_type() == IonType::Null => fields.retain(|(n, _)| n != name),
+            Some(position) => fields[position].1 = merge(Some(&fields[position].1), value),
+            None => fields.push((name.clone(), merge(None, value))),
+        }
+    }
+    Element::from(Struct::from_iter(fields)).with_annotations(annotations)
+}
+
+fn parse_ion_type(text: &str) -> Result<IonType> {
+    use IonType::*;
+    let ion_type = match text {
+        "null" => Null,
+        "bool" => Bool,
+        "int" => Int,
+        "float" => Float,
+        "decimal" => Decimal,
+        "timestamp" => Timestamp,
+        "symbol" => Symbol,
+        "string" => String,
+        "clob" => Clob,
+        "blob" => Blob,
+        "list" => List,
+        "sexp" => SExp,
+        "struct" => Struct,
+        other => bail!("'{other}' is not an Ion type"),
+    };
+    Ok(ion_type)
+}
+
+/// Converts a value to another Ion type. Numbers can be converted to other numeric types (if they
+/// can be represented exactly, except when converting to a float), text can be converted to any
+/// scalar type whose Ion text it contains, any scalar can be converted to text, and lists and
+/// s-expressions can be converted to each other. Anything can be converted to a null.
+fn change_type(element: &Element, ion_type: IonType) -> Result<Element> {
+    if element.ion_type() == ion_type {
+        return Ok(element.clone());
+    }
+    if ion_type == IonType::Null || element.is_null() {
+        return Ok(Element::null(ion_type));
+    }
+    let converted: Option<Element> = match (element.value(), ion_type) {
+        (Value::List(s), IonType::SExp) => Some(SExp::from(Sequence::clone(s)).into()),
+        (Value::SExp(s), IonType::List) => Some(List::from(Sequence::clone(s)).into()),
+        (Value::String(s), _) => parse_text_as(s.text(), ion_type),
+        (Value::Symbol(s), _) => s.text().and_then(|text| parse_text_as(text, ion_type)),
+        (value, IonType::String | IonType::Symbol) if !element.ion_type().is_container() => {
+            let text = Element::from(value.clone()).to_string();
+            Some(match ion_type {
+                IonType::String => Element::string(text),
+                _ => Element::symbol(text),
+            })
+        }
+        (Value::Int(i), IonType::Float) => i.clone().to_f64().map(Element::from),
+        (Value::Int(i), IonType::Decimal) => Some(i.clone().into_decimal().into()),
+        (Value::Decimal(d), IonType::Float) => d.clone().to_f64().map(Element::from),
+        (Value::Decimal(d), IonType::Int) => big_decimal_to_int(d.clone().into_big_decimal()),
+        (Value::Float(f), IonType::Int | IonType::Decimal) => BigDecimal::try_from(*f)
+            .ok()
+            .and_then(|big| match ion_type {
+                IonType::Int => big_decimal_to_int(big),
+                _ => Some(big.into_decimal().into()),
+            }),
+        _ => None,
+    };
+    converted.with_context(|| format!("cannot convert {element} to {ion_type}"))
+}
+
+/// Reads `text` as a single Ion scalar and converts it to the specified type, so that (for example)
+/// both "1" and "1.5" can become decimals.
+fn parse_text_as(text: &str, ion_type: IonType) -> Option<Element> {
+    if matches!(ion_type, IonType::String | IonType::Symbol) {
+        return Some(match ion_type {
+            IonType::String => Element::string(text),
+            _ => Element::symbol(text),
+        });
+    }
+    let scalar = Element::read_one(text).ok().filter(|e| {
+        e.annotations().is_empty()
+            && !e.ion_type().is_container()
+            && !matches!(e.ion_type(), IonType::String | IonType::Symbol)
+    })?;
+    change_type(&scalar, ion_type).ok()
+}
+
+fn big_decimal_to_int(big: BigDecimal) -> Option<Element> {
+    if !big.is_integer() {
+        return None;
+    }
+    big.to_i128().map(|i| Int::from(i).into())
+}
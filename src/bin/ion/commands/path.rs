@@ -0,0 +1,292 @@
+use std::fmt::{Display, Formatter};
+
+use anyhow::{bail, Context, Result};
+use ion_rs::{Decoder, Element, IonResult, LazyValue, ValueRef};
+
+/// A location within an Ion value (or, when the first step is an index, within a stream of
+/// top-level values).
+///
+/// Paths have a text syntax similar to `jq`'s: `.name` selects a struct field, `[3]` selects the
+/// fourth element of a list or s-expression and `["a name"]` selects a field whose name is not a
+/// plain identifier. For example, `.items[0].price` or `[2]["first name"]`. The empty path is
+/// written as `.`. In Ion, paths can also be written as lists whose elements are field names
+/// (symbols or strings) and indexes (ints), like `[items, 0, price]`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub(crate) struct IonPath {
+    steps: Vec<PathStep>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub(crate) enum PathStep {
+    Field(String),
+    Index(usize),
+}
+
+impl IonPath {
+    pub fn new(steps: Vec<PathStep>) -> Self {
+        Self { steps }
+    }
+
+    pub fn steps(&self) -> &[PathStep] {
+        &self.steps
+    }
+
+    /// Returns a new path that selects `step` within the value selected by this path.
+    pub fn child(&self, step: PathStep) -> Self {
+        let mut steps = self.steps.clone();
+        steps.push(step);
+        Self { steps }
+    }
+
+    pub fn field(&self, name: &str) -> Self {
+        self.child(PathStep::Field(name.to_owned()))
+    }
+
+    pub fn index(&self, index: usize) -> Self {
+        self.child(PathStep::Index(index))
+    }
+
+    /// Parses a path written in the text syntax described in the type's documentation.
+    pub fn parse(text: &str) -> Result<Self> {
+        let (path, rest) = Self::parse_prefix(text.trim())?;
+        if !rest.is_empty() {
+            bail!("invalid path '{text}': unexpected '{rest}'");
+        }
+        Ok(path)
+    }
+
+    /// Parses the path at the beginning of `text`, returning it and the text that follows it. Field
+    /// names written after a `.` end at the first character that cannot appear in a plain name.
+    pub fn parse_prefix(text: &str) -> Result<(Self, &str)> {
+        if !text.starts_with(['.', '[']) {
+            bail!("invalid path '{text}': a path must begin with '.' or '['");
+        }
+        let mut steps = Vec::new();
+        let mut rest = text;
+        while let Some(c) = rest.chars().next() {
+            match c {
+                '.' => {
+                    let name_end = rest[1..]
+                        .find(|c| !is_name_char(c))
+                        .map_or(rest.len(), |i| i + 1);
+                    let name = &rest[1..name_end];
+                    if name.is_empty() {
+                        if steps.is_empty() && !rest[1..].starts_with(['.', '[']) {
+                            // A lone `.` is the empty path.
+                            return Ok((Self::default(), &rest[1..]));
+                        }
+                        bail!("invalid path '{text}': expected a field name after '.'");
+                    }
+                    steps.push(PathStep::Field(name.to_owned()));
+                    rest = &rest[name_end..];
+                }
+                '[' => {
+                    let (step, remaining) = parse_bracketed_step(&rest[1..])
+                        .with_context(|| format!("invalid path '{text}'"))?;
+                    steps.push(step);
+                    rest = remaining;
+                }
+                _ => break,
+            }
+        }
+        Ok((Self { steps }, rest))
+    }
+
+    /// Reads a path from an Ion list of field names and indexes, or from a string in the text
+    /// syntax.
+    pub fn from_element(element: &Element) -> Result<Self> {
+        if let Some(text) = element.as_string() {
+            return Self::parse(text);
+        }
+        let sequence = element.as_sequence().with_context(|| {
+            format!("a path must be a string or a list of field names and indexes, found {element}")
+        })?;
+        sequence
+            .elements()
+            .map(|step| {
+                if let Some(name) = step.as_text() {
+                    Ok(PathStep::Field(name.to_owned()))
+                } else if let Some(index) = step.as_usize() {
+                    Ok(PathStep::Index(index))
+                } else {
+                    bail!("'{step}' is not a field name or index")
+                }
+            })
+            .collect::<Result<Vec<_>>>()
+            .map(Self::new)
+    }
+
+    /// Returns this path as an Ion list of field names (symbols) and indexes (ints).
+    pub fn to_element(&self) -> Element {
+        let steps: Vec<Element> = self
+            .steps
+            .iter()
+            .map(|step| match step {
+                PathStep::Field(name) => Element::symbol(name.as_str()),
+                PathStep::Index(index) => Element::from(*index as i64),
+            })
+            .collect();
+        ion_rs::List::from(steps).into()
+    }
+
+    /// Returns the value that this path selects within `root`, if any. If a struct has more than
+    /// one field with the selected name, the first one is used.
+    pub fn select<'a>(&self, root: &'a Element) -> Option<&'a Element> {
+        self.steps
+            .iter()
+            .try_fold(root, |element, step| match step {
+                PathStep::Field(name) => element.as_struct()?.get(name.as_str()),
+                PathStep::Index(index) => element.as_sequence()?.get(*index),
+            })
+    }
+
+    /// Like [`select`](Self::select), but navigates a lazily read value, so that only the parts of
+    /// it that are on the path are read.
+    pub fn select_lazy<'top, D: Decoder>(
+        &self,
+        root: LazyValue<'top, D>,
+    ) -> IonResult<Option<LazyValue<'top, D>>> {
+        let mut value = root;
+        for step in &self.steps {
+            let next = match (step, value.read()?) {
+                (PathStep::Field(name), ValueRef::Struct(strukt)) => strukt.find(name)?,
+                (PathStep::Index(index), ValueRef::List(list)) => {
+                    list.iter().nth(*index).transpose()?
+                }
+                (PathStep::Index(index), ValueRef::SExp(sexp)) => {
+                    sexp.iter().nth(*index).transpose()?
+                }
+                _ => None,
+            };
+            match next {
+                Some(next) => value = next,
+                None => return Ok(None),
+            }
+        }
+        Ok(Some(value))
+    }
+}
+
+/// Parses the remainder of a `[...]` step, returning the step and the text following the `]`.
+fn parse_bracketed_step(text: &str) -> Result<(PathStep, &str)> {
+    if let Some(quoted) = text.strip_prefix('"') {
+        let mut name = String::new();
+        let mut chars = quoted.char_indices();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '\\' => match chars.next() {
+                    Some((_, escaped)) => name.push(escaped),
+                    None => break,
+                },
+                '"' => {
+                    let rest = quoted[i + 1..]
+                        .strip_prefix(']')
+                        .context("expected ']' after a quoted field name")?;
+                    return Ok((PathStep::Field(name), rest));
+                }
+                c => name.push(c),
+            }
+        }
+        bail!("unterminated field name");
+    }
+    let end = text.find(']').context("expected ']'")?;
+    let index = text[..end]
+        .trim()
+        .parse::<usize>()
+        .with_context(|| format!("'{}' is not a valid index", &text[..end]))?;
+    Ok((PathStep::Index(index), &text[end + 1..]))
+}
+
+impl Display for IonPath {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.steps.is_empty() {
+            return write!(f, ".");
+        }
+        for step in &self.steps {
+            match step {
+                PathStep::Index(index) => write!(f, "[{index}]")?,
+                PathStep::Field(name) if is_plain_name(name) => write!(f, ".{name}")?,
+                PathStep::Field(name) => {
+                    let escaped = name.replace('\\', "\\\\").replace('"', "\\\"");
+                    write!(f, "[\"{escaped}\"]")?
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Returns `true` if `name` can be written after a `.` without quoting.
+fn is_plain_name(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(is_name_char)
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '$' | '-')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_and_display_round_trip() {
+        for text in [
+            ".",
+            ".a",
+            ".a.b[0]",
+            "[2][\"first name\"].x",
+            "[0][1]",
+            "[\"a\\\"b\"]",
+        ] {
+            let path = IonPath::parse(text).unwrap();
+            assert_eq!(path.to_string(), text);
+            assert_eq!(IonPath::from_element(&path.to_element()).unwrap(), path);
+        }
+    }
+
+    #[test]
+    fn parse_rejects_malformed_paths() {
+        for text in ["", "a", ".a[", ".a[x]", "..a", "[\"a]"] {
+            assert!(IonPath::parse(text).is_err(), "{text} should be rejected");
+        }
+    }
+
+    #[test]
+    fn select() {
+        let root = Element::read_one("{a: [1, {b: 2}], 'c d': 3}").unwrap();
+        let select = |text| IonPath::parse(text).unwrap().select(&root).cloned();
+        assert_eq!(select(".a[1].b"), Some(Element::from(2)));
+        assert_eq!(select("[\"c d\"]"), Some(Element::from(3)));
+        assert_eq!(select(".a[5]"), None);
+        assert_eq!(select(".a.b"), None);
+    }
+
+    #[test]
+    fn parse_prefix() {
+        let parse = |text| {
+            let (path, rest) = IonPath::parse_prefix(text).unwrap();
+            (path.to_string(), rest)
+        };
+        assert_eq!(parse(".a.b == 1"), (".a.b".to_owned(), " == 1"));
+        assert_eq!(parse(".a[0])"), (".a[0]".to_owned(), ")"));
+        assert_eq!(parse(". =~ /x/"), (".".to_owned(), " =~ /x/"));
+    }
+
+    #[test]
+    fn select_lazy() {
+        let mut reader =
+            ion_rs::Reader::new(ion_rs::AnyEncoding, "{a: [1, {b: 2}], c: (3 4)}").unwrap();
+        let root = reader.next().unwrap().unwrap();
+        let select = |text| {
+            let path = IonPath::parse(text).unwrap();
+            path.select_lazy(root)
+                .unwrap()
+                .map(|value| Element::try_from(value).unwrap())
+        };
+        assert_eq!(select(".a[1].b"), Some(Element::from(2)));
+        assert_eq!(select(".c[1]"), Some(Element::from(4)));
+        assert_eq!(select(".a[5]"), None);
+        assert_eq!(select(".c.d"), None);
+    }
+}
@@ -0,0 +1,192 @@
+use std::collections::HashMap;
+use std::num::NonZeroUsize;
+
+use anyhow::{bail, Context, Result};
+use clap::{value_parser, Arg, ArgGroup, ArgMatches, Command};
+use ion_rs::{AnyEncoding, Element, IonData, LazyValue};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use crate::commands::path::IonPath;
+use crate::commands::{CommandIo, IonCliCommand, WithIonCliArgument};
+
+pub struct SampleCommand;
+
+impl IonCliCommand for SampleCommand {
+    fn name(&self) -> &'static str {
+        "sample"
+    }
+
+    fn about(&self) -> &'static str {
+        "Writes a random sample of the top-level values in the input stream."
+    }
+
+    fn long_about(&self) -> Option<&'static str> {
+        Some(
+            "Selects a random subset of the top-level values of all inputs, which are treated as \
+            a single stream. `--values K` keeps K values, each value being equally likely to be \
+            kept (reservoir sampling); with `--key PATH`, it keeps K values for each distinct \
+            value at the path, so that rare keys are represented as well as common ones. `--rate \
+            P` keeps each value with probability P, so the number of values kept varies. Values \
+            are written in the order they were read. Runs with the same `--seed` and the same \
+            input select the same values; without a seed, each run selects differently.",
+        )
+    }
+
+    fn is_stable(&self) -> bool {
+        false
+    }
+
+    fn is_porcelain(&self) -> bool {
+        false
+    }
+
+    fn configure_args(&self, command: Command) -> Command {
+        command
+            .arg(
+                Arg::new("values")
+                    .long("values")
+                    .short('n')
+                    .value_parser(value_parser!(NonZeroUsize))
+                    .help("Keep this many values (for each key, with `--key`)."),
+            )
+            .arg(
+                Arg::new("rate")
+                    .long("rate")
+                    .short('r')
+                    .value_parser(parse_rate)
+                    .help("Keep each value with this probability, like `0.01`."),
+            )
+            .group(
+                ArgGroup::new("sampling")
+                    .args(["values", "rate"])
+                    .required(true),
+            )
+            .arg(
+                Arg::new("key")
+                    .long("key")
+                    .short('k')
+                    .value_name("PATH")
+                    .conflicts_with("rate")
+                    .help("Keep `--values` values for each distinct value at this path."),
+            )
+            .arg(
+                Arg::new("seed")
+                    .long("seed")
+                    .short('s')
+                    .value_parser(value_parser!(u64))
+                    .help("Seeds the random number generator, so that the sample is repeatable."),
+            )
+            .with_input()
+            .with_output()
+            .with_format()
+            .with_ion_version()
+    }
+
+    fn run(&self, _command_path: &mut Vec<String>, args: &ArgMatches) -> Result<()> {
+        let mut rng = match args.get_one::<u64>("seed") {
+            Some(seed) => ChaCha8Rng::seed_from_u64(*seed),
+            None => ChaCha8Rng::from_entropy(),
+        };
+        let key = args
+            .get_one::<String>("key")
+            .map(|key| IonPath::parse(key))
+            .transpose()?;
+        let capacity = args.get_one::<NonZeroUsize>("values").map(|n| n.get());
+        let rate = args.get_one::<f64>("rate").copied();
+
+        CommandIo::new(args)?.for_all_inputs(|output, inputs| {
+            let mut writer = output.as_writer()?;
+            // Reservoirs, by key; without `--key`, there is a single reservoir.
+            let mut reservoirs: HashMap<Option<IonData<Element>>, Reservoir> = HashMap::new();
+            let mut index = 0;
+            for input in inputs {
+                let input_name = input.name().to_owned();
+                let mut reader = input
+                    .into_reader()
+                    .with_context(|| format!("Input file '{}' was not valid Ion.", input_name))?;
+                while let Some(value) = reader.next()? {
+                    if let Some(rate) = rate {
+                        if rng.gen_bool(rate) {
+                            writer.write(Element::try_from(value)?)?;
+                        }
+                        continue;
+                    }
+                    let stratum = match &key {
+                        Some(key) => key
+                            .select_lazy(value)?
+                            .map(Element::try_from)
+                            .transpose()?
+                            .map(IonData::from),
+                        None => None,
+                    };
+                    reservoirs
+                        .entry(stratum)
+                        .or_insert_with(|| Reservoir::new(capacity.unwrap()))
+                        .add(index, value, &mut rng)?;
+                    index += 1;
+                }
+            }
+
+            let mut samples: Vec<(usize, Element)> = reservoirs
+                .into_values()
+                .flat_map(|reservoir| reservoir.samples)
+                .collect();
+            samples.sort_by_key(|(index, _)| *index);
+            for (_, element) in samples {
+                writer.write(element)?;
+            }
+            writer.close()?;
+            Ok(())
+        })
+    }
+}
+
+/// Parses a probability between 0 and 1.
+fn parse_rate(text: &str) -> Result<f64> {
+    let rate: f64 = text
+        .parse()
+        .with_context(|| format!("'{}' is not a number", text))?;
+    if !(0.0..=1.0).contains(&rate) {
+        bail!("the rate must be between 0 and 1");
+    }
+    Ok(rate)
+}
+
+/// A uniform random sample of a fixed number of the values seen so far.
+struct Reservoir {
+    capacity: usize,
+    seen: usize,
+    /// The kept values and their positions in the stream.
+    samples: Vec<(usize, Element)>,
+}
+
+impl Reservoir {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            seen: 0,
+            samples: Vec::new(),
+        }
+    }
+
+    /// Adds the value at `index` to the sample with probability `capacity / seen`, replacing a
+    /// random value if the sample is full. Values that are not kept are not read.
+    fn add(
+        &mut self,
+        index: usize,
+        value: LazyValue<AnyEncoding>,
+        rng: &mut impl Rng,
+    ) -> Result<()> {
+        self.seen += 1;
+        if self.samples.len() < self.capacity {
+            self.samples.push((index, Element::try_from(value)?));
+            return Ok(());
+        }
+        let slot = rng.gen_range(0..self.seen);
+        if slot < self.capacity {
+            self.samples[slot] = (index, Element::try_from(value)?);
+        }
+        Ok(())
+    }
+}
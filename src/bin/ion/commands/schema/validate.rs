@@ -7,9 +7,7 @@ use crate::output::CommandOutput;
 use anyhow::{Error, Result};
 use clap::builder::ArgPredicate;
 use clap::{Arg, ArgAction, ArgMatches, Command};
-use ion_rs::{
-    ion_sexp, AnyEncoding, ElementReader, IonError, Reader, SequenceWriter, TextFormat, Writer,
-};
+use ion_rs::{ion_sexp, ElementReader, IonError, SequenceWriter, TextFormat, Writer};
 use ion_rs::{v1_0, Element, ValueWriter};
 use ion_schema::result::ValidationResult;
 use ion_schema::violation::Violation;
@@ -138,7 +136,8 @@ impl IonCliCommand for ValidateCommand {
 
             match grouping {
                 FileHandles => {
-                    let document: Result<Vec<_>, _> = Reader::new(AnyEncoding, input.into_source())
+                    let document: Result<Vec<_>, _> = input
+                        .into_reader()
                         .and_then(|r| r.into_elements().collect());
                     match document {
                         Ok(document) => {
@@ -169,7 +168,7 @@ impl IonCliCommand for ValidateCommand {
                     }
                 }
                 TopLevelValues => {
-                    let reader = Reader::new(AnyEncoding, input.into_source())?;
+                    let reader = input.into_reader()?;
                     for value in reader.into_elements() {
                         match value {
                             Ok(value) => {
@@ -0,0 +1,224 @@
+use std::fs::File;
+use std::io::Read;
+use std::num::NonZeroUsize;
+use std::str::FromStr;
+
+use anyhow::{bail, Context, Result};
+use clap::{value_parser, Arg, ArgAction, ArgMatches, Command};
+use ion_rs::{AnyEncoding, Element, HasRange, IonInput, Reader, Struct};
+
+use crate::commands::tail::count_values;
+use crate::commands::{CommandIo, IonCliCommand, WithIonCliArgument};
+use crate::input::CommandInput;
+use crate::output::CommandOutput;
+
+pub struct SliceCommand;
+
+impl IonCliCommand for SliceCommand {
+    fn name(&self) -> &'static str {
+        "slice"
+    }
+
+    fn about(&self) -> &'static str {
+        "Prints a range of top-level values from each input."
+    }
+
+    fn long_about(&self) -> Option<&'static str> {
+        Some(
+            "Prints the top-level values of each input whose indexes are in a range. Ranges are \
+            written like Rust's: `100..200` selects values 100 to 199 (counting from 0), \
+            `100..=200` also selects value 200, `100..` selects value 100 and everything after it, \
+            `..10` selects the first 10 values and `7` selects only value 7. Negative indexes \
+            count from the end of the input, so `-10..` selects the last 10 values; this requires \
+            counting the values first, which reads files twice and holds STDIN in memory. With \
+            `--step N`, only every Nth value in the range is printed. With `--positions`, each \
+            value is printed in a struct like `{index: 3, offset: 120, length: 8, value: ...}`, \
+            where `offset` and `length` give the location of the value's (decompressed) encoding in \
+            the input.",
+        )
+    }
+
+    fn is_stable(&self) -> bool {
+        false
+    }
+
+    fn is_porcelain(&self) -> bool {
+        false
+    }
+
+    fn configure_args(&self, command: Command) -> Command {
+        command
+            .with_input()
+            .with_output()
+            .with_format()
+            .with_ion_version()
+            .with_syntax_highlighting()
+            .arg(
+                Arg::new("range")
+                    .long("range")
+                    .short('r')
+                    .value_parser(ValueRange::from_str)
+                    .allow_hyphen_values(true)
+                    .default_value("..")
+                    .help("The indexes of the values to print, like `100..200` or `-10..`."),
+            )
+            .arg(
+                Arg::new("step")
+                    .long("step")
+                    .short('s')
+                    .value_parser(value_parser!(NonZeroUsize))
+                    .default_value("1")
+                    .help("Only print every Nth value in the range, starting with the first."),
+            )
+            .arg(
+                Arg::new("positions")
+                    .long("positions")
+                    .action(ArgAction::SetTrue)
+                    .help("Print the index, byte offset and byte length of each value with it."),
+            )
+    }
+
+    fn run(&self, _command_path: &mut Vec<String>, args: &ArgMatches) -> Result<()> {
+        let range = *args.get_one::<ValueRange>("range").unwrap();
+        let selection = Selection {
+            range,
+            step: args.get_one::<NonZeroUsize>("step").unwrap().get(),
+            with_positions: args.get_flag("positions"),
+        };
+
+        CommandIo::new(args)?.for_each_input(|output, input| {
+            if !range.counts_from_end() {
+                let reader = input.into_reader()?;
+                return selection.write(reader, output, None);
+            }
+            // The values have to be counted before they can be selected, so the input is read twice.
+            if input.name() == "-" {
+                let catalog = input.catalog().clone();
+                let mut bytes = Vec::new();
+                input.into_source().read_to_end(&mut bytes)?;
+                let total = count_values(&mut catalog.reader(bytes.as_slice())?)?;
+                let reader = catalog.reader(bytes.as_slice())?;
+                selection.write(reader, output, Some(total))
+            } else {
+                let file_name = input.name().to_owned();
+                let catalog = input.catalog().clone();
+                let total = count_values(&mut input.into_reader()?)?;
+                let input = CommandInput::decompress(&file_name, File::open(&file_name)?)?
+                    .with_catalog(catalog);
+                let reader = input.into_reader()?;
+                selection.write(reader, output, Some(total))
+            }
+        })
+    }
+}
+
+/// A range of top-level value indexes, either of whose bounds may count from the end of the input.
+#[derive(Copy, Clone, Debug, PartialEq)]
+struct ValueRange {
+    start: Option<i64>,
+    /// The (exclusive) end of the range.
+    end: Option<i64>,
+}
+
+impl ValueRange {
+    fn counts_from_end(&self) -> bool {
+        self.start.is_some_and(|s| s < 0) || self.end.is_some_and(|e| e < 0)
+    }
+
+    /// Returns the start and (if bounded) end of the range as indexes into an input with `total`
+    /// values, which must be known if the range counts from the end.
+    fn resolve(&self, total: Option<usize>) -> (usize, Option<usize>) {
+        let resolve_bound = |bound: i64| match (usize::try_from(bound), total) {
+            (Ok(index), _) => index,
+            (Err(_), Some(total)) => total.saturating_sub(bound.unsigned_abs() as usize),
+            (Err(_), None) => unreachable!("the total is known for ranges that count from the end"),
+        };
+        (
+            self.start.map_or(0, resolve_bound),
+            self.end.map(resolve_bound),
+        )
+    }
+}
+
+impl FromStr for ValueRange {
+    type Err = anyhow::Error;
+
+    fn from_str(text: &str) -> Result<Self> {
+        let parse_bound = |bound: &str| -> Result<Option<i64>> {
+            let bound = bound.trim();
+            if bound.is_empty() {
+                return Ok(None);
+            }
+            let index = bound
+                .parse::<i64>()
+                .with_context(|| format!("'{bound}' is not a valid index"))?;
+            Ok(Some(index))
+        };
+        let Some((start, end)) = text.split_once("..") else {
+            let index = parse_bound(text)?.context("a range must not be empty")?;
+            // A single index selects one value.
+            let end = if index == -1 { None } else { Some(index + 1) };
+            return Ok(Self {
+                start: Some(index),
+                end,
+            });
+        };
+        let (end, inclusive) = match end.strip_prefix('=') {
+            Some(end) => (end, true),
+            None => (end, false),
+        };
+        let start = parse_bound(start)?;
+        let end = match (parse_bound(end)?, inclusive) {
+            (None, true) => bail!("an inclusive range (`..=`) must have an end"),
+            (Some(-1), true) => None,
+            (Some(end), true) => Some(end + 1),
+            (end, false) => end,
+        };
+        Ok(Self { start, end })
+    }
+}
+
+/// Which values to write and how to write them.
+struct Selection {
+    range: ValueRange,
+    step: usize,
+    with_positions: bool,
+}
+
+impl Selection {
+    /// Writes the selected values from `reader`. `total` is the number of values in the input, if
+    /// it has been counted.
+    fn write<I: IonInput>(
+        &self,
+        mut reader: Reader<AnyEncoding, I>,
+        output: &mut CommandOutput,
+        total: Option<usize>,
+    ) -> Result<()> {
+        let (start, end) = self.range.resolve(total);
+        let mut writer = output.as_writer()?;
+        let mut index = 0;
+        while let Some(value) = reader.next()? {
+            if end.is_some_and(|end| index >= end) {
+                break;
+            }
+            if index >= start && (index - start) % self.step == 0 {
+                if self.with_positions {
+                    let mut position = Struct::builder().with_field("index", index as i64);
+                    if let Some(raw) = value.raw() {
+                        let range = raw.range();
+                        position = position
+                            .with_field("offset", range.start as i64)
+                            .with_field("length", range.len() as i64);
+                    }
+                    let element = Element::try_from(value)?;
+                    writer.write(Element::from(position.with_field("value", element).build()))?;
+                } else {
+                    writer.write(value)?;
+                }
+            }
+            index += 1;
+        }
+        writer.close()?;
+        Ok(())
+    }
+}
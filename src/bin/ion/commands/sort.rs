@@ -0,0 +1,219 @@
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Seek, SeekFrom};
+
+use anyhow::{Context, Result};
+use clap::{value_parser, Arg, ArgAction, ArgMatches, Command};
+use ion_rs::{v1_0, AnyEncoding, Element, ElementReader, Reader, SequenceWriter, Writer};
+
+use crate::commands::ordering::{Run, SortOrder};
+use crate::commands::path::IonPath;
+use crate::commands::{CommandIo, IonCliCommand, WithIonCliArgument};
+
+pub struct SortCommand;
+
+impl IonCliCommand for SortCommand {
+    fn name(&self) -> &'static str {
+        "sort"
+    }
+
+    fn about(&self) -> &'static str {
+        "Sorts the top-level values of the input."
+    }
+
+    fn long_about(&self) -> Option<&'static str> {
+        Some(
+            "Sorts the top-level values of all inputs, which are treated as a single stream. \
+            Values are compared as a whole or, with one or more `--key` paths (like \
+            `.header.timestamp`), by the values at those paths in turn; values that do not have a \
+            key sort before those that do. Values are ordered by type (nulls, bools, numbers, \
+            timestamps, text, lobs, lists, s-expressions and then structs) and then by value, so \
+            that ints, decimals and floats are compared numerically and timestamps are compared \
+            by the instant they represent. The sort is stable. Inputs that have more values than \
+            `--chunk-size` are sorted in chunks that are written to temporary files and then \
+            merged, so the size of the input is not limited by the available memory. At most \
+            `--merge-width` temporary files are merged at once; when there are more, groups of \
+            them are first merged into larger temporary files.",
+        )
+    }
+
+    fn is_stable(&self) -> bool {
+        false
+    }
+
+    fn is_porcelain(&self) -> bool {
+        false
+    }
+
+    fn configure_args(&self, command: Command) -> Command {
+        command
+            .arg(
+                Arg::new("key")
+                    .long("key")
+                    .short('k')
+                    .action(ArgAction::Append)
+                    .value_name("PATH")
+                    .help("A path to the value to sort by, like `.a.b[0]`. May be repeated."),
+            )
+            .arg(
+                Arg::new("reverse")
+                    .long("reverse")
+                    .short('r')
+                    .action(ArgAction::SetTrue)
+                    .help("Sort in descending order."),
+            )
+            .arg(
+                Arg::new("chunk-size")
+                    .long("chunk-size")
+                    .value_parser(value_parser!(usize))
+                    .default_value("100000")
+                    .help("The maximum number of values to sort in memory at once."),
+            )
+            .arg(
+                Arg::new("merge-width")
+                    .long("merge-width")
+                    .value_parser(value_parser!(u64).range(2..))
+                    .default_value("64")
+                    .help("The maximum number of temporary files to merge at once."),
+            )
+            .with_input()
+            .with_output()
+            .with_format()
+            .with_ion_version()
+    }
+
+    fn run(&self, _command_path: &mut Vec<String>, args: &ArgMatches) -> Result<()> {
+        let keys = args
+            .get_many::<String>("key")
+            .unwrap_or_default()
+            .map(|key| IonPath::parse(key))
+            .collect::<Result<Vec<_>>>()?;
+        let order = SortOrder::new(keys, args.get_flag("reverse"));
+        let chunk_size = (*args.get_one::<usize>("chunk-size").unwrap()).max(1);
+        let merge_width = *args.get_one::<u64>("merge-width").unwrap() as usize;
+
+        CommandIo::new(args)?.for_all_inputs(|output, inputs| {
+            let mut chunk = Vec::new();
+            let mut spilled_runs = SpilledRuns::new(merge_width);
+            for input in inputs {
+                let input_name = input.name().to_owned();
+                let mut reader = input
+                    .into_reader()
+                    .with_context(|| format!("Input file '{}' was not valid Ion.", input_name))?;
+                for element in reader.elements() {
+                    chunk.push(element?);
+                    if chunk.len() >= chunk_size {
+                        order.sort(&mut chunk);
+                        spilled_runs.push(&order, spill(&chunk)?)?;
+                        chunk.clear();
+                    }
+                }
+            }
+            order.sort(&mut chunk);
+
+            let mut writer = output.as_writer()?;
+            if spilled_runs.is_empty() {
+                for element in chunk {
+                    writer.write(element)?;
+                }
+            } else {
+                let mut runs = spilled_runs.into_runs(&order)?;
+                runs.push(Box::new(chunk.into_iter().map(Ok)));
+                order.merge(runs, |element| {
+                    writer.write(element)?;
+                    Ok(())
+                })?;
+            }
+            writer.close()?;
+            Ok(())
+        })
+    }
+}
+
+/// The runs that have been spilled to temporary files, in input order. Each run is kept at a level,
+/// starting from 0; whenever a level has `width` runs, they are merged into a single run at the
+/// next level, so that no more than `width` files are read at once and each value is only
+/// rewritten once per level.
+struct SpilledRuns {
+    width: usize,
+    levels: Vec<Vec<Run>>,
+}
+
+impl SpilledRuns {
+    fn new(width: usize) -> Self {
+        Self {
+            width,
+            levels: Vec::new(),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.levels.iter().all(Vec::is_empty)
+    }
+
+    fn push(&mut self, order: &SortOrder, run: Run) -> Result<()> {
+        let mut run = run;
+        let mut level = 0;
+        loop {
+            if self.levels.len() == level {
+                self.levels.push(Vec::new());
+            }
+            self.levels[level].push(run);
+            if self.levels[level].len() < self.width {
+                return Ok(());
+            }
+            // The runs at a higher level all hold values from earlier in the input than those at
+            // a lower level, so merging a full level keeps the runs in input order.
+            run = spill_merged(order, std::mem::take(&mut self.levels[level]))?;
+            level += 1;
+        }
+    }
+
+    /// Returns the runs in input order, leaving room for one more, after merging them until there
+    /// are few enough.
+    fn into_runs(self, order: &SortOrder) -> Result<Vec<Run>> {
+        let mut runs: Vec<Run> = self.levels.into_iter().rev().flatten().collect();
+        while runs.len() >= self.width {
+            let mut merged = Vec::new();
+            let mut remaining = runs.into_iter().peekable();
+            while remaining.peek().is_some() {
+                let group: Vec<Run> = remaining.by_ref().take(self.width).collect();
+                merged.push(spill_merged(order, group)?);
+            }
+            runs = merged;
+        }
+        Ok(runs)
+    }
+}
+
+/// Writes a sorted chunk of values to a temporary file as binary Ion and returns a run that reads
+/// them back.
+fn spill(chunk: &[Element]) -> Result<Run> {
+    spill_with(|writer| {
+        writer.write_all(chunk)?;
+        Ok(())
+    })
+}
+
+/// Merges `runs` into a temporary file and returns a run that reads the merged values back.
+fn spill_merged(order: &SortOrder, runs: Vec<Run>) -> Result<Run> {
+    spill_with(|writer| {
+        order.merge(runs, |element| {
+            writer.write(element)?;
+            Ok(())
+        })
+    })
+}
+
+fn spill_with(
+    write: impl FnOnce(&mut Writer<v1_0::Binary, BufWriter<File>>) -> Result<()>,
+) -> Result<Run> {
+    let file = tempfile::tempfile().context("could not create a temporary file")?;
+    let mut writer = Writer::new(v1_0::Binary, BufWriter::new(file))?;
+    write(&mut writer)?;
+    let mut file = writer.close()?.into_inner()?;
+    file.seek(SeekFrom::Start(0))?;
+    let mut reader = Reader::new(AnyEncoding, BufReader::new(file))?;
+    Ok(Box::new(std::iter::from_fn(move || {
+        reader.read_next_element().transpose()
+    })))
+}
@@ -0,0 +1,599 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use clap::{value_parser, Arg, ArgGroup, ArgMatches, Command};
+use flate2::write::GzEncoder;
+use ion_rs::{
+    v1_0, v1_1, AnyEncoding, Element, IonEncoding, IonResult, LazyValue, WriteAsIon, Writer,
+};
+
+use crate::commands::path::IonPath;
+use crate::commands::{CommandIo, Format, IonCliCommand, WithIonCliArgument};
+use crate::output::{importing_writer, CommandOutputSpec, PrimedOutput};
+
+pub struct SplitCommand;
+
+impl IonCliCommand for SplitCommand {
+    fn name(&self) -> &'static str {
+        "split"
+    }
+
+    fn about(&self) -> &'static str {
+        "Splits a stream of top-level values into multiple output files."
+    }
+
+    fn long_about(&self) -> Option<&'static str> {
+        Some(
+            "Splits the top-level values of all inputs, which are treated as a single stream, into \
+            chunks that are each written to their own file. A chunk holds `--values N` values, \
+            roughly `--bytes N` bytes of encoded values (before compression; a chunk always holds \
+            at least one value), or, with `--key PATH`, every value whose key has the same text. \
+            Each chunk is a complete Ion stream: binary chunks begin with their own version \
+            marker and symbol table. Chunk files are named by `--template` (by default, \
+            `chunk-{n}` or `chunk-{key}` with an extension for the format), in which `{n}` is \
+            replaced by the number of the chunk (starting from 0000) and `{key}` by the chunk's \
+            key, with any characters other than letters, digits, `-` and `_` replaced by `_`. \
+            With `--key`, `{n}` is the number of the chunk in the order its key was first seen. \
+            Values that do not have the key are written to the chunk whose key is `missing`. If \
+            two different keys have the same file name text (like `a/b` and `a_b`, or `1` and \
+            `\"1\"`), the later one gets a suffix, like `a_b-2`. At most `--max-open-chunks` \
+            chunk files are open at once; when another one is needed, the one opened longest \
+            ago is closed, and reopened later to append to it if necessary.",
+        )
+    }
+
+    fn is_stable(&self) -> bool {
+        false
+    }
+
+    fn is_porcelain(&self) -> bool {
+        false
+    }
+
+    fn configure_args(&self, command: Command) -> Command {
+        command
+            .with_input()
+            .with_format()
+            .with_ion_version()
+            .arg(
+                Arg::new("values")
+                    .long("values")
+                    .short('n')
+                    .value_parser(value_parser!(NonZeroUsize))
+                    .help("Write this many values to each chunk."),
+            )
+            .arg(
+                Arg::new("bytes")
+                    .long("bytes")
+                    .short('b')
+                    .value_parser(parse_size)
+                    .help("Write about this many bytes to each chunk, like `500000` or `64M`."),
+            )
+            .arg(
+                Arg::new("key")
+                    .long("key")
+                    .short('k')
+                    .value_name("PATH")
+                    .help("Write values to a chunk for each distinct value at this path."),
+            )
+            .group(
+                ArgGroup::new("chunking")
+                    .args(["values", "bytes", "key"])
+                    .required(true),
+            )
+            .arg(
+                Arg::new("template")
+                    .long("template")
+                    .short('t')
+                    .help("The name of each chunk file, like `out/part-{n}.10n`."),
+            )
+            .arg(
+                Arg::new("max-open-chunks")
+                    .long("max-open-chunks")
+                    .value_parser(value_parser!(NonZeroUsize))
+                    .default_value("256")
+                    .conflicts_with_all(["values", "bytes"])
+                    .help("The most chunk files to keep open at once with --key."),
+            )
+            .arg(
+                Arg::new("compress")
+                    .long("compress")
+                    .short('z')
+                    .value_parser(["gzip", "zstd"])
+                    .help("Compress each chunk file."),
+            )
+    }
+
+    fn run(&self, _command_path: &mut Vec<String>, args: &ArgMatches) -> Result<()> {
+        let chunking = if let Some(values) = args.get_one::<NonZeroUsize>("values") {
+            Chunking::Values(values.get())
+        } else if let Some(bytes) = args.get_one::<u64>("bytes") {
+            Chunking::Bytes(*bytes)
+        } else {
+            let key = args.get_one::<String>("key").unwrap();
+            Chunking::Key(IonPath::parse(key)?)
+        };
+        let compression = match args.get_one::<String>("compress").map(String::as_str) {
+            Some("gzip") => Some(Compression::Gzip),
+            Some("zstd") => Some(Compression::Zstd),
+            _ => None,
+        };
+
+        let command_io = CommandIo::new(args)?;
+        let spec = CommandOutputSpec {
+            format: command_io.format,
+            encoding: command_io.encoding,
+            import: command_io.import.clone(),
+        };
+        let template = match args.get_one::<String>("template") {
+            Some(template) => template.to_owned(),
+            None => default_template(&chunking, spec.format, compression),
+        };
+        let max_open_chunks = args.get_one::<NonZeroUsize>("max-open-chunks").unwrap();
+        let mut splitter =
+            Splitter::new(chunking, template, spec, compression, max_open_chunks.get())?;
+        for input in command_io.inputs()? {
+            let input_name = input.name().to_owned();
+            let mut reader = input
+                .into_reader()
+                .with_context(|| format!("Input file '{}' was not valid Ion.", input_name))?;
+            while let Some(value) = reader.next()? {
+                splitter.write(value)?;
+            }
+        }
+        splitter.close()
+    }
+}
+
+/// Parses a number of bytes, which may have a `K`, `M` or `G` suffix (for powers of 1024).
+fn parse_size(text: &str) -> Result<u64> {
+    let (digits, multiplier) = match text.char_indices().last() {
+        Some((i, 'k' | 'K')) => (&text[..i], 1 << 10),
+        Some((i, 'm' | 'M')) => (&text[..i], 1 << 20),
+        Some((i, 'g' | 'G')) => (&text[..i], 1 << 30),
+        _ => (text, 1),
+    };
+    match digits.parse::<u64>() {
+        Ok(size) if size > 0 => size
+            .checked_mul(multiplier)
+            .with_context(|| format!("'{text}' is too large")),
+        _ => bail!("'{text}' is not a positive number of bytes"),
+    }
+}
+
+fn default_template(
+    chunking: &Chunking,
+    format: Format,
+    compression: Option<Compression>,
+) -> String {
+    let placeholder = match chunking {
+        Chunking::Key(_) => KEY_PLACEHOLDER,
+        _ => NUMBER_PLACEHOLDER,
+    };
+    let extension = match format {
+        Format::Binary => "10n",
+        Format::Text(_) => "ion",
+    };
+    let compression_extension = match compression {
+        Some(Compression::Gzip) => ".gz",
+        Some(Compression::Zstd) => ".zst",
+        None => "",
+    };
+    format!("chunk-{placeholder}.{extension}{compression_extension}")
+}
+
+const NUMBER_PLACEHOLDER: &str = "{n}";
+const KEY_PLACEHOLDER: &str = "{key}";
+
+/// The name used for the chunk of values that do not have the key.
+const MISSING_KEY_NAME: &str = "missing";
+
+/// How values are assigned to chunks.
+enum Chunking {
+    Values(usize),
+    Bytes(u64),
+    Key(IonPath),
+}
+
+#[derive(Copy, Clone)]
+enum Compression {
+    Gzip,
+    Zstd,
+}
+
+/// Assigns each value to a chunk and writes it to that chunk's file.
+struct Splitter {
+    chunking: Chunking,
+    template: String,
+    spec: CommandOutputSpec,
+    compression: Option<Compression>,
+    /// The number of chunks that have been started.
+    num_chunks: usize,
+    /// The chunk being written when chunking by size.
+    current: Option<ChunkWriter>,
+    values_in_current: usize,
+    /// The chunks that have been started when chunking by key.
+    chunks_by_key: HashMap<ChunkKey, KeyedChunk>,
+    /// The file name text of each key in `chunks_by_key`.
+    key_names: HashSet<String>,
+    /// The keys of the chunks whose files are open, opened longest ago first.
+    open_keys: VecDeque<ChunkKey>,
+    max_open_chunks: usize,
+}
+
+/// Identifies the chunk that a value belongs to when chunking by key. Values whose keys have the
+/// same text share a chunk, as do values whose keys are equal non-text values.
+#[derive(Clone, PartialEq, Eq, Hash)]
+enum ChunkKey {
+    Missing,
+    Text(String),
+    Other(String),
+}
+
+impl ChunkKey {
+    fn new(key: Option<&Element>) -> Self {
+        match key {
+            None => ChunkKey::Missing,
+            Some(key) => match key.as_text() {
+                Some(text) => ChunkKey::Text(text.to_owned()),
+                None => ChunkKey::Other(key.to_string()),
+            },
+        }
+    }
+}
+
+/// A chunk when chunking by key, whose file may have been closed to stay under the limit.
+struct KeyedChunk {
+    path: PathBuf,
+    writer: Option<ChunkWriter>,
+}
+
+impl Splitter {
+    fn new(
+        chunking: Chunking,
+        template: String,
+        spec: CommandOutputSpec,
+        compression: Option<Compression>,
+        max_open_chunks: usize,
+    ) -> Result<Self> {
+        let has_placeholder = template.contains(NUMBER_PLACEHOLDER)
+            || (matches!(chunking, Chunking::Key(_)) && template.contains(KEY_PLACEHOLDER));
+        if !has_placeholder {
+            bail!(
+                "the template '{template}' must contain {NUMBER_PLACEHOLDER} (or \
+                {KEY_PLACEHOLDER} with --key) so that each chunk has its own file"
+            );
+        }
+        Ok(Self {
+            chunking,
+            template,
+            spec,
+            compression,
+            num_chunks: 0,
+            current: None,
+            values_in_current: 0,
+            chunks_by_key: HashMap::new(),
+            key_names: HashSet::new(),
+            open_keys: VecDeque::new(),
+            max_open_chunks,
+        })
+    }
+
+    fn write(&mut self, value: LazyValue<AnyEncoding>) -> Result<()> {
+        match self.chunking {
+            Chunking::Values(max_values) => {
+                self.current_chunk()?.write(value)?;
+                self.values_in_current += 1;
+                if self.values_in_current >= max_values {
+                    self.close_current()?;
+                }
+            }
+            Chunking::Bytes(max_bytes) => {
+                let chunk = self.current_chunk()?;
+                chunk.write(value)?;
+                chunk.flush()?;
+                if chunk.bytes_written() >= max_bytes {
+                    self.close_current()?;
+                }
+            }
+            Chunking::Key(ref key) => {
+                let element = Element::try_from(value)?;
+                let key = ChunkKey::new(key.select(&element));
+                self.keyed_chunk(&key)?.write(element)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the open chunk for `key`, starting it or reopening its file if necessary.
+    fn keyed_chunk(&mut self, key: &ChunkKey) -> Result<&mut ChunkWriter> {
+        let is_open = match self.chunks_by_key.get(key) {
+            Some(chunk) => chunk.writer.is_some(),
+            None => false,
+        };
+        if !is_open {
+            if self.open_keys.len() >= self.max_open_chunks {
+                let oldest = self.open_keys.pop_front().unwrap();
+                let chunk = self.chunks_by_key.get_mut(&oldest).unwrap();
+                chunk.writer.take().unwrap().close()?;
+            }
+            let writer = match self.chunks_by_key.get(key) {
+                Some(chunk) => {
+                    ChunkWriter::create(&chunk.path, self.spec.clone(), self.compression, true)
+                        .with_context(|| {
+                            format!("could not reopen chunk file '{}'", chunk.path.display())
+                        })?
+                }
+                None => {
+                    let name = self.key_name(key);
+                    let path = self.chunk_path(Some(&name));
+                    let writer = self.create_chunk(&path)?;
+                    self.chunks_by_key
+                        .insert(key.clone(), KeyedChunk { path, writer: None });
+                    writer
+                }
+            };
+            self.chunks_by_key.get_mut(key).unwrap().writer = Some(writer);
+            self.open_keys.push_back(key.clone());
+        }
+        Ok(self
+            .chunks_by_key
+            .get_mut(key)
+            .unwrap()
+            .writer
+            .as_mut()
+            .unwrap())
+    }
+
+    /// Returns the file name text for a new key, which differs from that of every other key.
+    fn key_name(&mut self, key: &ChunkKey) -> String {
+        let sanitized = sanitize(key);
+        let mut name = sanitized.clone();
+        let mut suffix = 1;
+        while self.key_names.contains(&name) {
+            suffix += 1;
+            name = format!("{sanitized}-{suffix}");
+        }
+        self.key_names.insert(name.clone());
+        name
+    }
+
+    /// Returns the chunk being written, starting a new one if necessary.
+    fn current_chunk(&mut self) -> Result<&mut ChunkWriter> {
+        if self.current.is_none() {
+            let path = self.chunk_path(None);
+            self.current = Some(self.create_chunk(&path)?);
+        }
+        Ok(self.current.as_mut().unwrap())
+    }
+
+    fn close_current(&mut self) -> Result<()> {
+        self.values_in_current = 0;
+        match self.current.take() {
+            Some(chunk) => chunk.close(),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns the file name of the next chunk.
+    fn chunk_path(&self, key: Option<&str>) -> PathBuf {
+        let mut name = self
+            .template
+            .replace(NUMBER_PLACEHOLDER, &format!("{:04}", self.num_chunks));
+        if let Some(key) = key {
+            name = name.replace(KEY_PLACEHOLDER, key);
+        }
+        PathBuf::from(name)
+    }
+
+    fn create_chunk(&mut self, path: &Path) -> Result<ChunkWriter> {
+        self.num_chunks += 1;
+        ChunkWriter::create(path, self.spec.clone(), self.compression, false)
+            .with_context(|| format!("could not create chunk file '{}'", path.display()))
+    }
+
+    fn close(mut self) -> Result<()> {
+        self.close_current()?;
+        for (_, chunk) in self.chunks_by_key.drain() {
+            if let Some(writer) = chunk.writer {
+                writer.close()?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Turns a key into text that can be used in a file name.
+fn sanitize(key: &ChunkKey) -> String {
+    let text = match key {
+        ChunkKey::Missing => return MISSING_KEY_NAME.to_owned(),
+        ChunkKey::Text(text) | ChunkKey::Other(text) => text,
+    };
+    let sanitized: String = text
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if sanitized.is_empty() {
+        "_".to_owned()
+    } else {
+        sanitized
+    }
+}
+
+/// Writes the values of one chunk to its file.
+#[allow(non_camel_case_types)]
+enum ChunkWriter {
+    Text_1_0(Writer<v1_0::Text, ChunkFile>),
+    Binary_1_0(Writer<v1_0::Binary, ChunkFile>),
+    Text_1_1(Writer<v1_1::Text, ChunkFile>),
+    Binary_1_1(Writer<v1_1::Binary, ChunkFile>),
+    Importing_1_0(Writer<v1_0::Binary, PrimedOutput<ChunkFile>>),
+}
+
+impl ChunkWriter {
+    /// Starts a chunk file, or with `append`, another Ion stream at the end of an existing one.
+    fn create(
+        path: &Path,
+        spec: CommandOutputSpec,
+        compression: Option<Compression>,
+        append: bool,
+    ) -> Result<Self> {
+        if let Some(directory) = path.parent() {
+            fs::create_dir_all(directory)?;
+        }
+        let file = ChunkFile::create(path, compression, append)?;
+        if let Some(table) = &spec.import {
+            return Ok(ChunkWriter::Importing_1_0(importing_writer(file, table)?));
+        }
+        Ok(match (spec.encoding, spec.format) {
+            (IonEncoding::Text_1_0, Format::Text(text_format)) => {
+                ChunkWriter::Text_1_0(Writer::new(v1_0::Text.with_format(text_format), file)?)
+            }
+            (IonEncoding::Text_1_1, Format::Text(text_format)) => {
+                ChunkWriter::Text_1_1(Writer::new(v1_1::Text.with_format(text_format), file)?)
+            }
+            (IonEncoding::Binary_1_0, Format::Binary) => {
+                ChunkWriter::Binary_1_0(Writer::new(v1_0::Binary, file)?)
+            }
+            (IonEncoding::Binary_1_1, Format::Binary) => {
+                ChunkWriter::Binary_1_1(Writer::new(v1_1::Binary, file)?)
+            }
+            unrecognized => bail!("unsupported format '{:?}'", unrecognized),
+        })
+    }
+
+    fn write<V: WriteAsIon>(&mut self, value: V) -> IonResult<()> {
+        match self {
+            ChunkWriter::Text_1_0(w) => w.write(value).map(|_| ()),
+            ChunkWriter::Binary_1_0(w) => w.write(value).map(|_| ()),
+            ChunkWriter::Text_1_1(w) => w.write(value).map(|_| ()),
+            ChunkWriter::Binary_1_1(w) => w.write(value).map(|_| ()),
+            ChunkWriter::Importing_1_0(w) => w.write(value).map(|_| ()),
+        }
+    }
+
+    fn flush(&mut self) -> IonResult<()> {
+        match self {
+            ChunkWriter::Text_1_0(w) => w.flush(),
+            ChunkWriter::Binary_1_0(w) => w.flush(),
+            ChunkWriter::Text_1_1(w) => w.flush(),
+            ChunkWriter::Binary_1_1(w) => w.flush(),
+            ChunkWriter::Importing_1_0(w) => w.flush(),
+        }
+    }
+
+    /// The number of bytes (before compression) that have been flushed to the file.
+    fn bytes_written(&self) -> u64 {
+        match self {
+            ChunkWriter::Text_1_0(w) => w.output().bytes_written,
+            ChunkWriter::Binary_1_0(w) => w.output().bytes_written,
+            ChunkWriter::Text_1_1(w) => w.output().bytes_written,
+            ChunkWriter::Binary_1_1(w) => w.output().bytes_written,
+            ChunkWriter::Importing_1_0(w) => w.output().get_ref().bytes_written,
+        }
+    }
+
+    fn close(self) -> Result<()> {
+        let file = match self {
+            ChunkWriter::Text_1_0(w) => w.close()?,
+            ChunkWriter::Binary_1_0(w) => w.close()?,
+            ChunkWriter::Text_1_1(w) => w.close()?,
+            ChunkWriter::Binary_1_1(w) => w.close()?,
+            ChunkWriter::Importing_1_0(w) => w.close()?.into_inner(),
+        };
+        file.finish()
+    }
+}
+
+/// A chunk's file, which counts the bytes written to it and compresses them if requested.
+struct ChunkFile {
+    encoder: ChunkEncoder,
+    bytes_written: u64,
+}
+
+enum ChunkEncoder {
+    Uncompressed(BufWriter<File>),
+    Gzip(GzEncoder<BufWriter<File>>),
+    Zstd(zstd::Encoder<'static, BufWriter<File>>),
+}
+
+impl ChunkFile {
+    fn create(path: &Path, compression: Option<Compression>, append: bool) -> Result<Self> {
+        let file = File::options()
+            .write(true)
+            .create(true)
+            .append(append)
+            .truncate(!append)
+            .open(path)?;
+        let file = BufWriter::new(file);
+        let encoder = match compression {
+            None => ChunkEncoder::Uncompressed(file),
+            Some(Compression::Gzip) => {
+                ChunkEncoder::Gzip(GzEncoder::new(file, flate2::Compression::default()))
+            }
+            Some(Compression::Zstd) => ChunkEncoder::Zstd(zstd::Encoder::new(file, 0)?),
+        };
+        Ok(Self {
+            encoder,
+            bytes_written: 0,
+        })
+    }
+
+    /// Writes any data that the compressor is holding and flushes the file.
+    fn finish(self) -> Result<()> {
+        let mut file = match self.encoder {
+            ChunkEncoder::Uncompressed(file) => file,
+            ChunkEncoder::Gzip(encoder) => encoder.finish()?,
+            ChunkEncoder::Zstd(encoder) => encoder.finish()?,
+        };
+        file.flush()?;
+        Ok(())
+    }
+}
+
+impl Write for ChunkFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let bytes_written = match &mut self.encoder {
+            ChunkEncoder::Uncompressed(file) => file.write(buf)?,
+            ChunkEncoder::Gzip(encoder) => encoder.write(buf)?,
+            ChunkEncoder::Zstd(encoder) => encoder.write(buf)?,
+        };
+        self.bytes_written += bytes_written as u64;
+        Ok(bytes_written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match &mut self.encoder {
+            ChunkEncoder::Uncompressed(file) => file.flush(),
+            // Flushing a compressor ends its current block, which would hurt compression when
+            // chunking by size flushes after every value. Compressed data is written by `finish`.
+            ChunkEncoder::Gzip(_) | ChunkEncoder::Zstd(_) => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_sizes() {
+        assert_eq!(parse_size("1000").unwrap(), 1000);
+        assert_eq!(parse_size("4k").unwrap(), 4096);
+        assert_eq!(parse_size("2M").unwrap(), 2 * 1024 * 1024);
+        assert_eq!(parse_size("1G").unwrap(), 1024 * 1024 * 1024);
+        for invalid in ["", "0", "-1", "K", "1.5M", "1T"] {
+            assert!(parse_size(invalid).is_err(), "{invalid} should be rejected");
+        }
+    }
+}
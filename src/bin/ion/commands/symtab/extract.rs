@@ -0,0 +1,101 @@
+use std::collections::HashMap;
+
+use anyhow::{Context, Result};
+use clap::builder::NonEmptyStringValueParser;
+use clap::{value_parser, Arg, ArgMatches, Command};
+use ion_rs::ElementReader;
+
+use crate::commands::symtab::{count_symbols, shared_symbol_table_element, symbols_by_frequency};
+use crate::commands::{CommandIo, IonCliCommand, WithIonCliArgument};
+
+pub struct SymtabExtractCommand;
+
+impl IonCliCommand for SymtabExtractCommand {
+    fn name(&self) -> &'static str {
+        "extract"
+    }
+
+    fn about(&self) -> &'static str {
+        "Builds a shared symbol table from the symbols used in one or more Ion streams."
+    }
+
+    fn long_about(&self) -> Option<&'static str> {
+        Some(
+            "Counts how often each symbol is used as a field name, an annotation or a symbol \
+            value in the user data of all inputs, and writes a `$ion_shared_symbol_table` with \
+            the given `--name` and `--table-version` whose symbols are those with at least \
+            `--min-count` uses, most used first (symbols used equally often are in alphabetical \
+            order). Ion's system symbols, like `name` and `$ion_symbol_table`, are not included \
+            because every symbol table already defines them, and neither are symbols whose text \
+            is unknown.",
+        )
+    }
+
+    fn is_stable(&self) -> bool {
+        false
+    }
+
+    fn is_porcelain(&self) -> bool {
+        false
+    }
+
+    fn configure_args(&self, command: Command) -> Command {
+        command
+            .arg(
+                Arg::new("name")
+                    .long("name")
+                    .short('n')
+                    .required(true)
+                    .value_parser(NonEmptyStringValueParser::new())
+                    .help("The name of the shared symbol table, like `com.example.orders`."),
+            )
+            .arg(
+                Arg::new("table-version")
+                    .long("table-version")
+                    .short('t')
+                    .value_parser(value_parser!(u32).range(1..))
+                    .default_value("1")
+                    .help("The version of the shared symbol table."),
+            )
+            .arg(
+                Arg::new("min-count")
+                    .long("min-count")
+                    .short('m')
+                    .value_parser(value_parser!(u64).range(1..))
+                    .default_value("1")
+                    .help("Leave out symbols that are used fewer than this many times."),
+            )
+            .with_input()
+            .with_output()
+            .with_format()
+    }
+
+    fn run(&self, _command_path: &mut Vec<String>, args: &ArgMatches) -> Result<()> {
+        let name = args.get_one::<String>("name").unwrap();
+        let version = *args.get_one::<u32>("table-version").unwrap();
+        let min_count = *args.get_one::<u64>("min-count").unwrap();
+
+        CommandIo::new(args)?.for_all_inputs(|output, inputs| {
+            let mut counts: HashMap<String, u64> = HashMap::new();
+            for input in inputs {
+                let input_name = input.name().to_owned();
+                let mut reader = input
+                    .into_reader()
+                    .with_context(|| format!("Input file '{}' was not valid Ion.", input_name))?;
+                for element in reader.elements() {
+                    count_symbols(&element?, &mut counts);
+                }
+            }
+
+            let symbols = symbols_by_frequency(counts)
+                .into_iter()
+                .filter(|(_, count)| *count >= min_count)
+                .map(|(text, _)| text);
+
+            let mut writer = output.as_writer()?;
+            writer.write(shared_symbol_table_element(name, version, symbols))?;
+            writer.close()?;
+            Ok(())
+        })
+    }
+}
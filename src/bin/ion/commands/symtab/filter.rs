@@ -41,7 +41,7 @@ impl IonCliCommand for SymtabFilterCommand {
     fn run(&self, _command_path: &mut Vec<String>, args: &ArgMatches) -> Result<()> {
         let lift_requested = args.get_flag("lift");
         CommandIo::new(args)?.for_each_input(|output, input| {
-            let mut system_reader = SystemReader::new(AnyEncoding, input.into_source());
+            let mut system_reader = input.into_system_reader();
             filter_out_user_data(&mut system_reader, output, lift_requested)
         })
     }
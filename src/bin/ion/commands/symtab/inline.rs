@@ -0,0 +1,159 @@
+use std::io::Write;
+
+use anyhow::{bail, Result};
+use clap::{ArgMatches, Command};
+use ion_rs::*;
+
+use crate::catalog::CommandCatalog;
+use crate::commands::symtab::LocalSymbolTable;
+use crate::commands::{CommandIo, IonCliCommand, WithIonCliArgument};
+use crate::output::CommandOutput;
+
+pub struct SymtabInlineCommand;
+
+impl IonCliCommand for SymtabInlineCommand {
+    fn name(&self) -> &'static str {
+        "inline"
+    }
+
+    fn about(&self) -> &'static str {
+        "Replaces shared symbol table imports with local symbol tables that define the same symbols."
+    }
+
+    fn long_about(&self) -> Option<&'static str> {
+        Some(
+            "Copies each input to the output, replacing every local symbol table that imports \
+            shared symbol tables from `--catalog` with one that defines the imported symbols \
+            itself. Symbol IDs do not change, so all other data is copied as is, and the output \
+            can be read without the catalog. Imported symbols whose text the shared table does \
+            not have, because the import's `max_id` is larger than the table, are defined with \
+            unknown text.",
+        )
+    }
+
+    fn is_stable(&self) -> bool {
+        false
+    }
+
+    fn is_porcelain(&self) -> bool {
+        false
+    }
+
+    fn configure_args(&self, command: Command) -> Command {
+        command.with_input().with_output()
+    }
+
+    fn run(&self, _command_path: &mut Vec<String>, args: &ArgMatches) -> Result<()> {
+        CommandIo::new(args)?.for_each_input(|output, input| {
+            let catalog = input.catalog().clone();
+            let mut system_reader = input.into_system_reader();
+            inline_imports(&mut system_reader, &catalog, output)
+        })
+    }
+}
+
+pub fn inline_imports(
+    reader: &mut SystemReader<AnyEncoding, impl IonInput>,
+    catalog: &CommandCatalog,
+    output: &mut CommandOutput,
+) -> Result<()> {
+    loop {
+        let mut replacement = None;
+        match reader.next_item()? {
+            SystemStreamItem::VersionMarker(marker) => {
+                output.write_all(marker.span().bytes())?;
+            }
+            SystemStreamItem::SymbolTable(symtab) => {
+                let Some(raw_value) = symtab.as_value().raw() else {
+                    // This symbol table came from a macro expansion; there are no encoded bytes
+                    // to pass through.
+                    bail!("found an ephemeral symbol table, which is not yet supported")
+                };
+                let element = Element::try_from(symtab.as_value())?;
+                let table = LocalSymbolTable::new(raw_value.range().start, &element, catalog);
+                if table.imports.is_empty() {
+                    output.write_all(raw_value.span().bytes())?;
+                } else {
+                    replacement = Some(inlined_symbol_table(&table, catalog));
+                }
+            }
+            SystemStreamItem::EncodingDirective(_) => bail!("only Ion 1.0 streams are supported"),
+            SystemStreamItem::Value(value) => {
+                let Some(raw_value) = value.raw() else {
+                    bail!("found a value from a macro expansion, which is not yet supported")
+                };
+                output.write_all(raw_value.span().bytes())?;
+            }
+            SystemStreamItem::EndOfStream(_) => {
+                return Ok(());
+            }
+            _ => unreachable!("#[non_exhaustive] enum, current variants covered"),
+        };
+        if let Some(symbol_table) = replacement {
+            output.write_all(&encode_symbol_table(
+                &symbol_table,
+                reader.detected_encoding(),
+            )?)?;
+        }
+        // If this is a text encoding, then we need delimiting space to separate
+        // neighboring items that were passed through.
+        if reader.detected_encoding().is_text() {
+            output.write_all(b"\n")?;
+        }
+    }
+}
+
+/// Returns a local symbol table that defines the symbols that `table` imports, followed by its
+/// own symbols.
+fn inlined_symbol_table(table: &LocalSymbolTable, catalog: &CommandCatalog) -> Element {
+    let text_element = |text: Option<&str>| match text {
+        Some(text) => Element::string(text),
+        None => Element::null(IonType::String),
+    };
+    let mut symbols = Vec::new();
+    for import in &table.imports {
+        let imported = catalog
+            .get_table_with_version(&import.name, import.version as usize)
+            .map(|shared_table| shared_table.symbols())
+            .unwrap_or_default();
+        for index in 0..import.count {
+            symbols.push(text_element(
+                imported.get(index).and_then(|symbol| symbol.text()),
+            ));
+        }
+    }
+    symbols.extend(
+        table
+            .symbols
+            .iter()
+            .map(|text| text_element(text.as_deref())),
+    );
+    Element::from(
+        Struct::builder()
+            .with_field("symbols", List::from(symbols))
+            .build(),
+    )
+    .with_annotations(["$ion_symbol_table"])
+}
+
+/// Encodes a symbol table to be written in the middle of a stream with the given encoding.
+fn encode_symbol_table(symbol_table: &Element, encoding: IonEncoding) -> Result<Vec<u8>> {
+    // The symbol table's fields and annotation are all system symbols, so writing it does not
+    // write another symbol table.
+    let encoded = match encoding {
+        IonEncoding::Binary_1_0 => {
+            let mut writer = Writer::new(v1_0::Binary, Vec::new())?;
+            writer.write(symbol_table)?;
+            let encoded = writer.close()?;
+            // Skip the IVM that starts the encoded stream.
+            encoded[4..].to_vec()
+        }
+        IonEncoding::Text_1_0 => {
+            let mut writer = Writer::new(v1_0::Text, Vec::new())?;
+            writer.write(symbol_table)?;
+            writer.close()?
+        }
+        _ => bail!("only Ion 1.0 streams are supported"),
+    };
+    Ok(encoded)
+}
@@ -0,0 +1,331 @@
+use anyhow::{bail, Context, Result};
+use clap::{value_parser, Arg, ArgMatches, Command};
+use ion_rs::*;
+
+use crate::catalog::CommandCatalog;
+use crate::commands::symtab::{LocalSymbolTable, SYSTEM_SYMBOLS};
+use crate::commands::{CommandIo, IonCliCommand, WithIonCliArgument};
+use crate::output::CommandOutputWriter;
+
+pub struct SymtabListCommand;
+
+impl IonCliCommand for SymtabListCommand {
+    fn name(&self) -> &'static str {
+        "list"
+    }
+
+    fn about(&self) -> &'static str {
+        "Lists the local symbol tables in an Ion stream and the symbol IDs that each one defines."
+    }
+
+    fn long_about(&self) -> Option<&'static str> {
+        Some(
+            "Writes a struct for each local symbol table in the input that shows its byte \
+            `offset`, whether it appends to the active symbol table or resets it (`append`), \
+            the shared tables it `imports` and the IDs they occupy, the new `symbols` it defines \
+            starting at `first_id`, and the resulting table's `max_id`.\n\n\
+            With `--resolve`, writes a single struct instead, explaining where the given symbol ID \
+            gets its text from: the system symbol table, an imported shared table or a local \
+            symbol table. The symbol ID is resolved using the symbol table that is active for a \
+            value starting at `--offset`, which is the end of the stream by default.",
+        )
+    }
+
+    fn is_stable(&self) -> bool {
+        false
+    }
+
+    fn is_porcelain(&self) -> bool {
+        false
+    }
+
+    fn configure_args(&self, command: Command) -> Command {
+        command
+            .arg(
+                Arg::new("resolve")
+                    .long("resolve")
+                    .short('r')
+                    .value_name("ID")
+                    .value_parser(parse_symbol_id)
+                    .help("Explain how this symbol ID, like `$12` or `12`, resolves."),
+            )
+            .arg(
+                Arg::new("offset")
+                    .long("offset")
+                    .value_name("BYTES")
+                    .value_parser(value_parser!(usize))
+                    .requires("resolve")
+                    .help("Resolve the symbol ID at this byte offset instead of at the end."),
+            )
+            .with_input()
+            .with_output()
+            .with_format()
+    }
+
+    fn run(&self, _command_path: &mut Vec<String>, args: &ArgMatches) -> Result<()> {
+        let resolve = args.get_one::<usize>("resolve").copied();
+        let offset = args.get_one::<usize>("offset").copied();
+        CommandIo::new(args)?.for_each_input(|output, input| {
+            let catalog = input.catalog().clone();
+            let mut reader = input.into_system_reader();
+            let mut writer = output.as_writer()?;
+            match resolve {
+                Some(id) => {
+                    let explanation =
+                        resolve_symbol_id(&mut reader, &catalog, id, offset.unwrap_or(usize::MAX))?;
+                    writer.write(explanation)?;
+                }
+                None => list_symbol_tables(&mut reader, &catalog, &mut writer)?,
+            }
+            writer.close()?;
+            Ok(())
+        })
+    }
+}
+
+fn parse_symbol_id(text: &str) -> Result<usize> {
+    text.strip_prefix('$')
+        .unwrap_or(text)
+        .parse()
+        .with_context(|| format!("'{}' is not a symbol ID", text))
+}
+
+/// Writes a description of each local symbol table in the stream.
+fn list_symbol_tables(
+    reader: &mut SystemReader<AnyEncoding, impl IonInput>,
+    catalog: &CommandCatalog,
+    writer: &mut CommandOutputWriter,
+) -> Result<()> {
+    let mut context = SymbolContext::new();
+    loop {
+        match next_item(reader, catalog)? {
+            StreamItem::VersionMarker(_) => context.reset(),
+            StreamItem::SymbolTable(table) => {
+                let element = context.apply(&table);
+                writer.write(element)?;
+            }
+            StreamItem::Value(_) => {}
+            StreamItem::EndOfStream(_) => return Ok(()),
+        }
+    }
+}
+
+/// Returns a description of where the text of symbol `id` comes from in the symbol table that is
+/// active for a value starting at `offset`.
+fn resolve_symbol_id(
+    reader: &mut SystemReader<AnyEncoding, impl IonInput>,
+    catalog: &CommandCatalog,
+    id: usize,
+    offset: usize,
+) -> Result<Element> {
+    let mut context = SymbolContext::new();
+    let position = loop {
+        match next_item(reader, catalog)? {
+            StreamItem::VersionMarker(start) if start < offset => context.reset(),
+            StreamItem::SymbolTable(table) if table.offset < offset => {
+                context.apply(&table);
+            }
+            StreamItem::Value(start) if start.is_none_or(|start| start < offset) => {}
+            StreamItem::VersionMarker(_) | StreamItem::SymbolTable(_) | StreamItem::Value(_) => {
+                break offset
+            }
+            StreamItem::EndOfStream(end) => break end.min(offset),
+        }
+    };
+
+    let Some(definition) = context.definition_of(id) else {
+        bail!(
+            "symbol ID ${} is not defined at offset {}; the highest symbol ID there is ${}",
+            id,
+            position,
+            context.max_id()
+        );
+    };
+    // The reader applies a symbol table's changes just before it reads the next item, so its
+    // symbol table now matches `context`.
+    let text = reader.symbol_table().text_for(id);
+    let mut explanation = Struct::builder()
+        .with_field("id", id as i64)
+        .with_field("offset", position as i64)
+        .with_field(
+            "text",
+            text.map(Element::string)
+                .unwrap_or(Element::null(IonType::String)),
+        );
+    explanation = match &definition.source {
+        SymbolSource::System => explanation.with_field("source", Symbol::from("system")),
+        SymbolSource::Import {
+            name,
+            version,
+            table_offset,
+        } => explanation
+            .with_field("source", Symbol::from("import"))
+            .with_field("table_offset", *table_offset as i64)
+            .with_field(
+                "import",
+                Struct::builder()
+                    .with_field("name", name.as_str())
+                    .with_field("version", *version)
+                    .with_field("id", (id - definition.first_id + 1) as i64)
+                    .build(),
+            ),
+        SymbolSource::Local { table_offset } => explanation
+            .with_field("source", Symbol::from("local"))
+            .with_field("table_offset", *table_offset as i64),
+    };
+    Ok(explanation.build().into())
+}
+
+/// The top-level items of a stream that affect which symbol IDs are defined.
+enum StreamItem {
+    /// A version marker, with its offset.
+    VersionMarker(usize),
+    SymbolTable(LocalSymbolTable),
+    /// A value, with its offset if it was encoded in the stream.
+    Value(Option<usize>),
+    /// The end of the stream, with its offset.
+    EndOfStream(usize),
+}
+
+fn next_item(
+    reader: &mut SystemReader<AnyEncoding, impl IonInput>,
+    catalog: &CommandCatalog,
+) -> Result<StreamItem> {
+    let item = match reader.next_item()? {
+        SystemStreamItem::VersionMarker(marker) => {
+            if marker.major_minor() != (1, 0) {
+                bail!("only Ion 1.0 streams are supported");
+            }
+            StreamItem::VersionMarker(marker.range().start)
+        }
+        SystemStreamItem::SymbolTable(symtab) => {
+            let Some(raw_value) = symtab.as_value().raw() else {
+                // This symbol table came from a macro expansion, so it has no offset.
+                bail!("found an ephemeral symbol table, which is not yet supported")
+            };
+            let offset = raw_value.range().start;
+            let element = Element::try_from(symtab.as_value())?;
+            StreamItem::SymbolTable(LocalSymbolTable::new(offset, &element, catalog))
+        }
+        SystemStreamItem::EncodingDirective(_) => bail!("only Ion 1.0 streams are supported"),
+        SystemStreamItem::Value(value) => {
+            StreamItem::Value(value.raw().map(|raw| raw.range().start))
+        }
+        SystemStreamItem::EndOfStream(end) => StreamItem::EndOfStream(end.range().start),
+        _ => unreachable!("#[non_exhaustive] enum, current variants covered"),
+    };
+    Ok(item)
+}
+
+/// Where the text of a range of symbol IDs comes from.
+enum SymbolSource {
+    System,
+    Import {
+        name: String,
+        version: i64,
+        table_offset: usize,
+    },
+    Local {
+        table_offset: usize,
+    },
+}
+
+struct SymbolDefinition {
+    source: SymbolSource,
+    first_id: usize,
+    count: usize,
+}
+
+/// The symbol IDs defined by the active symbol table, grouped by where their text comes from.
+struct SymbolContext {
+    definitions: Vec<SymbolDefinition>,
+}
+
+impl SymbolContext {
+    fn new() -> Self {
+        let mut context = SymbolContext {
+            definitions: Vec::new(),
+        };
+        context.reset();
+        context
+    }
+
+    /// Goes back to the system symbol table, as the reader does at each version marker.
+    fn reset(&mut self) {
+        self.definitions.clear();
+        self.definitions.push(SymbolDefinition {
+            source: SymbolSource::System,
+            // Includes `$0`, whose text is always unknown.
+            first_id: 0,
+            count: SYSTEM_SYMBOLS.len() + 1,
+        });
+    }
+
+    fn max_id(&self) -> usize {
+        let last = self.definitions.last().unwrap();
+        last.first_id + last.count - 1
+    }
+
+    fn define(&mut self, source: SymbolSource, count: usize) -> usize {
+        let first_id = self.max_id() + 1;
+        if count > 0 {
+            self.definitions.push(SymbolDefinition {
+                source,
+                first_id,
+                count,
+            });
+        }
+        first_id
+    }
+
+    /// Applies the changes that `table` makes and returns a description of them.
+    fn apply(&mut self, table: &LocalSymbolTable) -> Element {
+        if !table.append {
+            self.reset();
+        }
+        let mut imports = Vec::with_capacity(table.imports.len());
+        for import in &table.imports {
+            let source = SymbolSource::Import {
+                name: import.name.clone(),
+                version: import.version,
+                table_offset: table.offset,
+            };
+            let first_id = self.define(source, import.count);
+            imports.push(Element::from(
+                Struct::builder()
+                    .with_field("name", import.name.as_str())
+                    .with_field("version", import.version)
+                    .with_field("first_id", first_id as i64)
+                    .with_field("max_id", self.max_id() as i64)
+                    .build(),
+            ));
+        }
+        let source = SymbolSource::Local {
+            table_offset: table.offset,
+        };
+        let first_id = self.define(source, table.symbols.len());
+        let symbols: Vec<Element> = table
+            .symbols
+            .iter()
+            .map(|text| match text {
+                Some(text) => Element::string(text.as_str()),
+                None => Element::null(IonType::String),
+            })
+            .collect();
+        Struct::builder()
+            .with_field("offset", table.offset as i64)
+            .with_field("append", table.append)
+            .with_field("imports", List::from(imports))
+            .with_field("symbols", List::from(symbols))
+            .with_field("first_id", first_id as i64)
+            .with_field("max_id", self.max_id() as i64)
+            .build()
+            .into()
+    }
+
+    fn definition_of(&self, id: usize) -> Option<&SymbolDefinition> {
+        self.definitions.iter().find(|definition| {
+            (definition.first_id..definition.first_id + definition.count).contains(&id)
+        })
+    }
+}
@@ -1,8 +1,167 @@
+use std::collections::HashMap;
+
+use ion_rs::{Catalog, Element, List, Struct, Symbol, Value};
+
+use crate::catalog::CommandCatalog;
 use crate::commands::command_namespace::IonCliNamespace;
+use crate::commands::symtab::extract::SymtabExtractCommand;
 use crate::commands::symtab::filter::SymtabFilterCommand;
+use crate::commands::symtab::inline::SymtabInlineCommand;
+use crate::commands::symtab::list::SymtabListCommand;
+use crate::commands::symtab::optimize::SymtabOptimizeCommand;
 use crate::commands::IonCliCommand;
 
+pub mod extract;
 pub mod filter;
+pub mod inline;
+pub mod list;
+pub mod optimize;
+
+/// The text of the symbols in Ion 1.0's system symbol table, in order from `$1`.
+pub(crate) const SYSTEM_SYMBOLS: &[&str] = &[
+    "$ion",
+    "$ion_1_0",
+    "$ion_symbol_table",
+    "name",
+    "version",
+    "imports",
+    "symbols",
+    "max_id",
+    "$ion_shared_symbol_table",
+];
+
+/// Returns the serialized form of a shared symbol table.
+pub(crate) fn shared_symbol_table_element(
+    name: &str,
+    version: u32,
+    symbols: impl IntoIterator<Item = String>,
+) -> Element {
+    let symbols: Vec<Element> = symbols.into_iter().map(Element::string).collect();
+    Element::from(
+        Struct::builder()
+            .with_field("name", name)
+            .with_field("version", version as i64)
+            .with_field("symbols", List::from(symbols))
+            .build(),
+    )
+    .with_annotations(["$ion_shared_symbol_table"])
+}
+
+/// Adds one to the count of each symbol used in `element`, including those in nested values.
+pub(crate) fn count_symbols(element: &Element, counts: &mut HashMap<String, u64>) {
+    let mut count = |symbol: &Symbol| {
+        if let Some(text) = symbol.text() {
+            *counts.entry(text.to_owned()).or_default() += 1;
+        }
+    };
+    let mut stack = vec![element];
+    while let Some(element) = stack.pop() {
+        element.annotations().iter().for_each(&mut count);
+        match element.value() {
+            Value::Symbol(symbol) => count(symbol),
+            Value::List(sequence) | Value::SExp(sequence) => stack.extend(sequence.elements()),
+            Value::Struct(strukt) => {
+                for (name, value) in strukt.fields() {
+                    count(name);
+                    stack.push(value);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Returns the symbols in `counts` that are not system symbols, most used first. Symbols that are
+/// used equally often are in alphabetical order.
+pub(crate) fn symbols_by_frequency(counts: HashMap<String, u64>) -> Vec<(String, u64)> {
+    let mut symbols: Vec<(String, u64)> = counts
+        .into_iter()
+        .filter(|(text, _)| !SYSTEM_SYMBOLS.contains(&text.as_str()))
+        .collect();
+    symbols.sort_by(|(left, left_count), (right, right_count)| {
+        right_count.cmp(left_count).then_with(|| left.cmp(right))
+    });
+    symbols
+}
+
+/// The parts of a `$ion_symbol_table` struct that define symbol IDs, interpreted the same way the
+/// reader does.
+pub(crate) struct LocalSymbolTable {
+    pub(crate) offset: usize,
+    pub(crate) append: bool,
+    pub(crate) imports: Vec<Import>,
+    /// The new symbols' text, which is unknown for values in `symbols` that are not strings.
+    pub(crate) symbols: Vec<Option<String>>,
+}
+
+pub(crate) struct Import {
+    pub(crate) name: String,
+    pub(crate) version: i64,
+    /// The number of symbol IDs that the import occupies.
+    pub(crate) count: usize,
+}
+
+impl LocalSymbolTable {
+    pub(crate) fn new(offset: usize, element: &Element, catalog: &CommandCatalog) -> Self {
+        let mut table = LocalSymbolTable {
+            offset,
+            append: false,
+            imports: Vec::new(),
+            symbols: Vec::new(),
+        };
+        let Some(fields) = element.as_struct() else {
+            return table;
+        };
+        match fields.get("imports").map(Element::value) {
+            Some(Value::Symbol(symbol)) if symbol.text() == Some("$ion_symbol_table") => {
+                table.append = true;
+            }
+            Some(Value::List(imports)) => {
+                table.imports = imports
+                    .elements()
+                    .filter_map(|import| Import::new(import, catalog))
+                    .collect();
+            }
+            _ => {}
+        }
+        if let Some(Value::List(symbols)) = fields.get("symbols").map(Element::value) {
+            table.symbols = symbols
+                .elements()
+                .map(|symbol| symbol.as_string().map(str::to_owned))
+                .collect();
+        }
+        table
+    }
+}
+
+impl Import {
+    /// Returns `None` for imports that the reader ignores because they have no name.
+    pub(crate) fn new(element: &Element, catalog: &CommandCatalog) -> Option<Self> {
+        let fields = element.as_struct()?;
+        let name = fields
+            .get("name")
+            .and_then(Element::as_string)
+            .filter(|name| !name.is_empty())?;
+        let version = fields
+            .get("version")
+            .and_then(Element::as_i64)
+            .filter(|version| *version > 0)
+            .unwrap_or(1);
+        let count = match fields.get("max_id").and_then(Element::as_i64) {
+            Some(max_id) if max_id >= 0 => max_id as usize,
+            // The reader has already failed if the table is missing from the catalog.
+            _ => catalog
+                .get_table_with_version(name, version as usize)
+                .map(|table| table.symbols().len())
+                .unwrap_or_default(),
+        };
+        Some(Import {
+            name: name.to_owned(),
+            version,
+            count,
+        })
+    }
+}
 
 pub struct SymtabNamespace;
 
@@ -16,6 +175,12 @@ impl IonCliNamespace for SymtabNamespace {
     }
 
     fn subcommands(&self) -> Vec<Box<dyn IonCliCommand>> {
-        vec![Box::new(SymtabFilterCommand)]
+        vec![
+            Box::new(SymtabFilterCommand),
+            Box::new(SymtabExtractCommand),
+            Box::new(SymtabListCommand),
+            Box::new(SymtabOptimizeCommand),
+            Box::new(SymtabInlineCommand),
+        ]
     }
 }
@@ -0,0 +1,149 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{Read, Write};
+
+use anyhow::{Context, Result};
+use clap::builder::NonEmptyStringValueParser;
+use clap::{value_parser, Arg, ArgMatches, Command};
+use ion_rs::*;
+
+use crate::commands::symtab::{count_symbols, shared_symbol_table_element, symbols_by_frequency};
+use crate::commands::{CommandIo, IonCliCommand, WithIonCliArgument};
+use crate::output::primed_writer;
+
+pub struct SymtabOptimizeCommand;
+
+impl IonCliCommand for SymtabOptimizeCommand {
+    fn name(&self) -> &'static str {
+        "optimize"
+    }
+
+    fn about(&self) -> &'static str {
+        "Re-encodes Ion streams as binary Ion with a single, minimal local symbol table."
+    }
+
+    fn long_about(&self) -> Option<&'static str> {
+        Some(
+            "Reads the values of all inputs and writes them as one binary Ion 1.0 stream whose \
+            only symbol table defines exactly the symbols that the values use. The most used \
+            symbols get the lowest symbol IDs, so that as many symbols as possible are encoded \
+            in a single byte. Reports how many bytes this saved to stderr.\n\n\
+            With `--shared-table`, also writes the symbol table to a file as a shared symbol \
+            table with the given `--name` and `--table-version`, which can be used with \
+            `--catalog` and `--import`.",
+        )
+    }
+
+    fn is_stable(&self) -> bool {
+        false
+    }
+
+    fn is_porcelain(&self) -> bool {
+        false
+    }
+
+    fn configure_args(&self, command: Command) -> Command {
+        command
+            .arg(
+                Arg::new("shared-table")
+                    .long("shared-table")
+                    .short('s')
+                    .value_name("FILE")
+                    .requires("name")
+                    .help("Also write the symbol table to this file as a shared symbol table."),
+            )
+            .arg(
+                Arg::new("name")
+                    .long("name")
+                    .short('n')
+                    .requires("shared-table")
+                    .value_parser(NonEmptyStringValueParser::new())
+                    .help("The name of the shared symbol table, like `com.example.orders`."),
+            )
+            .arg(
+                Arg::new("table-version")
+                    .long("table-version")
+                    .short('t')
+                    .value_parser(value_parser!(u32).range(1..))
+                    .default_value("1")
+                    .help("The version of the shared symbol table."),
+            )
+            .with_input()
+            .with_output()
+    }
+
+    fn run(&self, _command_path: &mut Vec<String>, args: &ArgMatches) -> Result<()> {
+        CommandIo::new(args)?.for_all_inputs(|output, inputs| {
+            let mut input_size = 0;
+            let mut elements = Vec::new();
+            for input in inputs {
+                let input_name = input.name().to_owned();
+                let catalog = input.catalog().clone();
+                let mut bytes = Vec::new();
+                input
+                    .into_source()
+                    .read_to_end(&mut bytes)
+                    .with_context(|| format!("could not read input '{}'", input_name))?;
+                input_size += bytes.len();
+                let mut reader = catalog
+                    .reader(bytes.as_slice())
+                    .with_context(|| format!("Input file '{}' was not valid Ion.", input_name))?;
+                for element in reader.elements() {
+                    elements.push(element?);
+                }
+            }
+
+            let mut counts = HashMap::new();
+            for element in &elements {
+                count_symbols(element, &mut counts);
+            }
+            let symbols: Vec<String> = symbols_by_frequency(counts)
+                .into_iter()
+                .map(|(text, _)| text)
+                .collect();
+
+            let encoded = encode(&elements, &symbols)?;
+            output.write_all(&encoded)?;
+            let saved = input_size as i64 - encoded.len() as i64;
+            eprintln!(
+                "Wrote {} bytes instead of {}, saving {} bytes ({:.1}%).",
+                encoded.len(),
+                input_size,
+                saved,
+                100.0 * saved as f64 / input_size.max(1) as f64
+            );
+
+            if let Some(path) = args.get_one::<String>("shared-table") {
+                let name = args.get_one::<String>("name").unwrap();
+                let version = *args.get_one::<u32>("table-version").unwrap();
+                let file = File::create(path)
+                    .with_context(|| format!("could not create shared table file '{}'", path))?;
+                let mut writer = Writer::new(v1_0::Text.with_format(TextFormat::Pretty), file)?;
+                writer.write(shared_symbol_table_element(name, version, symbols))?;
+                writer.close()?;
+            }
+            Ok(())
+        })
+    }
+}
+
+/// Encodes `elements` as binary Ion with a local symbol table that defines `symbols` in order.
+fn encode(elements: &[Element], symbols: &[String]) -> Result<Vec<u8>> {
+    let symbol_table = Element::from(
+        Struct::builder()
+            .with_field(
+                "symbols",
+                List::from_iter(symbols.iter().map(|text| Element::string(text.as_str()))),
+            )
+            .build(),
+    )
+    .with_annotations(["$ion_symbol_table"]);
+    let symbols: Vec<&str> = symbols.iter().map(String::as_str).collect();
+    let mut writer = primed_writer(Vec::new(), &symbol_table, &symbols)?;
+    for element in elements {
+        writer.write(element)?;
+    }
+    writer.flush()?;
+    let output = writer.close()?;
+    Ok(output.into_inner())
+}
@@ -0,0 +1,190 @@
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::thread;
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+use clap::{value_parser, Arg, ArgAction, ArgMatches, Command};
+use ion_rs::{AnyEncoding, Element, ElementReader, IonInput, Reader};
+
+use crate::commands::{CommandIo, IonCliCommand, WithIonCliArgument};
+use crate::input::CommandInput;
+use crate::output::CommandOutput;
+use crate::transcribe::write_all_as;
+
+/// How long `--follow` waits before checking whether more data has been appended to the file.
+const FOLLOW_POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+pub struct TailCommand;
+
+impl IonCliCommand for TailCommand {
+    fn name(&self) -> &'static str {
+        "tail"
+    }
+
+    fn about(&self) -> &'static str {
+        "Prints the specified number of top-level values at the end of the input stream."
+    }
+
+    fn long_about(&self) -> Option<&'static str> {
+        Some(
+            "Prints the last top-level values in each input. Files are read twice: once to count \
+            their values, which skips over the bodies of binary values without decoding them, and \
+            once to write the last values. STDIN is read once, keeping only the last values in \
+            memory. With `--follow`, `tail` keeps reading the (single, uncompressed) input file \
+            as it grows, printing each new top-level value as soon as it is complete. A text \
+            value at the very end of the file may not be printed until more data is appended, \
+            since (for example) `123` could be followed by more digits or `a` by `::b`.",
+        )
+    }
+
+    fn is_stable(&self) -> bool {
+        false
+    }
+
+    fn is_porcelain(&self) -> bool {
+        false
+    }
+
+    fn configure_args(&self, command: Command) -> Command {
+        command
+            .with_input()
+            .with_output()
+            .with_format()
+            .with_ion_version()
+            .with_syntax_highlighting()
+            .arg(
+                Arg::new("values")
+                    .long("values")
+                    .short('n')
+                    .value_parser(value_parser!(usize))
+                    .allow_negative_numbers(false)
+                    .default_value("10")
+                    .help("Specifies the number of output top-level values."),
+            )
+            .arg(
+                Arg::new("follow")
+                    .long("follow")
+                    .action(ArgAction::SetTrue)
+                    .help("Keep printing values as they are appended to the input file."),
+            )
+    }
+
+    fn run(&self, _command_path: &mut Vec<String>, args: &ArgMatches) -> Result<()> {
+        let num_values = *args.get_one::<usize>("values").unwrap();
+        let follow = args.get_flag("follow");
+        let inputs: Vec<&String> = args
+            .get_many::<String>("input")
+            .into_iter()
+            .flatten()
+            .collect();
+        if follow && inputs.len() != 1 {
+            bail!("--follow requires exactly one input file");
+        }
+        if follow && inputs[0] == "-" {
+            bail!("--follow cannot be used with STDIN");
+        }
+
+        CommandIo::new(args)?.for_each_input(|output, input| {
+            if input.name() == "-" {
+                return write_last_n_from_stream(output, input, num_values);
+            }
+            let file_name = input.name().to_owned();
+            let catalog = input.catalog().clone();
+            let mut reader = input
+                .into_reader()
+                .with_context(|| format!("Input file '{}' was not valid Ion.", file_name))?;
+            let total = count_values(&mut reader)?;
+            let skip = total.saturating_sub(num_values);
+            if follow {
+                let file = File::open(&file_name)?;
+                follow_file(
+                    output,
+                    catalog.reader(BufReader::new(FollowingReader(file)))?,
+                    skip,
+                )
+            } else {
+                let input = CommandInput::decompress(&file_name, File::open(&file_name)?)?
+                    .with_catalog(catalog);
+                let mut reader = input.into_reader()?;
+                for _ in 0..skip {
+                    reader.next()?;
+                }
+                let transform = None::<fn(Element) -> Result<Element>>;
+                write_all_as(&mut reader, output, transform)?;
+                Ok(())
+            }
+        })
+    }
+}
+
+/// Returns the number of top-level values remaining in the reader's input. Binary values are
+/// skipped over without being decoded.
+pub(crate) fn count_values<I: IonInput>(reader: &mut Reader<AnyEncoding, I>) -> Result<usize> {
+    let mut count = 0;
+    while reader.next()?.is_some() {
+        count += 1;
+    }
+    Ok(count)
+}
+
+/// Writes the last `num_values` values of an input that can only be read once.
+fn write_last_n_from_stream(
+    output: &mut CommandOutput,
+    input: CommandInput,
+    num_values: usize,
+) -> Result<()> {
+    let mut reader = input.into_reader()?;
+    let mut last_values = VecDeque::with_capacity(num_values);
+    for element in reader.elements() {
+        if num_values == 0 {
+            break;
+        }
+        if last_values.len() == num_values {
+            last_values.pop_front();
+        }
+        last_values.push_back(element?);
+    }
+    let mut writer = output.as_writer()?;
+    for element in last_values {
+        writer.write(element)?;
+    }
+    writer.close()?;
+    Ok(())
+}
+
+/// Skips `skip` values of the file and then writes each value that follows, waiting for more to be
+/// appended when the end of the file is reached.
+fn follow_file(
+    output: &mut CommandOutput,
+    mut reader: Reader<AnyEncoding, BufReader<FollowingReader>>,
+    skip: usize,
+) -> Result<()> {
+    for _ in 0..skip {
+        reader.next()?;
+    }
+    let mut writer = output.as_writer()?;
+    while let Some(value) = reader.next()? {
+        writer.write(value)?;
+        writer.flush()?;
+    }
+    writer.close()?;
+    Ok(())
+}
+
+/// A reader that waits for data to be appended to a file instead of reporting that it has reached
+/// the end of the file.
+struct FollowingReader(File);
+
+impl Read for FollowingReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        loop {
+            let bytes_read = self.0.read(buf)?;
+            if bytes_read > 0 || buf.is_empty() {
+                return Ok(bytes_read);
+            }
+            thread::sleep(FOLLOW_POLL_INTERVAL);
+        }
+    }
+}
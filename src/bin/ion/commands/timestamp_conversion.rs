@@ -1,5 +1,6 @@
-use anyhow::Result;
-use ion_rs::{Element, IonType};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Datelike, FixedOffset, NaiveDateTime, Timelike};
+use ion_rs::{Element, IonType, Timestamp};
 
 use super::structural_recursion::{map_structure, ElementMapper};
 
@@ -53,7 +54,8 @@ fn is_timestamp_like(s: &str) -> bool {
     }
 }
 
-fn as_timestamp(s: &str) -> Option<Element> {
+/// Returns `s` as an Ion timestamp if it passes [`is_timestamp_like`] and parses as one.
+pub(crate) fn as_timestamp(s: &str) -> Option<Element> {
     if !is_timestamp_like(s) {
         return None;
     }
@@ -61,3 +63,35 @@ fn as_timestamp(s: &str) -> Option<Element> {
         .ok()
         .filter(|e| e.ion_type() == IonType::Timestamp)
 }
+
+/// Converts a point in time expressed as seconds (plus nanoseconds) since the Unix epoch to a UTC
+/// timestamp. The timestamp has second precision unless `nanoseconds` is non-zero.
+pub(crate) fn timestamp_from_epoch(seconds: i64, nanoseconds: u32) -> Result<Timestamp> {
+    let date_time = DateTime::from_timestamp(seconds, nanoseconds)
+        .with_context(|| format!("{seconds}s since the epoch is out of range"))?;
+    let builder = Timestamp::with_ymd(date_time.year() as u32, date_time.month(), date_time.day())
+        .with_hms(date_time.hour(), date_time.minute(), date_time.second());
+    let timestamp = if nanoseconds == 0 {
+        builder.with_offset(0).build()?
+    } else {
+        builder
+            .with_nanoseconds(nanoseconds)
+            .with_offset(0)
+            .build()?
+    };
+    Ok(timestamp)
+}
+
+/// Returns the point in time represented by `timestamp` as seconds (plus nanoseconds) since the
+/// Unix epoch. A timestamp with an unknown offset is treated as UTC, and fractional seconds finer
+/// than nanoseconds are truncated.
+pub(crate) fn timestamp_to_epoch(timestamp: &Timestamp) -> Result<(i64, u32)> {
+    let utc = if timestamp.offset().is_some() {
+        let date_time: DateTime<FixedOffset> = timestamp.clone().try_into()?;
+        date_time.to_utc()
+    } else {
+        let date_time: NaiveDateTime = timestamp.clone().try_into()?;
+        date_time.and_utc()
+    };
+    Ok((utc.timestamp(), utc.timestamp_subsec_nanos()))
+}
@@ -0,0 +1,186 @@
+use anyhow::{Context, Result};
+use bigdecimal::num_bigint::BigInt;
+use ciborium::value::{Integer, Value as CborValue};
+use clap::{ArgMatches, Command};
+use ion_rs::{Element, ElementReader, Int, Sequence, Symbol, Timestamp, TimestampPrecision, Value};
+
+use crate::commands::{CommandIo, IonCliCommand, WithIonCliArgument};
+
+// CBOR tag numbers (RFC 8949, section 3.4) used to represent Ion types.
+
+/// A date/time string in RFC 3339 format.
+pub(crate) const DATE_TIME_TEXT_TAG: u64 = 0;
+/// A date/time expressed as seconds since the Unix epoch.
+pub(crate) const EPOCH_DATE_TIME_TAG: u64 = 1;
+/// An unsigned bignum, encoded as a big-endian byte string.
+pub(crate) const POSITIVE_BIGNUM_TAG: u64 = 2;
+/// A negative bignum `-1 - n`, where `n` is encoded as a big-endian byte string.
+pub(crate) const NEGATIVE_BIGNUM_TAG: u64 = 3;
+/// A decimal fraction, encoded as an `[exponent, mantissa]` array.
+pub(crate) const DECIMAL_FRACTION_TAG: u64 = 4;
+/// A full-date string like `2025-01-31` (RFC 8943).
+pub(crate) const FULL_DATE_TEXT_TAG: u64 = 1004;
+/// Marks a data item as CBOR without changing its meaning. An annotation whose text is one of the
+/// tag numbers above is written as that tag enclosing this one, so that it is not read back as
+/// the type the tag stands for.
+pub(crate) const SELF_DESCRIBED_CBOR_TAG: u64 = 55799;
+
+/// Returns `true` if `tag` is one of the tags that are used to represent Ion types.
+pub(crate) fn is_type_tag(tag: u64) -> bool {
+    matches!(
+        tag,
+        DATE_TIME_TEXT_TAG
+            | EPOCH_DATE_TIME_TAG
+            | POSITIVE_BIGNUM_TAG
+            | NEGATIVE_BIGNUM_TAG
+            | DECIMAL_FRACTION_TAG
+            | FULL_DATE_TEXT_TAG
+    )
+}
+
+pub struct ToCborCommand;
+
+impl IonCliCommand for ToCborCommand {
+    fn name(&self) -> &'static str {
+        "cbor"
+    }
+
+    fn about(&self) -> &'static str {
+        "Converts Ion data to CBOR."
+    }
+
+    fn long_about(&self) -> Option<&'static str> {
+        Some(
+            "Converts Ion data to a CBOR sequence, writing each top-level value as a separate CBOR \
+            data item. Blobs and clobs become byte strings, integers too large for CBOR's major \
+            types become bignums (tags 2 and 3) and decimals become decimal fractions (tag 4). \
+            Timestamps with second precision or finer and a known offset become RFC 3339 \
+            date/time strings (tag 0), and timestamps with day precision become full-date strings \
+            (tag 1004). Other timestamps, which have year, month or minute precision or an \
+            unknown offset, become untagged text strings in Ion's text format. Annotations that \
+            are unsigned integers become CBOR tags; other annotations are discarded. An \
+            annotation that is one of the tag numbers above encloses the value in a \
+            self-described CBOR tag (55799) as well, so that it is not read back as that type.",
+        )
+    }
+
+    fn is_stable(&self) -> bool {
+        false
+    }
+
+    fn is_porcelain(&self) -> bool {
+        false
+    }
+
+    fn configure_args(&self, command: Command) -> Command {
+        command.with_input().with_output()
+    }
+
+    fn run(&self, _command_path: &mut Vec<String>, args: &ArgMatches) -> Result<()> {
+        CommandIo::new(args)?.for_each_input(|output, input| {
+            let input_name = input.name().to_owned();
+            let mut reader = input
+                .into_reader()
+                .with_context(|| format!("Input file '{}' was not valid Ion.", input_name))?;
+            for element in reader.elements() {
+                ciborium::into_writer(&to_cbor_value(&element?)?, &mut *output)?;
+            }
+            Ok(())
+        })
+    }
+}
+
+/// Converts an Ion `Element` to a CBOR value, turning integer annotations into tags.
+fn to_cbor_value(element: &Element) -> Result<CborValue> {
+    let mut cbor = value_to_cbor(element.value())?;
+    // The first annotation becomes the outermost tag.
+    let annotations: Vec<&Symbol> = element.annotations().iter().collect();
+    for annotation in annotations.into_iter().rev() {
+        if let Some(tag) = annotation.text().and_then(|text| text.parse::<u64>().ok()) {
+            if is_type_tag(tag) {
+                cbor = CborValue::Tag(SELF_DESCRIBED_CBOR_TAG, Box::new(cbor));
+            }
+            cbor = CborValue::Tag(tag, Box::new(cbor));
+        }
+    }
+    Ok(cbor)
+}
+
+fn value_to_cbor(value: &Value) -> Result<CborValue> {
+    let cbor = match value {
+        Value::Null(_) => CborValue::Null,
+        Value::Bool(b) => CborValue::Bool(*b),
+        Value::Int(i) => int_to_cbor(i),
+        Value::Float(f) => CborValue::Float(*f),
+        Value::Decimal(d) => {
+            // Negative zero is the only coefficient that cannot be represented as an `Int`.
+            let mantissa = Int::try_from(d.coefficient()).unwrap_or(Int::ZERO);
+            let exponent = CborValue::Integer(d.exponent().into());
+            CborValue::Tag(
+                DECIMAL_FRACTION_TAG,
+                Box::new(CborValue::Array(vec![exponent, int_to_cbor(&mantissa)])),
+            )
+        }
+        Value::Timestamp(t) => timestamp_to_cbor(t),
+        Value::Symbol(s) => CborValue::Text(s.text().unwrap_or("$0").to_owned()),
+        Value::String(s) => CborValue::Text(s.text().to_owned()),
+        Value::Blob(b) | Value::Clob(b) => CborValue::Bytes(b.as_ref().to_vec()),
+        Value::List(s) | Value::SExp(s) => to_cbor_array(s)?,
+        Value::Struct(s) => {
+            let pairs = s
+                .fields()
+                .map(|(name, value)| {
+                    let name = CborValue::Text(name.text().unwrap_or("$0").to_owned());
+                    Ok((name, to_cbor_value(value)?))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            CborValue::Map(pairs)
+        }
+    };
+    Ok(cbor)
+}
+
+fn to_cbor_array(sequence: &Sequence) -> Result<CborValue> {
+    let values = sequence
+        .elements()
+        .map(to_cbor_value)
+        .collect::<Result<Vec<_>>>()?;
+    Ok(CborValue::Array(values))
+}
+
+/// Encodes a timestamp using the CBOR tag for its precision, if there is one.
+fn timestamp_to_cbor(timestamp: &Timestamp) -> CborValue {
+    match timestamp.precision() {
+        // Ion's text representation is RFC 3339 for timestamps with second precision or finer,
+        // as long as the offset is known; RFC 3339 uses `-00:00` to mean UTC instead.
+        TimestampPrecision::Second if timestamp.offset().is_some() => CborValue::Tag(
+            DATE_TIME_TEXT_TAG,
+            Box::new(CborValue::Text(timestamp.to_string())),
+        ),
+        TimestampPrecision::Day => {
+            let date = format!(
+                "{:04}-{:02}-{:02}",
+                timestamp.year(),
+                timestamp.month(),
+                timestamp.day()
+            );
+            CborValue::Tag(FULL_DATE_TEXT_TAG, Box::new(CborValue::Text(date)))
+        }
+        _ => CborValue::Text(timestamp.to_string()),
+    }
+}
+
+/// Encodes an integer using CBOR's major types when it fits, or as a bignum otherwise.
+fn int_to_cbor(int: &Int) -> CborValue {
+    if let Some(small) = int.as_i128().and_then(|i| Integer::try_from(i).ok()) {
+        return CborValue::Integer(small);
+    }
+    let big = BigInt::from_signed_bytes_le(&int.to_le_signed_bytes());
+    let (tag, magnitude) = if int.is_negative() {
+        (NEGATIVE_BIGNUM_TAG, -big - 1)
+    } else {
+        (POSITIVE_BIGNUM_TAG, big)
+    };
+    let (_sign, bytes) = magnitude.to_bytes_be();
+    CborValue::Tag(tag, Box::new(CborValue::Bytes(bytes)))
+}
@@ -0,0 +1,213 @@
+use std::collections::{HashMap, HashSet};
+
+use anyhow::{bail, Result};
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use ion_rs::*;
+
+use crate::commands::from::csv::parse_single_byte;
+use crate::commands::{CommandIo, IonCliCommand, WithIonCliArgument};
+use crate::input::CommandInput;
+
+pub struct ToCsvCommand;
+
+impl IonCliCommand for ToCsvCommand {
+    fn name(&self) -> &'static str {
+        "csv"
+    }
+
+    fn about(&self) -> &'static str {
+        "Converts a stream of Ion structs to CSV."
+    }
+
+    fn long_about(&self) -> Option<&'static str> {
+        Some(
+            "Converts a stream of Ion structs to CSV, writing one row per top-level struct. Unless \
+            `--columns` is specified, the header is the union of all field names in the order in \
+            which they were first seen. Scalars are written as text without loss: timestamps, \
+            decimals and floats use their Ion text representation, and blobs and clobs are \
+            base64-encoded. Nulls are written as empty cells. Lists, s-expressions, annotated \
+            values and (unless `--flatten` is specified) structs are written as compact Ion text. \
+            Symbols and field names with unknown text cannot be written and are an error; the \
+            annotations of the top-level structs are ignored.",
+        )
+    }
+
+    fn is_stable(&self) -> bool {
+        false
+    }
+
+    fn is_porcelain(&self) -> bool {
+        false
+    }
+
+    fn configure_args(&self, command: Command) -> Command {
+        command
+            .arg(
+                Arg::new("columns")
+                    .long("columns")
+                    .short('c')
+                    .value_delimiter(',')
+                    .action(ArgAction::Append)
+                    .help("Comma-separated list of the columns to write, in order. Other fields are ignored."),
+            )
+            .arg(
+                Arg::new("flatten")
+                    .long("flatten")
+                    .action(ArgAction::SetTrue)
+                    .help("Flatten nested structs into columns with dotted names like 'a.b.c'."),
+            )
+            .arg(
+                Arg::new("delimiter")
+                    .long("delimiter")
+                    .short('d')
+                    .default_value(",")
+                    .value_parser(parse_single_byte)
+                    .help("The field delimiter. Use '\\t' for tab-separated output."),
+            )
+            .arg(
+                Arg::new("no-header")
+                    .long("no-header")
+                    .action(ArgAction::SetTrue)
+                    .help("Do not write a header row."),
+            )
+            .with_input()
+            .with_output()
+    }
+
+    fn run(&self, _command_path: &mut Vec<String>, args: &ArgMatches) -> Result<()> {
+        let pinned_columns: Option<Vec<String>> = args
+            .get_many::<String>("columns")
+            .map(|columns| columns.cloned().collect());
+        let flatten = args.get_flag("flatten");
+        let delimiter = *args.get_one::<u8>("delimiter").unwrap();
+        let write_header = !args.get_flag("no-header");
+
+        CommandIo::new(args)?.for_all_inputs(|output, inputs| {
+            let mut csv_writer = csv::WriterBuilder::new()
+                .delimiter(delimiter)
+                .from_writer(output);
+
+            if let Some(columns) = pinned_columns {
+                // The columns are known up front, so rows can be written as they are read.
+                if write_header {
+                    csv_writer.write_record(&columns)?;
+                }
+                for_each_row(inputs, flatten, |row| {
+                    csv_writer.write_record(row_values(&columns, row))?;
+                    Ok(())
+                })?;
+            } else {
+                // The header is the union of all rows' columns, so every row must be read first.
+                let mut rows = Vec::new();
+                for_each_row(inputs, flatten, |row| {
+                    rows.push(row);
+                    Ok(())
+                })?;
+                let columns = discover_columns(&rows);
+                if write_header {
+                    csv_writer.write_record(&columns)?;
+                }
+                for row in rows {
+                    csv_writer.write_record(row_values(&columns, row))?;
+                }
+            }
+            csv_writer.flush()?;
+            Ok(())
+        })
+    }
+}
+
+/// A single row of output: (column name, cell text) pairs in the order their fields were read.
+type Row = Vec<(String, String)>;
+
+/// Reads each top-level value of each input, converting it to a [`Row`].
+fn for_each_row(
+    inputs: Vec<CommandInput>,
+    flatten: bool,
+    mut f: impl FnMut(Row) -> Result<()>,
+) -> Result<()> {
+    for input in inputs {
+        let input_name = input.name().to_owned();
+        let mut reader = input.into_reader()?;
+        for (index, element) in reader.elements().enumerate() {
+            let element = element?;
+            let Some(strukt) = element.as_struct() else {
+                bail!(
+                    "top-level value {} in '{input_name}' is a {}; each row must be a struct",
+                    index + 1,
+                    element.ion_type()
+                );
+            };
+            let mut row = Row::new();
+            collect_cells(strukt, "", flatten, &mut row)?;
+            f(row)?;
+        }
+    }
+    Ok(())
+}
+
+/// Adds a cell to `row` for each field in `strukt`. If `flatten` is true, the fields of nested
+/// structs become cells of their own with their path (joined by `.`) as the column name.
+fn collect_cells(strukt: &Struct, prefix: &str, flatten: bool, row: &mut Row) -> Result<()> {
+    for (name, value) in strukt.fields() {
+        let Some(name) = name.text() else {
+            bail!("a field name has unknown text, which cannot be written as a column name");
+        };
+        let column = if prefix.is_empty() {
+            name.to_owned()
+        } else {
+            format!("{prefix}.{name}")
+        };
+        match value.as_struct() {
+            Some(nested) if flatten && !nested.is_empty() && value.annotations().is_empty() => {
+                collect_cells(nested, &column, flatten, row)?
+            }
+            _ => row.push((column, cell_text(value)?)),
+        }
+    }
+    Ok(())
+}
+
+/// Returns the union of all columns in `rows` in the order in which they were first seen.
+fn discover_columns(rows: &[Row]) -> Vec<String> {
+    let mut seen = HashSet::new();
+    let mut columns = Vec::new();
+    for (column, _) in rows.iter().flatten() {
+        if seen.insert(column.as_str()) {
+            columns.push(column.clone());
+        }
+    }
+    columns
+}
+
+/// Arranges the cells of `row` in the order specified by `columns`. Missing cells are left empty.
+/// If a struct repeats a field name, the last occurrence wins.
+fn row_values(columns: &[String], row: Row) -> Vec<String> {
+    let mut cells: HashMap<String, String> = row.into_iter().collect();
+    columns
+        .iter()
+        .map(|column| cells.remove(column).unwrap_or_default())
+        .collect()
+}
+
+/// Renders a single Ion value as the text of a CSV cell.
+fn cell_text(element: &Element) -> Result<String> {
+    use base64::{engine::general_purpose as base64_encoder, Engine as _};
+    if !element.annotations().is_empty() {
+        // Annotated values are written as Ion text so that their annotations are kept.
+        return Ok(element.to_string());
+    }
+    let text = match element.value() {
+        Value::Null(_) => String::new(),
+        Value::String(s) => s.text().to_owned(),
+        Value::Symbol(s) => match s.text() {
+            Some(text) => text.to_owned(),
+            None => bail!("a symbol has unknown text, which cannot be written as a cell"),
+        },
+        Value::Blob(b) | Value::Clob(b) => base64_encoder::STANDARD.encode(b),
+        // Every other type's Ion text is both unambiguous and lossless. For example, decimals keep
+        // their precision (`1.50`) and timestamps keep their precision and offset.
+        other => other.to_string(),
+    };
+    Ok(text)
+}
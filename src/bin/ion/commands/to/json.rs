@@ -1,15 +1,34 @@
+use std::collections::HashSet;
 use std::io::Write;
 use std::str::FromStr;
 
-use anyhow::{Context, Result};
-use clap::{ArgMatches, Command};
-use ion_rs::*;
-use serde_json::{Map, Number, Value as JsonValue};
-use zstd::zstd_safe::WriteBuf;
+use anyhow::{bail, Context, Result};
+use clap::{arg, value_parser, Arg, ArgMatches, Command};
+use ion_rs::v1_0::RawValueRef;
+use ion_rs::{
+    AnyEncoding, IonInput, IonResult, IonType, LazyField, LazyRawFieldName, LazyRawValue,
+    LazyValue, RawSymbolRef, Reader, ValueRef,
+};
+use serde::Serialize;
+use serde_json::{json, Map, Number, Value as JsonValue};
 
+use crate::commands::timestamp_conversion::timestamp_to_epoch;
 use crate::commands::{CommandIo, IonCliCommand, WithIonCliArgument};
 use crate::output::CommandOutput;
 
+// Keys of the objects that `--typed` uses to represent Ion values that JSON cannot represent
+// natively. `ion from json --typed` recognizes objects with a `$type` field and decodes them.
+
+/// The Ion type of the value, for example `"timestamp"`.
+pub(crate) const TYPE_KEY: &str = "$type";
+/// The value itself, in a JSON representation that depends on its type. `null` for a typed null.
+pub(crate) const VALUE_KEY: &str = "value";
+/// The value's annotations, if any.
+pub(crate) const ANNOTATIONS_KEY: &str = "$annotations";
+/// The `$type` of a symbol, field name or annotation whose text is unknown, which keeps it apart
+/// from one whose text is `$0`. Its `value` is the symbol ID.
+pub(crate) const UNKNOWN_SYMBOL_TYPE: &str = "unknown_symbol";
+
 pub struct ToJsonCommand;
 
 impl IonCliCommand for ToJsonCommand {
@@ -30,47 +49,345 @@ impl IonCliCommand for ToJsonCommand {
     }
 
     fn configure_args(&self, command: Command) -> Command {
-        // NOTE: it may be necessary to add format-specific options. For example, a "pretty" option
-        // would make sense for JSON, but not binary formats like CBOR.
-        command.with_input().with_output()
+        command
+            .arg(arg!(--typed "Preserve all Ion type information by writing values that JSON cannot represent as {\"$type\": ..., \"value\": ...} objects"))
+            .arg(
+                // The ID differs from the flag's name so that `CommandIo` does not mistake this for
+                // the Ion output format.
+                Arg::new("json-format")
+                    .long("format")
+                    .short('f')
+                    .value_parser(["compact", "lines", "pretty"])
+                    .default_value("lines")
+                    .help("'lines' writes one compact value per line, 'compact' writes the whole output on a single line and 'pretty' indents nested values."),
+            )
+            .arg(
+                Arg::new("indent")
+                    .long("indent")
+                    .value_parser(value_parser!(usize))
+                    .default_value("2")
+                    .help("The number of spaces to indent each level of nesting with when using '--format pretty'."),
+            )
+            .arg(arg!(--array "Wrap the output in a single JSON array instead of writing a stream of values"))
+            .arg(arg!(--"sort-keys" "Sort the fields of each object by name"))
+            .arg(
+                Arg::new("big-ints")
+                    .long("big-ints")
+                    .value_parser(["number", "string"])
+                    .default_value("number")
+                    .conflicts_with("typed")
+                    .help("How to write ints beyond ±(2^53 - 1), which many JSON parsers cannot represent exactly."),
+            )
+            .arg(
+                Arg::new("special-floats")
+                    .long("special-floats")
+                    .value_parser(["null", "string"])
+                    .default_value("null")
+                    .conflicts_with("typed")
+                    .help("How to write nan, +inf and -inf. 'string' writes \"NaN\", \"Infinity\" and \"-Infinity\"."),
+            )
+            .arg(
+                Arg::new("timestamps")
+                    .long("timestamps")
+                    .value_parser(["string", "epoch-millis"])
+                    .default_value("string")
+                    .conflicts_with("typed")
+                    .help("How to write timestamps: as Ion text or as milliseconds since the Unix epoch."),
+            )
+            .arg(
+                Arg::new("blobs")
+                    .long("blobs")
+                    .value_parser(["base64", "base64url", "hex"])
+                    .default_value("base64")
+                    .conflicts_with("typed")
+                    .help("How to encode the bytes of blobs and clobs. 'base64url' is unpadded."),
+            )
+            .arg(
+                Arg::new("unknown-symbols")
+                    .long("unknown-symbols")
+                    .value_parser(["null", "sid"])
+                    .default_value("null")
+                    .conflicts_with("typed")
+                    .help("How to write symbols with unknown text: as null or as \"$N\", where N is the symbol ID. Field names with unknown text are an error unless 'sid' is chosen."),
+            )
+            .with_input()
+            .with_output()
+    }
+
+    fn long_about(&self) -> Option<&'static str> {
+        Some(
+            "Converts Ion data to JSON, by default writing one JSON value per line. The conversion \
+            follows Ion's JSON down-conversion guidelines, which discard annotations and any type \
+            information that JSON cannot represent. With `--typed`, every value that JSON cannot \
+            represent natively (including annotated values) is written as an object like \
+            {\"$type\": \"timestamp\", \"value\": \"2025-01-01T\", \"$annotations\": [\"a\"]}, \
+            which `ion from json --typed` converts back to the original Ion value. Symbols, field \
+            names and annotations whose text is unknown are written as \
+            {\"$type\": \"unknown_symbol\", \"value\": 10}, where the value is the symbol ID.",
+        )
     }
 
     fn run(&self, _command_path: &mut Vec<String>, args: &ArgMatches) -> Result<()> {
-        CommandIo::new(args)?.for_each_input(|output, input| {
-            let input_name = input.name().to_owned();
-            let mut reader = Reader::new(AnyEncoding, input.into_source())
-                .with_context(|| format!("Input file '{}' was not valid Ion.", input_name))?;
-            convert(&mut reader, output)
+        let layout = match args.get_one::<String>("json-format").unwrap().as_str() {
+            "compact" => JsonLayout::Compact,
+            "pretty" => JsonLayout::Pretty,
+            _ => JsonLayout::Lines,
+        };
+        let options = JsonOutputOptions {
+            typed: args.get_flag("typed"),
+            layout,
+            indent: " ".repeat(*args.get_one::<usize>("indent").unwrap()),
+            as_array: args.get_flag("array"),
+            sort_keys: args.get_flag("sort-keys"),
+            policies: DownConversionPolicies::from_args(args),
+        };
+        // All inputs are written to the same stream so that `--array` produces a single array.
+        CommandIo::new(args)?.for_all_inputs(|output, inputs| {
+            let mut writer = JsonStreamWriter::new(output, &options);
+            for input in inputs {
+                let input_name = input.name().to_owned();
+                let mut reader = input
+                    .into_reader()
+                    .with_context(|| format!("Input file '{}' was not valid Ion.", input_name))?;
+                convert(&mut reader, &mut writer)?;
+            }
+            writer.finish()
         })
     }
 }
 
-pub fn convert(
+/// How the JSON output is laid out.
+#[derive(Clone, Copy, PartialEq)]
+enum JsonLayout {
+    Compact,
+    Lines,
+    Pretty,
+}
+
+struct JsonOutputOptions {
+    typed: bool,
+    layout: JsonLayout,
+    indent: String,
+    as_array: bool,
+    sort_keys: bool,
+    policies: DownConversionPolicies,
+}
+
+/// Writes a stream of JSON values, either one after another or as the elements of one JSON array.
+struct JsonStreamWriter<'a, 'b> {
+    output: &'a mut CommandOutput<'b>,
+    options: &'a JsonOutputOptions,
+    value_count: usize,
+}
+
+impl<'a, 'b> JsonStreamWriter<'a, 'b> {
+    fn new(output: &'a mut CommandOutput<'b>, options: &'a JsonOutputOptions) -> Self {
+        Self {
+            output,
+            options,
+            value_count: 0,
+        }
+    }
+
+    fn write(&mut self, mut value: JsonValue) -> Result<()> {
+        const FLUSH_EVERY_N: usize = 100;
+        if self.options.sort_keys {
+            sort_keys(&mut value);
+        }
+        let text = match self.options.layout {
+            JsonLayout::Pretty => self.pretty_text(&value)?,
+            JsonLayout::Compact | JsonLayout::Lines => value.to_string(),
+        };
+        if self.options.as_array {
+            let (opening, separator) = match self.options.layout {
+                JsonLayout::Compact => ("[", ","),
+                JsonLayout::Lines | JsonLayout::Pretty => ("[\n", ",\n"),
+            };
+            let prefix = if self.value_count == 0 {
+                opening
+            } else {
+                separator
+            };
+            write!(self.output, "{prefix}")?;
+            if self.options.layout == JsonLayout::Pretty {
+                // Indent the value's lines so that it is nested inside the array.
+                let indented: Vec<String> = text
+                    .lines()
+                    .map(|line| format!("{}{line}", self.options.indent))
+                    .collect();
+                write!(self.output, "{}", indented.join("\n"))?;
+            } else {
+                write!(self.output, "{text}")?;
+            }
+        } else if self.options.layout == JsonLayout::Compact {
+            // Top-level values are separated by a space, which is needed between numbers.
+            let separator = if self.value_count == 0 { "" } else { " " };
+            write!(self.output, "{separator}{text}")?;
+        } else {
+            writeln!(self.output, "{text}")?;
+        }
+        self.value_count += 1;
+        if self.value_count.is_multiple_of(FLUSH_EVERY_N) {
+            self.output.flush()?;
+        }
+        Ok(())
+    }
+
+    fn pretty_text(&self, value: &JsonValue) -> Result<String> {
+        let mut bytes = Vec::new();
+        let formatter =
+            serde_json::ser::PrettyFormatter::with_indent(self.options.indent.as_bytes());
+        let mut serializer = serde_json::Serializer::with_formatter(&mut bytes, formatter);
+        value.serialize(&mut serializer)?;
+        Ok(String::from_utf8(bytes)?)
+    }
+
+    /// Closes the array if the output is being wrapped in one, or ends the line of compact output.
+    fn finish(self) -> Result<()> {
+        if !self.options.as_array {
+            if self.options.layout == JsonLayout::Compact && self.value_count > 0 {
+                writeln!(self.output)?;
+            }
+            return Ok(());
+        }
+        match (self.value_count, self.options.layout) {
+            (0, _) => writeln!(self.output, "[]")?,
+            (_, JsonLayout::Compact) => writeln!(self.output, "]")?,
+            (_, JsonLayout::Lines | JsonLayout::Pretty) => writeln!(self.output, "\n]")?,
+        }
+        Ok(())
+    }
+}
+
+/// Recursively sorts the fields of every object in `value` by name.
+fn sort_keys(value: &mut JsonValue) {
+    match value {
+        JsonValue::Array(values) => values.iter_mut().for_each(sort_keys),
+        JsonValue::Object(map) => {
+            map.sort_keys();
+            map.values_mut().for_each(sort_keys);
+        }
+        _ => {}
+    }
+}
+
+fn convert(
     reader: &mut Reader<AnyEncoding, impl IonInput>,
-    output: &mut CommandOutput,
+    writer: &mut JsonStreamWriter,
 ) -> Result<()> {
-    const FLUSH_EVERY_N: usize = 100;
-    let mut value_count = 0usize;
     while let Some(value) = reader.next()? {
-        writeln!(output, "{}", to_json_value(value)?)?;
-        value_count += 1;
-        if value_count.is_multiple_of(FLUSH_EVERY_N) {
-            output.flush()?;
-        }
+        let json = if writer.options.typed {
+            to_typed_json_value(value)?
+        } else {
+            to_json_value(value, &writer.options.policies)?
+        };
+        writer.write(json)?;
     }
     Ok(())
 }
 
-fn to_json_value(value: LazyValue<AnyEncoding>) -> Result<JsonValue> {
+/// How the default (untyped) conversion handles each kind of Ion value that JSON cannot represent
+/// faithfully.
+struct DownConversionPolicies {
+    big_ints: BigIntPolicy,
+    special_floats: SpecialFloatPolicy,
+    timestamps: TimestampPolicy,
+    lobs: LobPolicy,
+    unknown_symbols: UnknownSymbolPolicy,
+}
+
+impl DownConversionPolicies {
+    fn from_args(args: &ArgMatches) -> Self {
+        let choice = |id: &str| args.get_one::<String>(id).unwrap().as_str();
+        Self {
+            big_ints: match choice("big-ints") {
+                "string" => BigIntPolicy::String,
+                _ => BigIntPolicy::Number,
+            },
+            special_floats: match choice("special-floats") {
+                "string" => SpecialFloatPolicy::String,
+                _ => SpecialFloatPolicy::Null,
+            },
+            timestamps: match choice("timestamps") {
+                "epoch-millis" => TimestampPolicy::EpochMillis,
+                _ => TimestampPolicy::String,
+            },
+            lobs: match choice("blobs") {
+                "base64url" => LobPolicy::Base64Url,
+                "hex" => LobPolicy::Hex,
+                _ => LobPolicy::Base64,
+            },
+            unknown_symbols: match choice("unknown-symbols") {
+                "sid" => UnknownSymbolPolicy::SymbolId,
+                _ => UnknownSymbolPolicy::Null,
+            },
+        }
+    }
+}
+
+enum BigIntPolicy {
+    /// Write every int as a JSON number, however large.
+    Number,
+    /// Write ints that a double cannot represent exactly (beyond ±(2^53 - 1)) as strings.
+    String,
+}
+
+enum SpecialFloatPolicy {
+    /// Write `nan`, `+inf` and `-inf` as `null`, per Ion's JSON down-conversion guidelines.
+    Null,
+    /// Write them as the strings `"NaN"`, `"Infinity"` and `"-Infinity"`.
+    String,
+}
+
+enum TimestampPolicy {
+    /// Write timestamps as strings in Ion's text format.
+    String,
+    /// Write timestamps as the number of milliseconds since the Unix epoch.
+    EpochMillis,
+}
+
+enum LobPolicy {
+    Base64,
+    /// The URL- and filename-safe base64 alphabet (RFC 4648, section 5), without padding.
+    Base64Url,
+    /// Lowercase hexadecimal.
+    Hex,
+}
+
+enum UnknownSymbolPolicy {
+    /// Write symbols with unknown text as `null`.
+    Null,
+    /// Write symbols with unknown text as `"$N"`, where `N` is the symbol ID.
+    SymbolId,
+}
+
+fn to_json_value(
+    value: LazyValue<AnyEncoding>,
+    policies: &DownConversionPolicies,
+) -> Result<JsonValue> {
     use ValueRef::*;
-    let value = match value.read()? {
+    let json = match value.read()? {
         Null(_) => JsonValue::Null,
         Bool(b) => JsonValue::Bool(b),
-        Int(i) => JsonValue::Number(Number::from(i.expect_i128()?)),
+        Int(i) => {
+            // The largest integer that a double (the only number type in many JSON parsers) can
+            // represent exactly.
+            const MAX_SAFE_INTEGER: i64 = (1 << 53) - 1;
+            let is_safe = i
+                .as_i64()
+                .is_some_and(|i| (-MAX_SAFE_INTEGER..=MAX_SAFE_INTEGER).contains(&i));
+            match policies.big_ints {
+                BigIntPolicy::String if !is_safe => JsonValue::String(i.to_string()),
+                _ => JsonValue::Number(Number::from_str(&i.to_string())?),
+            }
+        }
         Float(f) if f.is_finite() => JsonValue::Number(Number::from_f64(f).expect("f64 is finite")),
-        // Special floats like +inf, -inf, and NaN are written as `null` in
-        // accordance with Ion's JSON down-conversion guidelines.
-        Float(_f) => JsonValue::Null,
+        Float(f) => match policies.special_floats {
+            // Special floats like +inf, -inf, and NaN are written as `null` in
+            // accordance with Ion's JSON down-conversion guidelines.
+            SpecialFloatPolicy::Null => JsonValue::Null,
+            SpecialFloatPolicy::String => JsonValue::String(float_text(f)),
+        },
         Decimal(d) => {
             let mut text = d.to_string().replace('d', "e");
             if text.ends_with('.') {
@@ -83,39 +400,233 @@ fn to_json_value(value: LazyValue<AnyEncoding>) -> Result<JsonValue> {
                     .with_context(|| format!("{d} could not be turned into a Number"))?,
             )
         }
-        Timestamp(t) => JsonValue::String(t.to_string()), // Note: normalizes 'Z' to '+00:00' format
-        Symbol(s) => s
-            .text()
-            .map(|text| JsonValue::String(text.to_owned()))
-            .unwrap_or_else(|| JsonValue::Null),
+        Timestamp(t) => match policies.timestamps {
+            TimestampPolicy::String => JsonValue::String(t.to_string()), // Note: normalizes 'Z' to '+00:00' format
+            TimestampPolicy::EpochMillis => {
+                let (seconds, nanoseconds) = timestamp_to_epoch(&t)?;
+                JsonValue::from(seconds * 1000 + i64::from(nanoseconds / 1_000_000))
+            }
+        },
+        Symbol(s) => match (s.text(), &policies.unknown_symbols) {
+            (Some(text), _) => JsonValue::String(text.to_owned()),
+            (None, UnknownSymbolPolicy::Null) => JsonValue::Null,
+            (None, UnknownSymbolPolicy::SymbolId) => {
+                JsonValue::String(format!("${}", symbol_id(value)?))
+            }
+        },
         String(s) => JsonValue::String(s.text().to_owned()),
         Blob(b) | Clob(b) => {
             use base64::{engine::general_purpose as base64_encoder, Engine as _};
-            let base64_text = base64_encoder::STANDARD.encode(b.as_slice());
-            JsonValue::String(base64_text)
+            let bytes = b.data();
+            let text = match policies.lobs {
+                LobPolicy::Base64 => base64_encoder::STANDARD.encode(bytes),
+                LobPolicy::Base64Url => base64_encoder::URL_SAFE_NO_PAD.encode(bytes),
+                LobPolicy::Hex => bytes.iter().map(|byte| format!("{byte:02x}")).collect(),
+            };
+            JsonValue::String(text)
         }
-        SExp(s) => to_json_array(s.iter())?,
-        List(l) => to_json_array(l.iter())?,
+        SExp(s) => to_json_array(s.iter(), policies)?,
+        List(l) => to_json_array(l.iter(), policies)?,
         Struct(s) => {
             let mut map = Map::new();
             for field in s {
                 let field = field?;
-                let name = field.name()?.text().unwrap_or("$0").to_owned();
-                let value = to_json_value(field.value())?;
+                let name = match (field.name()?.text(), &policies.unknown_symbols) {
+                    (Some(text), _) => text.to_owned(),
+                    // JSON field names cannot be `null`.
+                    (None, UnknownSymbolPolicy::Null) => bail!(
+                        "a field name has unknown text; use '--unknown-symbols sid' to write it as \"$N\""
+                    ),
+                    (None, UnknownSymbolPolicy::SymbolId) => format!("${}", field_name_id(&field)?),
+                };
+                let value = to_json_value(field.value(), policies)?;
                 map.insert(name, value);
             }
             JsonValue::Object(map)
         }
     };
-    Ok(value)
+    Ok(json)
+}
+
+/// Returns the symbol ID with which a symbol value was encoded. Symbols produced by a macro
+/// rather than read from the stream have no symbol ID, and are reported as `$0`.
+fn symbol_id(value: LazyValue<AnyEncoding>) -> Result<usize> {
+    if let Some(raw_value) = value.raw() {
+        if let RawValueRef::Symbol(RawSymbolRef::SymbolId(sid)) = raw_value.read()? {
+            return Ok(sid);
+        }
+    }
+    Ok(0)
+}
+
+/// Like [`symbol_id`], but for the name of a struct field.
+pub(crate) fn field_name_id(field: &LazyField<AnyEncoding>) -> Result<usize> {
+    if let Some(raw_name) = field.raw_name() {
+        if let RawSymbolRef::SymbolId(sid) = raw_name.read()? {
+            return Ok(sid);
+        }
+    }
+    Ok(0)
 }
 
 fn to_json_array<'a>(
     ion_values: impl IntoIterator<Item = IonResult<LazyValue<'a, AnyEncoding>>>,
+    policies: &DownConversionPolicies,
 ) -> Result<JsonValue> {
     let result: Result<Vec<JsonValue>> = ion_values
         .into_iter()
-        .flat_map(|v| v.map(to_json_value))
+        .flat_map(|v| v.map(|v| to_json_value(v, policies)))
         .collect();
     Ok(JsonValue::Array(result?))
 }
+
+/// Converts an Ion value to JSON without loss. Values that JSON can represent natively (untyped
+/// nulls, bools, strings, 64-bit ints, lists and structs) are written as-is unless they are
+/// annotated; everything else is written as a `$type` object.
+fn to_typed_json_value(value: LazyValue<AnyEncoding>) -> Result<JsonValue> {
+    use base64::{engine::general_purpose as base64_encoder, Engine as _};
+    use ValueRef::*;
+    if let Symbol(s) = value.read()? {
+        if s.text().is_none() {
+            let annotations = typed_json_annotations(value)?;
+            return Ok(unknown_symbol_json(symbol_id(value)?, annotations));
+        }
+    }
+    // `None` means that the JSON value is a native representation of the Ion value.
+    let (json, ion_type): (JsonValue, Option<IonType>) = match value.read()? {
+        Null(IonType::Null) => (JsonValue::Null, None),
+        Null(ion_type) => (JsonValue::Null, Some(ion_type)),
+        Bool(b) => (JsonValue::Bool(b), None),
+        Int(i) => match i.as_i64() {
+            Some(small) => (JsonValue::from(small), None),
+            None => (JsonValue::String(i.to_string()), Some(IonType::Int)),
+        },
+        Float(f) => (JsonValue::String(float_text(f)), Some(IonType::Float)),
+        Decimal(d) => (JsonValue::String(d.to_string()), Some(IonType::Decimal)),
+        Timestamp(t) => (JsonValue::String(t.to_string()), Some(IonType::Timestamp)),
+        Symbol(s) => (
+            JsonValue::String(s.text().unwrap().to_owned()),
+            Some(IonType::Symbol),
+        ),
+        String(s) => (JsonValue::String(s.text().to_owned()), None),
+        Blob(b) => (
+            JsonValue::String(base64_encoder::STANDARD.encode(b.data())),
+            Some(IonType::Blob),
+        ),
+        Clob(c) => (
+            JsonValue::String(base64_encoder::STANDARD.encode(c.data())),
+            Some(IonType::Clob),
+        ),
+        SExp(s) => (to_typed_json_array(s.iter())?, Some(IonType::SExp)),
+        List(l) => (to_typed_json_array(l.iter())?, None),
+        Struct(s) => {
+            let mut fields = Vec::new();
+            for field in s {
+                let field = field?;
+                // A field name with unknown text is kept as its symbol ID.
+                let name = match field.name()?.text() {
+                    Some(text) => Ok(text.to_owned()),
+                    None => Err(field_name_id(&field)?),
+                };
+                fields.push((name, to_typed_json_value(field.value())?));
+            }
+            let mut seen = HashSet::new();
+            let has_repeated_names = fields
+                .iter()
+                .any(|(name, _)| !seen.insert(name.as_deref().ok()));
+            if has_repeated_names || seen.contains(&None) {
+                // A JSON object cannot repeat a key, and its keys cannot be `unknown_symbol`
+                // objects, so such a struct is written as a list of [name, value] pairs.
+                let pairs = fields
+                    .into_iter()
+                    .map(|(name, value)| {
+                        let name = match name {
+                            Ok(name) => JsonValue::String(name),
+                            Err(sid) => unknown_symbol_json(sid, Vec::new()),
+                        };
+                        json!([name, value])
+                    })
+                    .collect();
+                (JsonValue::Array(pairs), Some(IonType::Struct))
+            } else {
+                // A struct with a `$type` field must itself be wrapped so that it is not mistaken
+                // for a typed value.
+                let is_ambiguous = seen.contains(&Some(TYPE_KEY));
+                let map: Map<_, _> = fields
+                    .into_iter()
+                    .map(|(name, value)| (name.unwrap(), value))
+                    .collect();
+                (
+                    JsonValue::Object(map),
+                    is_ambiguous.then_some(IonType::Struct),
+                )
+            }
+        }
+    };
+
+    let annotations = typed_json_annotations(value)?;
+    if ion_type.is_none() && annotations.is_empty() {
+        return Ok(json);
+    }
+    let mut typed = Map::new();
+    typed.insert(TYPE_KEY.to_owned(), value.ion_type().to_string().into());
+    typed.insert(VALUE_KEY.to_owned(), json);
+    if !annotations.is_empty() {
+        typed.insert(ANNOTATIONS_KEY.to_owned(), JsonValue::Array(annotations));
+    }
+    Ok(JsonValue::Object(typed))
+}
+
+/// Returns the annotations of `value` for a `$type` object.
+fn typed_json_annotations(value: LazyValue<AnyEncoding>) -> Result<Vec<JsonValue>> {
+    // The raw annotations hold the symbol IDs of the annotations whose text is unknown.
+    let mut raw_annotations = value.raw().map(|raw| raw.annotations());
+    value
+        .annotations()
+        .map(|annotation| {
+            let raw_annotation = raw_annotations.as_mut().and_then(Iterator::next);
+            match annotation?.text() {
+                Some(text) => Ok(JsonValue::from(text)),
+                None => {
+                    let sid = match raw_annotation.transpose()? {
+                        Some(RawSymbolRef::SymbolId(sid)) => sid,
+                        _ => 0,
+                    };
+                    Ok(unknown_symbol_json(sid, Vec::new()))
+                }
+            }
+        })
+        .collect()
+}
+
+/// Returns the `$type` object for a symbol whose text is unknown, given its symbol ID.
+fn unknown_symbol_json(sid: usize, annotations: Vec<JsonValue>) -> JsonValue {
+    let mut typed = Map::new();
+    typed.insert(TYPE_KEY.to_owned(), UNKNOWN_SYMBOL_TYPE.into());
+    typed.insert(VALUE_KEY.to_owned(), sid.into());
+    if !annotations.is_empty() {
+        typed.insert(ANNOTATIONS_KEY.to_owned(), JsonValue::Array(annotations));
+    }
+    JsonValue::Object(typed)
+}
+
+fn to_typed_json_array<'a>(
+    ion_values: impl IntoIterator<Item = IonResult<LazyValue<'a, AnyEncoding>>>,
+) -> Result<JsonValue> {
+    let values = ion_values
+        .into_iter()
+        .map(|v| to_typed_json_value(v?))
+        .collect::<Result<Vec<_>>>()?;
+    Ok(JsonValue::Array(values))
+}
+
+/// Returns the text of a float in a form that Rust's `f64::from_str` reads back exactly.
+pub(crate) fn float_text(f: f64) -> String {
+    if f.is_nan() {
+        "NaN".to_owned()
+    } else if f.is_infinite() {
+        if f > 0.0 { "Infinity" } else { "-Infinity" }.to_owned()
+    } else {
+        format!("{f:e}")
+    }
+}
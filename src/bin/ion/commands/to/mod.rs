@@ -1,9 +1,17 @@
 use crate::commands::command_namespace::IonCliNamespace;
 use crate::commands::IonCliCommand;
 
+use crate::commands::to::cbor::ToCborCommand;
+use crate::commands::to::csv::ToCsvCommand;
 use crate::commands::to::json::ToJsonCommand;
+use crate::commands::to::msgpack::ToMsgPackCommand;
+use crate::commands::to::yaml::ToYamlCommand;
 
+pub mod cbor;
+pub mod csv;
 pub mod json;
+pub mod msgpack;
+pub mod yaml;
 
 pub struct ToNamespace;
 
@@ -17,6 +25,12 @@ impl IonCliNamespace for ToNamespace {
     }
 
     fn subcommands(&self) -> Vec<Box<dyn IonCliCommand>> {
-        vec![Box::new(ToJsonCommand)]
+        vec![
+            Box::new(ToCborCommand),
+            Box::new(ToCsvCommand),
+            Box::new(ToJsonCommand),
+            Box::new(ToMsgPackCommand),
+            Box::new(ToYamlCommand),
+        ]
     }
 }
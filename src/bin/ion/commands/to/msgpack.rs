@@ -0,0 +1,163 @@
+use std::str::FromStr;
+
+use anyhow::{Context, Result};
+use clap::{Arg, ArgMatches, Command};
+use ion_rs::{Element, ElementReader, Int, Sequence, Timestamp, Value};
+use rmpv::Value as MsgPackValue;
+
+use crate::commands::timestamp_conversion::timestamp_to_epoch;
+use crate::commands::{CommandIo, IonCliCommand, WithIonCliArgument};
+
+/// The extension type reserved by the MessagePack specification for timestamps.
+pub(crate) const TIMESTAMP_EXT_TYPE: i8 = -1;
+
+pub struct ToMsgPackCommand;
+
+impl IonCliCommand for ToMsgPackCommand {
+    fn name(&self) -> &'static str {
+        "msgpack"
+    }
+
+    fn about(&self) -> &'static str {
+        "Converts Ion data to MessagePack."
+    }
+
+    fn long_about(&self) -> Option<&'static str> {
+        Some(
+            "Converts Ion data to MessagePack, writing each top-level value as a separate \
+            MessagePack object. Blobs and clobs become binary values; a blob or clob whose first \
+            annotation is an integer from 0 to 127 becomes an extension value of that type (the \
+            negative types are reserved by MessagePack). \
+            Timestamps become timestamp extension values or, with `--timestamps string`, strings in \
+            Ion's text format. Like `ion to json`, decimals become floats. Integers too large for \
+            MessagePack are written as strings. Other annotations are discarded.",
+        )
+    }
+
+    fn is_stable(&self) -> bool {
+        false
+    }
+
+    fn is_porcelain(&self) -> bool {
+        false
+    }
+
+    fn configure_args(&self, command: Command) -> Command {
+        command
+            .arg(
+                Arg::new("timestamps")
+                    .long("timestamps")
+                    .value_parser(["ext", "string"])
+                    .default_value("ext")
+                    .help("How to write timestamps: as the timestamp extension type (which normalizes them to UTC) or as Ion text."),
+            )
+            .with_input()
+            .with_output()
+    }
+
+    fn run(&self, _command_path: &mut Vec<String>, args: &ArgMatches) -> Result<()> {
+        let timestamps_as_ext = args.get_one::<String>("timestamps").unwrap() == "ext";
+        CommandIo::new(args)?.for_each_input(|output, input| {
+            let input_name = input.name().to_owned();
+            let mut reader = input
+                .into_reader()
+                .with_context(|| format!("Input file '{}' was not valid Ion.", input_name))?;
+            let converter = MsgPackConverter { timestamps_as_ext };
+            for element in reader.elements() {
+                let value = converter.to_msgpack_value(&element?)?;
+                rmpv::encode::write_value(output, &value)?;
+            }
+            Ok(())
+        })
+    }
+}
+
+struct MsgPackConverter {
+    timestamps_as_ext: bool,
+}
+
+impl MsgPackConverter {
+    fn to_msgpack_value(&self, element: &Element) -> Result<MsgPackValue> {
+        let ext_type = element
+            .annotations()
+            .first()
+            .and_then(|text| text.parse::<i8>().ok())
+            // Negative extension types are reserved by the MessagePack specification.
+            .filter(|ext_type| *ext_type >= 0);
+        let value = match (element.value(), ext_type) {
+            (Value::Blob(bytes) | Value::Clob(bytes), Some(ext_type)) => {
+                MsgPackValue::Ext(ext_type, bytes.as_ref().to_vec())
+            }
+            (value, _) => self.value_to_msgpack(value)?,
+        };
+        Ok(value)
+    }
+
+    fn value_to_msgpack(&self, value: &Value) -> Result<MsgPackValue> {
+        let msgpack = match value {
+            Value::Null(_) => MsgPackValue::Nil,
+            Value::Bool(b) => MsgPackValue::Boolean(*b),
+            Value::Int(i) => int_to_msgpack(i),
+            Value::Float(f) => MsgPackValue::F64(*f),
+            Value::Decimal(d) => {
+                let text = d.to_string().replace('d', "e");
+                let float = f64::from_str(text.trim_end_matches('.'))
+                    .with_context(|| format!("{d} could not be turned into a float"))?;
+                MsgPackValue::F64(float)
+            }
+            Value::Timestamp(t) if self.timestamps_as_ext => encode_timestamp(t)?,
+            Value::Timestamp(t) => MsgPackValue::from(t.to_string()),
+            Value::Symbol(s) => MsgPackValue::from(s.text().unwrap_or("$0")),
+            Value::String(s) => MsgPackValue::from(s.text()),
+            Value::Blob(b) | Value::Clob(b) => MsgPackValue::Binary(b.as_ref().to_vec()),
+            Value::List(s) | Value::SExp(s) => self.to_msgpack_array(s)?,
+            Value::Struct(s) => {
+                let pairs = s
+                    .fields()
+                    .map(|(name, value)| {
+                        let name = MsgPackValue::from(name.text().unwrap_or("$0"));
+                        Ok((name, self.to_msgpack_value(value)?))
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                MsgPackValue::Map(pairs)
+            }
+        };
+        Ok(msgpack)
+    }
+
+    fn to_msgpack_array(&self, sequence: &Sequence) -> Result<MsgPackValue> {
+        let values = sequence
+            .elements()
+            .map(|element| self.to_msgpack_value(element))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(MsgPackValue::Array(values))
+    }
+}
+
+/// MessagePack integers are limited to the range of an `i64` or `u64`; larger integers are written
+/// as strings so that no digits are lost.
+fn int_to_msgpack(int: &Int) -> MsgPackValue {
+    if let Some(i) = int.as_i64() {
+        MsgPackValue::from(i)
+    } else if let Some(u) = int.as_i128().and_then(|i| u64::try_from(i).ok()) {
+        MsgPackValue::from(u)
+    } else {
+        MsgPackValue::from(int.to_string())
+    }
+}
+
+/// Encodes a timestamp using the smallest of the three layouts of the timestamp extension type.
+fn encode_timestamp(timestamp: &Timestamp) -> Result<MsgPackValue> {
+    let (seconds, nanoseconds) = timestamp_to_epoch(timestamp)?;
+    let data = match u32::try_from(seconds) {
+        // timestamp 32: seconds as a u32
+        Ok(seconds) if nanoseconds == 0 => seconds.to_be_bytes().to_vec(),
+        // timestamp 64: nanoseconds in the upper 30 bits, seconds in the lower 34 bits
+        _ if (0..1 << 34).contains(&seconds) => (((nanoseconds as u64) << 34) | seconds as u64)
+            .to_be_bytes()
+            .to_vec(),
+        // timestamp 96: nanoseconds as a u32 followed by seconds as an i64
+        _ => [nanoseconds.to_be_bytes().as_slice(), &seconds.to_be_bytes()].concat(),
+    };
+    Ok(MsgPackValue::Ext(TIMESTAMP_EXT_TYPE, data))
+}
@@ -0,0 +1,203 @@
+use std::collections::HashSet;
+
+use anyhow::{Context, Result};
+use clap::{ArgMatches, Command};
+use ion_rs::{Element, ElementReader, IonType, Sequence, Symbol, Value};
+use serde::Serialize;
+use serde_yaml::value::{Tag, TaggedValue};
+use serde_yaml::{Mapping, Value as YamlValue};
+
+use crate::commands::{CommandIo, IonCliCommand, WithIonCliArgument};
+
+// YAML tags used to represent Ion values that have no native YAML equivalent. `ion from yaml`
+// recognizes each of these, so a round trip through YAML is lossless.
+
+/// `!ion/annotated {annotations: [a, b], value: ...}`, used when a value's annotations cannot be
+/// represented by a single YAML tag.
+pub(crate) const ANNOTATED_TAG: &str = "ion/annotated";
+/// `!ion/null int`, a typed null.
+pub(crate) const NULL_TAG: &str = "ion/null";
+/// `!ion/int "123..."`, an integer too large for YAML's 64-bit integers.
+pub(crate) const INT_TAG: &str = "ion/int";
+/// `!ion/decimal 1.50`, a decimal in Ion text.
+pub(crate) const DECIMAL_TAG: &str = "ion/decimal";
+/// `!ion/timestamp 2025-01-01T00:00Z`, a timestamp in Ion text.
+pub(crate) const TIMESTAMP_TAG: &str = "ion/timestamp";
+/// `!ion/symbol foo`, a symbol.
+pub(crate) const SYMBOL_TAG: &str = "ion/symbol";
+/// `!ion/unknown_symbol 0`, a symbol, field name or annotation with unknown text. This keeps it
+/// apart from one whose text is `$0`. The symbol ID is not kept, so it is always 0.
+pub(crate) const UNKNOWN_SYMBOL_TAG: &str = "ion/unknown_symbol";
+/// `!ion/blob aGk=`, a base64-encoded blob.
+pub(crate) const BLOB_TAG: &str = "ion/blob";
+/// `!ion/clob aGk=`, a base64-encoded clob.
+pub(crate) const CLOB_TAG: &str = "ion/clob";
+/// `!ion/sexp [...]`, an s-expression.
+pub(crate) const SEXP_TAG: &str = "ion/sexp";
+/// `!ion/struct [[name, value], ...]`, a struct with repeated field names, field names with
+/// unknown text or a field named `<<`, which YAML mappings cannot represent.
+pub(crate) const STRUCT_TAG: &str = "ion/struct";
+
+pub struct ToYamlCommand;
+
+impl IonCliCommand for ToYamlCommand {
+    fn name(&self) -> &'static str {
+        "yaml"
+    }
+
+    fn about(&self) -> &'static str {
+        "Converts Ion data to YAML."
+    }
+
+    fn long_about(&self) -> Option<&'static str> {
+        Some(
+            "Converts Ion data to YAML, writing each top-level value as a separate YAML document. \
+            A single annotation is written as a YAML tag (`!annotation`). Ion types without a YAML \
+            equivalent are written using tags in the `ion/` namespace (for example, \
+            `!ion/timestamp` or `!ion/decimal`), which `ion from yaml` understands. Symbols, field \
+            names and annotations whose text is unknown are written as `!ion/unknown_symbol 0`; \
+            their symbol IDs are not kept.",
+        )
+    }
+
+    fn is_stable(&self) -> bool {
+        false
+    }
+
+    fn is_porcelain(&self) -> bool {
+        false
+    }
+
+    fn configure_args(&self, command: Command) -> Command {
+        command.with_input().with_output()
+    }
+
+    fn run(&self, _command_path: &mut Vec<String>, args: &ArgMatches) -> Result<()> {
+        CommandIo::new(args)?.for_each_input(|output, input| {
+            let input_name = input.name().to_owned();
+            let mut reader = input
+                .into_reader()
+                .with_context(|| format!("Input file '{}' was not valid Ion.", input_name))?;
+            // Each value serialized by the same `Serializer` becomes its own YAML document.
+            let mut serializer = serde_yaml::Serializer::new(output);
+            for element in reader.elements() {
+                to_yaml_value(&element?)?.serialize(&mut serializer)?;
+            }
+            Ok(())
+        })
+    }
+}
+
+/// Converts an Ion `Element` (including its annotations) to a YAML value.
+fn to_yaml_value(element: &Element) -> Result<YamlValue> {
+    let value = value_to_yaml(element.value())?;
+    let annotations: Vec<&Symbol> = element.annotations().iter().collect();
+    let yaml = match annotations.as_slice() {
+        [] => value,
+        [annotation]
+            if is_plain_tag(annotation.text()) && !matches!(value, YamlValue::Tagged(_)) =>
+        {
+            tagged(annotation.text().unwrap(), value)
+        }
+        _ => {
+            let mut mapping = Mapping::new();
+            mapping.insert(
+                "annotations".into(),
+                YamlValue::Sequence(annotations.into_iter().map(symbol_to_yaml).collect()),
+            );
+            mapping.insert("value".into(), value);
+            tagged(ANNOTATED_TAG, YamlValue::Mapping(mapping))
+        }
+    };
+    Ok(yaml)
+}
+
+fn value_to_yaml(value: &Value) -> Result<YamlValue> {
+    use base64::{engine::general_purpose as base64_encoder, Engine as _};
+    let yaml = match value {
+        Value::Null(IonType::Null) => YamlValue::Null,
+        Value::Null(ion_type) => tagged(NULL_TAG, ion_type.to_string().into()),
+        Value::Bool(b) => YamlValue::Bool(*b),
+        Value::Int(i) => match i.as_i64() {
+            Some(small) => small.into(),
+            None => tagged(INT_TAG, i.to_string().into()),
+        },
+        Value::Float(f) => (*f).into(),
+        Value::Decimal(d) => tagged(DECIMAL_TAG, d.to_string().into()),
+        Value::Timestamp(t) => tagged(TIMESTAMP_TAG, t.to_string().into()),
+        Value::Symbol(s) => match s.text() {
+            Some(text) => tagged(SYMBOL_TAG, text.into()),
+            None => symbol_to_yaml(s),
+        },
+        Value::String(s) => s.text().into(),
+        Value::Blob(b) => tagged(BLOB_TAG, base64_encoder::STANDARD.encode(b).into()),
+        Value::Clob(c) => tagged(CLOB_TAG, base64_encoder::STANDARD.encode(c).into()),
+        Value::List(l) => to_yaml_sequence(l)?,
+        Value::SExp(s) => tagged(SEXP_TAG, to_yaml_sequence(s)?),
+        Value::Struct(s) => {
+            let mut seen = HashSet::new();
+            let has_repeated_names = s.fields().any(|(name, _)| !seen.insert(name.text()));
+            // Tagged values cannot be written as mapping keys, so a field name with unknown text
+            // is only written in a `[name, value]` pair.
+            let has_unknown_names = seen.contains(&None);
+            // A `<<` key would be read back as a YAML merge key.
+            let has_merge_key = seen.contains(&Some("<<"));
+            if has_repeated_names || has_unknown_names || has_merge_key {
+                let pairs = s
+                    .fields()
+                    .map(|(name, value)| {
+                        Ok(YamlValue::Sequence(vec![
+                            symbol_to_yaml(name),
+                            to_yaml_value(value)?,
+                        ]))
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                tagged(STRUCT_TAG, YamlValue::Sequence(pairs))
+            } else {
+                let mut mapping = Mapping::new();
+                for (name, value) in s.fields() {
+                    mapping.insert(name.text().unwrap().into(), to_yaml_value(value)?);
+                }
+                YamlValue::Mapping(mapping)
+            }
+        }
+    };
+    Ok(yaml)
+}
+
+fn to_yaml_sequence(sequence: &Sequence) -> Result<YamlValue> {
+    let values = sequence
+        .elements()
+        .map(to_yaml_value)
+        .collect::<Result<Vec<_>>>()?;
+    Ok(YamlValue::Sequence(values))
+}
+
+/// Converts a field name or annotation to a YAML string or, if its text is unknown, to an
+/// `!ion/unknown_symbol`.
+fn symbol_to_yaml(symbol: &Symbol) -> YamlValue {
+    match symbol.text() {
+        Some(text) => text.into(),
+        // Values are converted after they have been read into elements, which do not keep the
+        // symbol IDs of symbols with unknown text.
+        None => tagged(UNKNOWN_SYMBOL_TAG, 0.into()),
+    }
+}
+
+fn tagged(tag: &str, value: YamlValue) -> YamlValue {
+    YamlValue::Tagged(Box::new(TaggedValue {
+        tag: Tag::new(tag),
+        value,
+    }))
+}
+
+/// Returns `true` if `annotation` can be written as a YAML tag without escaping and without being
+/// mistaken for one of the `ion/` tags.
+fn is_plain_tag(annotation: Option<&str>) -> bool {
+    annotation.is_some_and(|annotation| {
+        !annotation.is_empty()
+            && annotation
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'))
+    })
+}
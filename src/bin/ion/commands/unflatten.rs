@@ -0,0 +1,252 @@
+use std::collections::HashMap;
+
+use anyhow::{bail, Context, Result};
+use clap::{ArgMatches, Command};
+use ion_rs::{Element, ElementReader, IonType, List, Struct, Symbol, Value};
+
+use crate::commands::flatten::KeyStyle;
+use crate::commands::path::PathStep;
+use crate::commands::{CommandIo, IonCliCommand, WithIonCliArgument};
+
+pub struct UnflattenCommand;
+
+impl IonCliCommand for UnflattenCommand {
+    fn name(&self) -> &'static str {
+        "unflatten"
+    }
+
+    fn about(&self) -> &'static str {
+        "Rebuilds nested structs and lists from structs written by `ion flatten`."
+    }
+
+    fn long_about(&self) -> Option<&'static str> {
+        Some(
+            "Rewrites each top-level struct, whose field names are paths like `a.b` or `a.c[0]`, \
+            as the nested structs and lists those paths describe. This reverses `ion flatten` when \
+            it is given the same `--separator`, `--index-style` and `--annotations` options. If \
+            the first step of every path is a list index, the result is a list. Lists are filled \
+            with nulls where an index is missing, but an index must be less than the number of \
+            fields in the struct. Values other than structs are written as-is. It is an error for a path to lead both to a value and through it, as with `a: 1` and \
+            `a.b: 2`.",
+        )
+    }
+
+    fn is_stable(&self) -> bool {
+        false
+    }
+
+    fn is_porcelain(&self) -> bool {
+        false
+    }
+
+    fn configure_args(&self, command: Command) -> Command {
+        KeyStyle::configure_args(command)
+            .with_input()
+            .with_output()
+            .with_format()
+            .with_ion_version()
+    }
+
+    fn run(&self, _command_path: &mut Vec<String>, args: &ArgMatches) -> Result<()> {
+        let key_style = KeyStyle::from_args(args)?;
+        let annotations_field = args.get_one::<String>("annotations").map(String::as_str);
+        CommandIo::new(args)?.for_each_input(|output, input| {
+            let input_name = input.name().to_owned();
+            let mut reader = input
+                .into_reader()
+                .with_context(|| format!("Input file '{}' was not valid Ion.", input_name))?;
+            let mut writer = output.as_writer()?;
+            for element in reader.elements() {
+                writer.write(unflatten(element?, &key_style, annotations_field)?)?;
+            }
+            writer.close()?;
+            Ok(())
+        })
+    }
+}
+
+/// A value in the tree being rebuilt.
+enum Node {
+    Leaf(Element),
+    Struct(Vec<(String, Node)>),
+    /// Indexes that have not been given a value are `None`.
+    List(Vec<Option<Node>>),
+}
+
+impl Node {
+    /// Returns an empty container of the kind that `step` selects from.
+    fn container_for(step: &PathStep) -> Node {
+        match step {
+            PathStep::Field(_) => Node::Struct(Vec::new()),
+            PathStep::Index(_) => Node::List(Vec::new()),
+        }
+    }
+
+    /// Returns the child of this container at `step`, adding `child` there first if it has none.
+    /// Returns `None` if `step` does not select from this kind of value.
+    fn child(&mut self, step: &PathStep, child: impl FnOnce() -> Node) -> Option<&mut Node> {
+        match (self, step) {
+            (Node::Struct(fields), PathStep::Field(name)) => {
+                let position = match fields.iter().position(|(field, _)| field == name) {
+                    Some(position) => position,
+                    None => {
+                        fields.push((name.to_owned(), child()));
+                        fields.len() - 1
+                    }
+                };
+                Some(&mut fields[position].1)
+            }
+            (Node::List(values), PathStep::Index(index)) => {
+                if values.len() <= *index {
+                    values.resize_with(index + 1, || None);
+                }
+                Some(values[*index].get_or_insert_with(child))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Rebuilds a top-level value from a flattened struct. Values other than structs are returned
+/// as-is.
+fn unflatten(
+    flattened: Element,
+    key_style: &KeyStyle,
+    annotations_field: Option<&str>,
+) -> Result<Element> {
+    let Value::Struct(strukt) = flattened.value() else {
+        return Ok(flattened);
+    };
+    let mut annotations = HashMap::new();
+    let mut fields = Vec::new();
+    for (name, value) in strukt.fields() {
+        let name = name.text().unwrap_or("$0");
+        if Some(name) == annotations_field {
+            annotations.extend(read_annotations(value)?);
+        } else {
+            fields.push((key_style.parse_key(name), name, value));
+        }
+    }
+
+    // Each index of a flattened list has at least one field of its own, so a larger index cannot
+    // have come from `flatten`. Rejecting it keeps a path like `a.99999999999` from allocating a
+    // list of that length.
+    let max_index = fields.len();
+    for (steps, name, _) in &fields {
+        for step in steps {
+            if let PathStep::Index(index) = step {
+                if *index >= max_index {
+                    bail!(
+                        "the index {index} in '{name}' is out of range; the struct has only \
+                        {max_index} fields"
+                    );
+                }
+            }
+        }
+    }
+
+    let mut root = match fields.first() {
+        Some((steps, _, _)) => Node::container_for(&steps[0]),
+        None => Node::Struct(Vec::new()),
+    };
+    for (steps, name, value) in fields {
+        let (last, parents) = steps.split_last().unwrap();
+        let mut node = &mut root;
+        for (step, next) in parents.iter().zip(&steps[1..]) {
+            node = match node.child(step, || Node::container_for(next)) {
+                Some(child @ (Node::Struct(_) | Node::List(_))) => child,
+                _ => bail!("the value of '{name}' conflicts with the value of another field"),
+            };
+        }
+        let leaf = Node::Leaf(value.clone());
+        let added = match (node, last) {
+            // Structs may have repeated field names, so each value is added as a new field.
+            (Node::Struct(fields), PathStep::Field(field)) => {
+                fields.push((field.to_owned(), leaf));
+                true
+            }
+            (node @ Node::List(_), step) => {
+                let mut leaf = Some(leaf);
+                node.child(step, || leaf.take().unwrap());
+                leaf.is_none()
+            }
+            _ => false,
+        };
+        if !added {
+            bail!("the value of '{name}' conflicts with the value of another field");
+        }
+    }
+
+    let element = into_element(root, "", key_style, &annotations);
+    let root_annotations: Vec<Symbol> = flattened.annotations().iter().cloned().collect();
+    Ok(element.with_annotations(root_annotations))
+}
+
+/// Reads the struct of annotations written by `flatten --annotations`.
+fn read_annotations(value: &Element) -> Result<Vec<(String, Vec<Symbol>)>> {
+    let Some(strukt) = value.as_struct() else {
+        bail!("the annotations field must be a struct, found {}", value);
+    };
+    strukt
+        .fields()
+        .map(|(name, annotations)| {
+            let Some(list) = annotations.as_sequence() else {
+                bail!(
+                    "the annotations of a field must be a list, found {}",
+                    annotations
+                );
+            };
+            let symbols = list
+                .elements()
+                .map(|annotation| match annotation.as_text() {
+                    Some(text) => Ok(Symbol::from(text)),
+                    None => bail!("annotations must be symbols, found {}", annotation),
+                })
+                .collect::<Result<Vec<_>>>()?;
+            Ok((name.text().unwrap_or("$0").to_owned(), symbols))
+        })
+        .collect()
+}
+
+/// Converts `node`, whose flattened field name is `key`, into an element, adding the annotations
+/// recorded for it and for its children.
+fn into_element(
+    node: Node,
+    key: &str,
+    key_style: &KeyStyle,
+    annotations: &HashMap<String, Vec<Symbol>>,
+) -> Element {
+    let element = match node {
+        Node::Leaf(element) => element,
+        Node::Struct(fields) => Struct::from_iter(fields.into_iter().map(|(name, child)| {
+            let child_key = key_style.child_key(key, &PathStep::Field(name.clone()));
+            (
+                name,
+                into_element(child, &child_key, key_style, annotations),
+            )
+        }))
+        .into(),
+        Node::List(values) => {
+            let elements: Vec<Element> = values
+                .into_iter()
+                .enumerate()
+                .map(|(index, child)| match child {
+                    Some(child) => {
+                        let child_key = key_style.child_key(key, &PathStep::Index(index));
+                        into_element(child, &child_key, key_style, annotations)
+                    }
+                    None => Element::null(IonType::Null),
+                })
+                .collect();
+            List::from(elements).into()
+        }
+    };
+    match annotations.get(key) {
+        Some(extra) if !key.is_empty() => {
+            let mut all: Vec<Symbol> = element.annotations().iter().cloned().collect();
+            all.extend(extra.iter().cloned());
+            element.with_annotations(all)
+        }
+        _ => element,
+    }
+}
@@ -0,0 +1,173 @@
+use std::collections::HashMap;
+use std::io::{BufReader, BufWriter, Seek, SeekFrom};
+
+use anyhow::{Context, Result};
+use clap::{value_parser, Arg, ArgAction, ArgMatches, Command};
+use ion_rs::{v1_0, AnyEncoding, Element, ElementReader, List, Reader, Struct, Writer};
+
+use crate::commands::hash::DigestType;
+use crate::commands::path::IonPath;
+use crate::commands::{CommandIo, IonCliCommand, WithIonCliArgument};
+
+pub struct UniqCommand;
+
+impl IonCliCommand for UniqCommand {
+    fn name(&self) -> &'static str {
+        "uniq"
+    }
+
+    fn about(&self) -> &'static str {
+        "Removes duplicate top-level values."
+    }
+
+    fn long_about(&self) -> Option<&'static str> {
+        Some(
+            "Writes the first occurrence of each distinct top-level value in the input, which need \
+            not be sorted. Values are duplicates if they are equivalent in the Ion data model or, \
+            with one or more `--key` paths, if the values at those paths are. Rather than the \
+            values themselves, only their Ion Hash digests are kept in memory, but one digest is \
+            kept for every distinct value, so memory use grows with the number of distinct values \
+            and is not bounded. With `--repeated`, only values that \
+            occur more than once are written, each one as soon as its second occurrence is read, \
+            so it is the second occurrence that is written. With `--count`, each distinct value \
+            is written in a struct like `{count: 3, value: ...}` after reading all of the input; \
+            the first occurrences are kept in a temporary file until then.",
+        )
+    }
+
+    fn is_stable(&self) -> bool {
+        false
+    }
+
+    fn is_porcelain(&self) -> bool {
+        false
+    }
+
+    fn configure_args(&self, command: Command) -> Command {
+        command
+            .arg(
+                Arg::new("key")
+                    .long("key")
+                    .short('k')
+                    .action(ArgAction::Append)
+                    .value_name("PATH")
+                    .help("Compare only the values at this path, like `.id`. May be repeated."),
+            )
+            .arg(
+                Arg::new("repeated")
+                    .long("repeated")
+                    .short('d')
+                    .action(ArgAction::SetTrue)
+                    .help("Only write values that occur more than once."),
+            )
+            .arg(
+                Arg::new("count")
+                    .long("count")
+                    .short('c')
+                    .action(ArgAction::SetTrue)
+                    .help("Write the number of occurrences of each distinct value."),
+            )
+            .arg(
+                Arg::new("hash")
+                    .long("hash")
+                    .value_parser(value_parser!(DigestType))
+                    .default_value("sha-256")
+                    .help("The hash algorithm used to compute the digests that identify values."),
+            )
+            .with_input()
+            .with_output()
+            .with_format()
+            .with_ion_version()
+    }
+
+    fn run(&self, _command_path: &mut Vec<String>, args: &ArgMatches) -> Result<()> {
+        let keys = args
+            .get_many::<String>("key")
+            .unwrap_or_default()
+            .map(|key| IonPath::parse(key))
+            .collect::<Result<Vec<_>>>()?;
+        let hasher = *args.get_one::<DigestType>("hash").unwrap();
+        let only_repeated = args.get_flag("repeated");
+        let with_counts = args.get_flag("count");
+
+        CommandIo::new(args)?.for_all_inputs(|output, inputs| {
+            let mut writer = output.as_writer()?;
+            // The number of occurrences of each distinct value, counting no further than needed.
+            // There is an entry for every distinct value, so this grows without bound.
+            let mut counts: HashMap<Vec<u8>, usize> = HashMap::new();
+            // With `--count`, the first occurrences of the distinct values are written to a
+            // temporary file in order, and only their counts and positions are kept in memory.
+            let mut first_occurrences = match with_counts {
+                true => {
+                    let file = tempfile::tempfile().context("could not create a temporary file")?;
+                    Some(Writer::new(v1_0::Binary, BufWriter::new(file))?)
+                }
+                false => None,
+            };
+            let mut first_counts: Vec<usize> = Vec::new();
+            let mut positions: HashMap<Vec<u8>, usize> = HashMap::new();
+
+            for input in inputs {
+                let input_name = input.name().to_owned();
+                let mut reader = input
+                    .into_reader()
+                    .with_context(|| format!("Input file '{}' was not valid Ion.", input_name))?;
+                for element in reader.elements() {
+                    let element = element?;
+                    let digest = if keys.is_empty() {
+                        hasher.hash_it(&element)?
+                    } else {
+                        hasher.hash_it(&key_values(&keys, &element))?
+                    };
+                    if let Some(first_occurrences) = first_occurrences.as_mut() {
+                        match positions.get(&digest) {
+                            Some(&position) => first_counts[position] += 1,
+                            None => {
+                                positions.insert(digest, first_counts.len());
+                                first_counts.push(1);
+                                first_occurrences.write(element)?;
+                            }
+                        }
+                        continue;
+                    }
+                    let count = counts.entry(digest).or_default();
+                    *count += 1;
+                    let write_at = if only_repeated { 2 } else { 1 };
+                    if *count == write_at {
+                        writer.write(element)?;
+                    }
+                    // Keep the count from growing so that it cannot overflow.
+                    *count = (*count).min(write_at);
+                }
+            }
+
+            if let Some(first_occurrences) = first_occurrences {
+                let mut file = first_occurrences.close()?.into_inner()?;
+                file.seek(SeekFrom::Start(0))?;
+                let mut reader = Reader::new(AnyEncoding, BufReader::new(file))?;
+                for (element, count) in reader.elements().zip(first_counts) {
+                    if only_repeated && count < 2 {
+                        continue;
+                    }
+                    let counted = Struct::builder()
+                        .with_field("count", count as i64)
+                        .with_field("value", element?)
+                        .build();
+                    writer.write(Element::from(counted))?;
+                }
+            }
+            writer.close()?;
+            Ok(())
+        })
+    }
+}
+
+/// Returns a value that identifies `element` by the values at each of the key paths: a list with a
+/// one-element list for each key that is present and an empty list for each that is not.
+fn key_values(keys: &[IonPath], element: &Element) -> Element {
+    let values: Vec<Element> = keys
+        .iter()
+        .map(|key| List::from(key.select(element).cloned().into_iter().collect::<Vec<_>>()).into())
+        .collect();
+    List::from(values).into()
+}
@@ -123,6 +123,8 @@ impl<R: Read> Read for HexReader<R> {
 }
 
 #[cfg(test)]
+// The readers under test wrap in-memory `Cursor`s, so byte-at-a-time reads are cheap.
+#[allow(clippy::unbuffered_bytes)]
 mod tests {
     use super::*;
     use std::io::Cursor;
@@ -1,5 +1,7 @@
 use crate::auto_decompress::{decompress, AutoDecompressingReader};
+use crate::catalog::CommandCatalog;
 use anyhow::Result;
+use ion_rs::{AnyEncoding, IonResult, Reader, SystemReader};
 use std::io::{BufReader, Read};
 
 // The number of header bytes to inspect with the `infer` crate to detect compression.
@@ -21,6 +23,7 @@ pub struct CommandInput {
     // This field is retained so commands can print debug information about the input source.
     // It is not currently used, which generates a compile warning.
     compression: CompressionDetected,
+    catalog: CommandCatalog,
 }
 
 impl CommandInput {
@@ -35,6 +38,7 @@ impl CommandInput {
             source: decompressed,
             name: name.into(),
             compression,
+            catalog: CommandCatalog::default(),
         })
     }
 
@@ -48,6 +52,7 @@ impl CommandInput {
             source: BufReader::new(Box::new(source)),
             name: name.into(),
             compression: CompressionDetected::None,
+            catalog: CommandCatalog::default(),
         })
     }
 
@@ -58,6 +63,27 @@ impl CommandInput {
         self.source
     }
 
+    /// Sets the catalog that readers of this input use to resolve shared symbol table imports.
+    pub fn with_catalog(mut self, catalog: CommandCatalog) -> Self {
+        self.catalog = catalog;
+        self
+    }
+
+    /// Returns the catalog that readers of this input use to resolve shared symbol table imports.
+    pub fn catalog(&self) -> &CommandCatalog {
+        &self.catalog
+    }
+
+    /// Constructs a [`Reader`] for this input that resolves imports using its catalog.
+    pub fn into_reader(self) -> IonResult<Reader<AnyEncoding, AutoDecompressingReader>> {
+        self.catalog.reader(self.source)
+    }
+
+    /// Constructs a [`SystemReader`] for this input that resolves imports using its catalog.
+    pub fn into_system_reader(self) -> SystemReader<AnyEncoding, AutoDecompressingReader> {
+        self.catalog.system_reader(self.source)
+    }
+
     /// Returns either:
     /// * the name of the input file that this `CommandInput` represents
     /// * the string `"-"` if this `CommandInput` represents STDIN.
@@ -1,5 +1,6 @@
 mod ansi_codes;
 mod auto_decompress;
+mod catalog;
 mod commands;
 mod file_writer;
 mod hex_reader;
@@ -10,17 +11,30 @@ mod transcribe;
 
 use crate::commands::cat::CatCommand;
 use crate::commands::complaint::SucksCommand;
+use crate::commands::count::CountCommand;
+use crate::commands::diff::DiffCommand;
+use crate::commands::flatten::FlattenCommand;
 use crate::commands::from::FromNamespace;
 use crate::commands::generate::GenerateCommand;
+use crate::commands::grep::GrepCommand;
 use crate::commands::hash::HashCommand;
 use crate::commands::head::HeadCommand;
 use crate::commands::inspect::InspectCommand;
 use crate::commands::jq::JqCommand;
+use crate::commands::merge::MergeCommand;
+use crate::commands::patch::PatchCommand;
 use crate::commands::primitive::PrimitiveCommand;
+use crate::commands::sample::SampleCommand;
 use crate::commands::schema::SchemaNamespace;
+use crate::commands::slice::SliceCommand;
+use crate::commands::sort::SortCommand;
+use crate::commands::split::SplitCommand;
 use crate::commands::stats::StatsCommand;
 use crate::commands::symtab::SymtabNamespace;
+use crate::commands::tail::TailCommand;
 use crate::commands::to::ToNamespace;
+use crate::commands::unflatten::UnflattenCommand;
+use crate::commands::uniq::UniqCommand;
 use anyhow::Result;
 use commands::{IonCliCommand, IonCliNamespace};
 use ion_rs::IonError;
@@ -61,16 +75,29 @@ impl IonCliNamespace for RootCommand {
     fn subcommands(&self) -> Vec<Box<dyn IonCliCommand>> {
         vec![
             Box::new(CatCommand),
+            Box::new(CountCommand),
+            Box::new(DiffCommand),
+            Box::new(FlattenCommand),
             Box::new(FromNamespace),
             Box::new(GenerateCommand),
+            Box::new(GrepCommand),
             Box::new(HashCommand),
             Box::new(HeadCommand),
             Box::new(InspectCommand),
             Box::new(JqCommand),
+            Box::new(MergeCommand),
+            Box::new(PatchCommand),
             Box::new(PrimitiveCommand),
+            Box::new(SampleCommand),
             Box::new(SchemaNamespace),
+            Box::new(SliceCommand),
+            Box::new(SortCommand),
+            Box::new(SplitCommand),
             Box::new(SymtabNamespace),
+            Box::new(TailCommand),
             Box::new(ToNamespace),
+            Box::new(UnflattenCommand),
+            Box::new(UniqCommand),
             Box::new(StatsCommand),
             Box::new(SucksCommand),
         ]
@@ -1,10 +1,13 @@
+use crate::commands::symtab::SYSTEM_SYMBOLS;
 use crate::commands::Format;
 use crate::file_writer::FileWriter;
-use anyhow::bail;
-use ion_rs::{v1_0, v1_1, IonEncoding, Writer};
+use anyhow::{bail, Context};
+use ion_rs::{v1_0, v1_1, Element, IonEncoding, List, SharedSymbolTable, Struct, Symbol, Writer};
 use ion_rs::{IonResult, WriteAsIon};
+use std::collections::HashSet;
 use std::io;
 use std::io::Write;
+use std::rc::Rc;
 use syntect::dumps::from_uncompressed_data;
 use syntect::easy::HighlightLines;
 use syntect::highlighting::{Style, Theme};
@@ -63,6 +66,8 @@ pub enum CommandOutputWriter<'a, 'b> {
     Binary_1_0(Writer<v1_0::Binary, &'b mut CommandOutput<'a>>),
     Text_1_1(Writer<v1_1::Text, &'b mut CommandOutput<'a>>),
     Binary_1_1(Writer<v1_1::Binary, &'b mut CommandOutput<'a>>),
+    /// Binary Ion 1.0 whose symbol table imports a shared symbol table.
+    Importing_1_0(Writer<v1_0::Binary, PrimedOutput<&'b mut CommandOutput<'a>>>),
 }
 
 impl CommandOutputWriter<'_, '_> {
@@ -72,19 +77,20 @@ impl CommandOutputWriter<'_, '_> {
             CommandOutputWriter::Binary_1_0(w) => w.write(value).map(|_| ())?,
             CommandOutputWriter::Text_1_1(w) => w.write(value).map(|_| ())?,
             CommandOutputWriter::Binary_1_1(w) => w.write(value).map(|_| ())?,
+            CommandOutputWriter::Importing_1_0(w) => w.write(value).map(|_| ())?,
         }
 
         Ok(self)
     }
 
     /// Writes bytes of previously encoded values to the output stream.
-    #[allow(dead_code)]
     pub fn flush(&mut self) -> IonResult<()> {
         match self {
             CommandOutputWriter::Text_1_0(w) => w.flush(),
             CommandOutputWriter::Binary_1_0(w) => w.flush(),
             CommandOutputWriter::Text_1_1(w) => w.flush(),
             CommandOutputWriter::Binary_1_1(w) => w.flush(),
+            CommandOutputWriter::Importing_1_0(w) => w.flush(),
         }
     }
 
@@ -94,6 +100,7 @@ impl CommandOutputWriter<'_, '_> {
             CommandOutputWriter::Binary_1_0(w) => w.close().map(|_| ())?,
             CommandOutputWriter::Text_1_1(w) => w.close().map(|_| ())?,
             CommandOutputWriter::Binary_1_1(w) => w.close().map(|_| ())?,
+            CommandOutputWriter::Importing_1_0(w) => w.close().map(|_| ())?,
         }
 
         Ok(())
@@ -118,7 +125,20 @@ impl<'a> CommandOutput<'a> {
     }
 
     pub fn as_writer<'b>(&'b mut self) -> anyhow::Result<CommandOutputWriter<'a, 'b>> {
-        let CommandOutputSpec { format, encoding } = *self.spec();
+        let CommandOutputSpec {
+            format,
+            encoding,
+            import,
+        } = self.spec().clone();
+
+        if let Some(table) = import {
+            if encoding != IonEncoding::Binary_1_0 {
+                bail!("importing a shared symbol table requires binary Ion 1.0 output");
+            }
+            return Ok(CommandOutputWriter::Importing_1_0(importing_writer(
+                self, &table,
+            )?));
+        }
 
         Ok(match (encoding, format) {
             (IonEncoding::Text_1_0, Format::Text(text_format)) => CommandOutputWriter::Text_1_0(
@@ -138,10 +158,118 @@ impl<'a> CommandOutput<'a> {
     }
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Clone)]
 pub struct CommandOutputSpec {
     pub format: Format,
     pub encoding: IonEncoding,
+    /// The shared symbol table that binary output imports, if any.
+    pub import: Option<Rc<SharedSymbolTable>>,
+}
+
+/// The output of a writer that was constructed by [`primed_writer`]. While `discard` is set,
+/// everything written to it is dropped.
+pub struct PrimedOutput<W: Write> {
+    output: W,
+    discard: bool,
+}
+
+impl<W: Write> PrimedOutput<W> {
+    pub fn get_ref(&self) -> &W {
+        &self.output
+    }
+
+    pub fn into_inner(self) -> W {
+        self.output
+    }
+}
+
+impl<W: Write> Write for PrimedOutput<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.discard {
+            return Ok(buf.len());
+        }
+        self.output.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.output.flush()
+    }
+}
+
+/// Constructs a binary Ion 1.0 writer whose symbol table begins with the symbols of `table`.
+pub(crate) fn importing_writer<W: Write>(
+    output: W,
+    table: &SharedSymbolTable,
+) -> anyhow::Result<Writer<v1_0::Binary, PrimedOutput<W>>> {
+    // A shared table may define the same text more than once, including the text of a system
+    // symbol, or have symbols with unknown text. Each symbol ID must still be primed, so those IDs
+    // are given placeholder text that the data cannot be expected to use; the writer then encodes
+    // text that is defined more than once with its first symbol ID.
+    let mut defined: HashSet<&str> = SYSTEM_SYMBOLS.iter().copied().collect();
+    let symbols: Vec<String> = table
+        .symbols()
+        .iter()
+        .enumerate()
+        .map(|(index, symbol)| match symbol.text() {
+            Some(text) if defined.insert(text) => text.to_owned(),
+            _ => format!("\u{0}ion-cli placeholder {}", index),
+        })
+        .collect();
+    let symbols: Vec<&str> = symbols.iter().map(String::as_str).collect();
+    let import = Struct::builder()
+        .with_field("name", table.name())
+        .with_field("version", table.version() as i64)
+        .with_field("max_id", table.symbols().len() as i64)
+        .build();
+    let symbol_table = Element::from(
+        Struct::builder()
+            .with_field("imports", List::from(vec![Element::from(import)]))
+            .build(),
+    )
+    .with_annotations(["$ion_symbol_table"]);
+    primed_writer(output, &symbol_table, &symbols)
+        .with_context(|| format!("cannot import '{}'", table.name()))
+}
+
+/// Constructs a binary Ion 1.0 writer that starts its output with `symbol_table`, a local symbol
+/// table that defines `symbols` in order after the system symbols.
+///
+/// `Writer` cannot be given an initial symbol table, so instead it writes a value containing each
+/// of `symbols` in order, which gives them the same symbol IDs that `symbol_table` does. The symbol
+/// table and value that it writes for them are discarded, and `symbol_table` is written in their
+/// place. The symbol tables that it writes for any other symbols append to that one.
+pub(crate) fn primed_writer<W: Write>(
+    output: W,
+    symbol_table: &Element,
+    symbols: &[&str],
+) -> anyhow::Result<Writer<v1_0::Binary, PrimedOutput<W>>> {
+    let mut defined: HashSet<&str> = SYSTEM_SYMBOLS.iter().copied().collect();
+    for text in symbols {
+        if !defined.insert(text) {
+            bail!("the symbol '{}' is already defined", text);
+        }
+    }
+
+    // The symbol table's fields and annotation are all system symbols, so writing it does not
+    // write another symbol table.
+    let mut encoded = Writer::new(v1_0::Binary, Vec::new())?;
+    encoded.write(symbol_table)?;
+    let encoded = encoded.close()?;
+
+    let output = PrimedOutput {
+        output,
+        discard: false,
+    };
+    // Creating the writer writes an IVM.
+    let mut writer = Writer::new(v1_0::Binary, output)?;
+    // Skip the encoded stream's own IVM.
+    writer.output_mut().output.write_all(&encoded[4..])?;
+    writer.output_mut().discard = true;
+    let symbols: Vec<Symbol> = symbols.iter().map(|text| Symbol::from(*text)).collect();
+    writer.write(symbols.as_slice())?;
+    writer.flush()?;
+    writer.output_mut().discard = false;
+    Ok(writer)
 }
 
 impl Write for HighlightedStreamWriter<'_> {
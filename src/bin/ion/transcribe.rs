@@ -1,56 +1,35 @@
-use crate::commands::Format;
-use anyhow::{bail, Result};
+use crate::output::{CommandOutput, CommandOutputWriter};
+use anyhow::Result;
 use ion_rs::*;
-use std::io::Write;
 
-/// Constructs the appropriate writer for the given format, then writes all values from the
+/// Constructs the appropriate writer for the output's format, then writes all values from the
 /// `Reader` to the new `Writer`, applying an optional mapping function to each element.
 pub(crate) fn write_all_as<I: IonInput, M: Fn(Element) -> Result<Element>>(
     reader: &mut Reader<AnyEncoding, I>,
-    output: &mut impl Write,
-    encoding: IonEncoding,
-    format: Format,
+    output: &mut CommandOutput,
     mapper: Option<M>,
 ) -> Result<usize> {
-    write_n_as(reader, output, encoding, format, usize::MAX, mapper)
+    write_n_as(reader, output, usize::MAX, mapper)
 }
 
-/// Constructs the appropriate writer for the given format, then writes up to `count` values from the
-/// `Reader` to the new `Writer`, applying an optional mapping function to each element.
+/// Constructs the appropriate writer for the output's format, then writes up to `count` values from
+/// the `Reader` to the new `Writer`, applying an optional mapping function to each element.
 pub(crate) fn write_n_as<I: IonInput, M: Fn(Element) -> Result<Element>>(
     reader: &mut Reader<AnyEncoding, I>,
-    output: &mut impl Write,
-    encoding: IonEncoding,
-    format: Format,
+    output: &mut CommandOutput,
     count: usize,
     mapper: Option<M>,
 ) -> Result<usize> {
-    let written = match (encoding, format) {
-        (IonEncoding::Text_1_0, Format::Text(text_format)) => {
-            let mut writer = Writer::new(v1_0::Text.with_format(text_format), output)?;
-            transcribe_n(&mut writer, reader, count, mapper)
-        }
-        (IonEncoding::Text_1_1, Format::Text(text_format)) => {
-            let mut writer = Writer::new(v1_1::Text.with_format(text_format), output)?;
-            transcribe_n(&mut writer, reader, count, mapper)
-        }
-        (IonEncoding::Binary_1_0, Format::Binary) => {
-            let mut writer = Writer::new(v1_0::Binary, output)?;
-            transcribe_n(&mut writer, reader, count, mapper)
-        }
-        (IonEncoding::Binary_1_1, Format::Binary) => {
-            let mut writer = Writer::new(v1_1::Binary, output)?;
-            transcribe_n(&mut writer, reader, count, mapper)
-        }
-        unrecognized => bail!("unsupported format '{:?}'", unrecognized),
-    }?;
+    let mut writer = output.as_writer()?;
+    let written = transcribe_n(&mut writer, reader, count, mapper)?;
+    writer.close()?;
     Ok(written)
 }
 
 /// Writes up to `count` values from the `Reader` to the provided `Writer`,
 /// applying an optional mapping function to each element.
 fn transcribe_n<M: Fn(Element) -> Result<Element>>(
-    writer: &mut Writer<impl Encoding, impl Write>,
+    writer: &mut CommandOutputWriter,
     reader: &mut Reader<impl Decoder, impl IonInput>,
     count: usize,
     mapper: Option<M>,
@@ -1,9 +1,10 @@
 use anyhow::Result;
 use assert_cmd::Command;
-use ion_rs::Element;
+use ion_rs::{Element, IonData};
 use rstest::*;
 use std::fs::File;
 use std::io::{Read, Write};
+use std::path::Path;
 use std::time::Duration;
 use tempfile::TempDir;
 
@@ -280,6 +281,570 @@ mod timestamp_roundtrip_tests {
     }
 }
 
+mod typed_json_tests {
+    use super::*;
+
+    #[rstest]
+    #[case::json_native_values(r#"{name: "x", count: 3, items: [true, null]}"#)]
+    #[case::scalars(
+        r#"{big: 123456789012345678901234567890, f: 2.5e0, d: 1.50, t: 2025-01-01T10:30Z, sym: abc, unknown: $0}"#
+    )]
+    #[case::special_floats("nan +inf -inf -0e0")]
+    #[case::lobs_and_sexps(r#"{data: {{aGVsbG8=}}, text: {{"hi"}}, expr: (+ 1 (a b))}"#)]
+    #[case::typed_nulls("null.int null.struct a::null")]
+    #[case::annotations(r#"a::b::{x: c::1, y: d::[e::"f"]}"#)]
+    #[case::repeated_field_names("{a: 1, a: 2}")]
+    #[case::field_named_type(r#"{'$type': "int", value: 1}"#)]
+    #[case::unknown_symbols_and_dollar_zero_text(r#"'$0' $0 {'$0': 1, $0: 2} {$0: 3} '$0'::$0::x"#)]
+    /// Tests that Ion survives a round trip through typed JSON unchanged
+    fn test_ion_typed_json_ion_roundtrip(#[case] original_ion: &str) -> Result<()> {
+        let mut to_json_cmd = Command::cargo_bin("ion")?;
+        to_json_cmd
+            .args(["to", "json", "--typed"])
+            .timeout(Duration::new(5, 0))
+            .write_stdin(original_ion.as_bytes());
+        let json_output = to_json_cmd.assert().success().get_output().stdout.clone();
+
+        let mut from_json_cmd = Command::cargo_bin("ion")?;
+        from_json_cmd
+            .args(["from", "json", "--typed"])
+            .timeout(Duration::new(5, 0))
+            .write_stdin(json_output);
+        let final_output = from_json_cmd.assert().success().get_output().stdout.clone();
+
+        let original_elements = Element::read_all(original_ion.as_bytes())?;
+        let final_elements = Element::read_all(&final_output)?;
+        // `IonData` compares floats by their bits, so `nan` is equal to itself.
+        assert_eq!(
+            IonData::from(original_elements),
+            IonData::from(final_elements)
+        );
+        Ok(())
+    }
+
+    #[test]
+    /// Tests the shape of the `$type` objects
+    fn test_to_typed_json_output() -> Result<()> {
+        let mut cmd = Command::cargo_bin("ion")?;
+        cmd.args(["to", "json", "--typed"])
+            .timeout(Duration::new(5, 0))
+            .write_stdin("{n: 1, t: a::2025T}");
+        let output = cmd.assert().success().get_output().stdout.clone();
+        assert_eq!(
+            String::from_utf8(output)?,
+            "{\"n\":1,\"t\":{\"$type\":\"timestamp\",\"value\":\"2025T\",\"$annotations\":[\"a\"]}}\n"
+        );
+        Ok(())
+    }
+    #[rstest]
+    #[case::text(br#"$ion_symbol_table::{symbols: [null, "b"]} $10::{$10: $10}"#)]
+    #[case::binary(&[
+        0xe0, 0x01, 0x00, 0xea, 0xe8, 0x81, 0x83, 0xd5, 0x87, 0xb3, 0x8f, 0x81, 0x62, 0xe6, 0x81,
+        0x8a, 0xd3, 0x8a, 0x71, 0x0a,
+    ])]
+    /// Tests that symbols, field names and annotations with unknown text keep their symbol IDs
+    fn test_to_typed_json_unknown_symbol_ids(#[case] input: &[u8]) -> Result<()> {
+        let mut cmd = Command::cargo_bin("ion")?;
+        cmd.args(["to", "json", "--typed"])
+            .timeout(Duration::new(5, 0))
+            .write_stdin(input);
+        let output = cmd.assert().success().get_output().stdout.clone();
+        let unknown = r#"{"$type":"unknown_symbol","value":10}"#;
+        assert_eq!(
+            String::from_utf8(output)?.trim_end(),
+            format!(
+                r#"{{"$type":"struct","value":[[{unknown},{unknown}]],"$annotations":[{unknown}]}}"#
+            )
+        );
+        Ok(())
+    }
+}
+
+mod json_output_format_tests {
+    use super::*;
+
+    #[rstest]
+    #[case::lines(&[], "{\"b\":1,\"a\":[1,2]}\n3\n")]
+    #[case::compact(&["-f", "compact"], "{\"b\":1,\"a\":[1,2]} 3\n")]
+    #[case::compact_array(&["-f", "compact", "--array"], "[{\"b\":1,\"a\":[1,2]},3]\n")]
+    #[case::lines_array(&["--array"], "[\n{\"b\":1,\"a\":[1,2]},\n3\n]\n")]
+    #[case::pretty(
+        &["-f", "pretty"],
+        "{\n  \"b\": 1,\n  \"a\": [\n    1,\n    2\n  ]\n}\n3\n"
+    )]
+    #[case::pretty_array_with_indent(
+        &["-f", "pretty", "--indent", "1", "--array"],
+        "[\n {\n  \"b\": 1,\n  \"a\": [\n   1,\n   2\n  ]\n },\n 3\n]\n"
+    )]
+    #[case::sorted_keys(&["--sort-keys"], "{\"a\":[1,2],\"b\":1}\n3\n")]
+    /// Tests each of the JSON output layouts
+    fn test_to_json_output_format(#[case] args: &[&str], #[case] expected: &str) -> Result<()> {
+        let mut cmd = Command::cargo_bin("ion")?;
+        cmd.args(["to", "json"])
+            .args(args)
+            .timeout(Duration::new(5, 0))
+            .write_stdin("{b: 1, a: [1, 2]} 3");
+        let output = cmd.assert().success().get_output().stdout.clone();
+        assert_eq!(String::from_utf8(output)?, expected);
+        Ok(())
+    }
+
+    #[test]
+    /// Tests that `--array` wraps the values of every input in a single array
+    fn test_to_json_array_spans_inputs() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let mut input_paths = Vec::new();
+        for (index, data) in ["1 2", "3"].iter().enumerate() {
+            let input_path = temp_dir.path().join(format!("input{index}.ion"));
+            File::create(&input_path)?.write_all(data.as_bytes())?;
+            input_paths.push(input_path.to_str().unwrap().to_owned());
+        }
+        let mut cmd = Command::cargo_bin("ion")?;
+        cmd.args(["to", "json", "-f", "compact", "--array"])
+            .args(&input_paths)
+            .timeout(Duration::new(5, 0));
+        let output = cmd.assert().success().get_output().stdout.clone();
+        assert_eq!(String::from_utf8(output)?, "[1,2,3]\n");
+        Ok(())
+    }
+}
+
+mod json_down_conversion_tests {
+    use super::*;
+
+    const INPUT: &str =
+        "[123456789012345678901234567890, 9007199254740991, nan, -inf, 2024-01-01T00:00:01.5Z, {{/+8=}}, $0]";
+
+    #[rstest]
+    #[case::defaults(
+        &[],
+        r#"[123456789012345678901234567890,9007199254740991,null,null,"2024-01-01T00:00:01.5+00:00","/+8=",null]"#
+    )]
+    #[case::big_ints_as_strings(
+        &["--big-ints", "string"],
+        r#"["123456789012345678901234567890",9007199254740991,null,null,"2024-01-01T00:00:01.5+00:00","/+8=",null]"#
+    )]
+    #[case::special_floats_as_strings(
+        &["--special-floats", "string"],
+        r#"[123456789012345678901234567890,9007199254740991,"NaN","-Infinity","2024-01-01T00:00:01.5+00:00","/+8=",null]"#
+    )]
+    #[case::timestamps_as_epoch_millis(
+        &["--timestamps", "epoch-millis"],
+        r#"[123456789012345678901234567890,9007199254740991,null,null,1704067201500,"/+8=",null]"#
+    )]
+    #[case::blobs_as_base64url(
+        &["--blobs", "base64url"],
+        r#"[123456789012345678901234567890,9007199254740991,null,null,"2024-01-01T00:00:01.5+00:00","_-8",null]"#
+    )]
+    #[case::blobs_as_hex(
+        &["--blobs", "hex"],
+        r#"[123456789012345678901234567890,9007199254740991,null,null,"2024-01-01T00:00:01.5+00:00","ffef",null]"#
+    )]
+    #[case::unknown_symbols_as_sids(
+        &["--unknown-symbols", "sid"],
+        r#"[123456789012345678901234567890,9007199254740991,null,null,"2024-01-01T00:00:01.5+00:00","/+8=","$0"]"#
+    )]
+    /// Tests each of the down-conversion policies
+    fn test_to_json_down_conversion_policy(
+        #[case] args: &[&str],
+        #[case] expected: &str,
+    ) -> Result<()> {
+        let mut cmd = Command::cargo_bin("ion")?;
+        cmd.args(["to", "json"])
+            .args(args)
+            .timeout(Duration::new(5, 0))
+            .write_stdin(INPUT);
+        let output = cmd.assert().success().get_output().stdout.clone();
+        assert_eq!(String::from_utf8(output)?.trim_end(), expected);
+        Ok(())
+    }
+
+    #[rstest]
+    #[case::text(br#"$ion_symbol_table::{symbols: [null, "b"]} [$10, $11]"#)]
+    // The same stream in binary: the symbol table's first symbol is a null string.
+    #[case::binary(&[
+        0xe0, 0x01, 0x00, 0xea, 0xe8, 0x81, 0x83, 0xd5, 0x87, 0xb3, 0x8f, 0x81, 0x62, 0xb4, 0x71,
+        0x0a, 0x71, 0x0b,
+    ])]
+    /// Tests that a nonzero symbol ID whose text is unknown is written as that symbol ID
+    fn test_to_json_unknown_symbol_ids(#[case] input: &[u8]) -> Result<()> {
+        let mut cmd = Command::cargo_bin("ion")?;
+        cmd.args(["to", "json", "--unknown-symbols", "sid"])
+            .timeout(Duration::new(5, 0))
+            .write_stdin(input);
+        let output = cmd.assert().success().get_output().stdout.clone();
+        assert_eq!(String::from_utf8(output)?.trim_end(), r#"["$10","b"]"#);
+        Ok(())
+    }
+
+    #[rstest]
+    #[case::text(br#"$ion_symbol_table::{symbols: [null, "b"]} {$10: $11}"#)]
+    #[case::binary(&[
+        0xe0, 0x01, 0x00, 0xea, 0xe8, 0x81, 0x83, 0xd5, 0x87, 0xb3, 0x8f, 0x81, 0x62, 0xd3, 0x8a,
+        0x71, 0x0b,
+    ])]
+    /// Tests that a field name whose text is unknown is written as its symbol ID, and cannot be
+    /// written as null
+    fn test_to_json_unknown_field_names(#[case] input: &[u8]) -> Result<()> {
+        let mut cmd = Command::cargo_bin("ion")?;
+        cmd.args(["to", "json", "--unknown-symbols", "sid"])
+            .timeout(Duration::new(5, 0))
+            .write_stdin(input);
+        let output = cmd.assert().success().get_output().stdout.clone();
+        assert_eq!(String::from_utf8(output)?.trim_end(), r#"{"$10":"b"}"#);
+
+        let mut cmd = Command::cargo_bin("ion")?;
+        cmd.args(["to", "json", "--unknown-symbols", "null"])
+            .timeout(Duration::new(5, 0))
+            .write_stdin(input);
+        cmd.assert().failure();
+        Ok(())
+    }
+}
+
+mod from_csv_tests {
+    use super::*;
+
+    #[rstest]
+    #[case::inferred_types(
+        &[],
+        "id,name,price,seen,active,zip,note\n1,\"Smith, J\",1.50,2025-01-01T10:30:00Z,true,00501,\n",
+        r#"{id: 1, name: "Smith, J", price: 1.50, seen: 2025-01-01T10:30:00Z, active: true, zip: "00501", note: null}"#
+    )]
+    #[case::no_infer(
+        &["--no-infer"],
+        "id,price\n1,1.50\n",
+        r#"{id: "1", price: "1.50"}"#
+    )]
+    #[case::tab_delimited_without_header(
+        &["--delimiter", "\\t", "--no-header"],
+        "a\t2e0\n",
+        r#"{column_1: "a", column_2: 2e0}"#
+    )]
+    #[case::extra_cells(
+        &[],
+        "a\n1,2\n",
+        r#"{a: 1, column_2: 2}"#
+    )]
+    /// Tests that each CSV row becomes a struct and that cell types are inferred as expected
+    fn test_from_csv(
+        #[case] flags: &[&str],
+        #[case] csv_input: &str,
+        #[case] expected_ion: &str,
+    ) -> Result<()> {
+        let mut cmd = Command::cargo_bin("ion")?;
+        cmd.args(["from", "-X", "csv"])
+            .args(flags)
+            .timeout(Duration::new(5, 0))
+            .write_stdin(csv_input.as_bytes());
+
+        let assert = cmd.assert().success();
+        let output = assert.get_output();
+        let actual_ion = Element::read_all(&output.stdout)?;
+        let expected_ion = Element::read_all(expected_ion.as_bytes())?;
+        assert_eq!(expected_ion, actual_ion);
+        Ok(())
+    }
+}
+
+mod to_csv_tests {
+    use super::*;
+
+    #[rstest]
+    #[case::union_of_columns(
+        &[],
+        r#"{id: 1, name: "a,b", price: 1.50, seen: 2025-01-01T10:30Z} {id: 2, tags: [x], data: {{aGk=}}}"#,
+        "id,name,price,seen,tags,data\n1,\"a,b\",1.50,2025-01-01T10:30+00:00,,\n2,,,,[x],aGk=\n"
+    )]
+    #[case::pinned_columns(
+        &["--columns", "c,a", "--no-header"],
+        "{a: 1, b: 2, c: 3}",
+        "3,1\n"
+    )]
+    #[case::flattened(
+        &["--flatten"],
+        "{id: 1, user: {name: bob, address: {zip: \"00501\"}}}",
+        "id,user.name,user.address.zip\n1,bob,00501\n"
+    )]
+    #[case::not_flattened(
+        &[],
+        "{id: 1, user: {name: bob}}",
+        "id,user\n1,{name: bob}\n"
+    )]
+    #[case::annotations_kept(
+        &["--flatten"],
+        r#"{price: usd::1.50, user: x::{name: bob}, note: n::"hi"}"#,
+        "price,user,note\nusd::1.50,x::{name: bob},\"n::\"\"hi\"\"\"\n"
+    )]
+    /// Tests that each top-level struct becomes a CSV row
+    fn test_to_csv(
+        #[case] flags: &[&str],
+        #[case] ion_input: &str,
+        #[case] expected_csv: &str,
+    ) -> Result<()> {
+        let mut cmd = Command::cargo_bin("ion")?;
+        cmd.args(["to", "-X", "csv"])
+            .args(flags)
+            .timeout(Duration::new(5, 0))
+            .write_stdin(ion_input.as_bytes());
+
+        let assert = cmd.assert().success();
+        let output = assert.get_output();
+        assert_eq!(expected_csv, String::from_utf8_lossy(&output.stdout));
+        Ok(())
+    }
+
+    #[test]
+    fn test_to_csv_rejects_non_struct() -> Result<()> {
+        let mut cmd = Command::cargo_bin("ion")?;
+        cmd.args(["to", "-X", "csv"])
+            .timeout(Duration::new(5, 0))
+            .write_stdin("{a: 1} 2");
+        cmd.assert().failure();
+        Ok(())
+    }
+
+    #[rstest]
+    #[case::symbol_value("{a: $0}")]
+    #[case::field_name("{$0: 1}")]
+    /// Tests that symbols with unknown text are an error rather than written as "$0"
+    fn test_to_csv_rejects_unknown_symbols(#[case] ion_input: &str) -> Result<()> {
+        let mut cmd = Command::cargo_bin("ion")?;
+        cmd.args(["to", "-X", "csv"])
+            .timeout(Duration::new(5, 0))
+            .write_stdin(ion_input);
+        cmd.assert().failure();
+        Ok(())
+    }
+}
+
+mod yaml_tests {
+    use super::*;
+
+    #[rstest]
+    #[case::scalars(r#"{name: "x", count: 3, big: 123456789012345678901234567890, ratio: 2.5e0}"#)]
+    #[case::ion_specific_types(
+        r#"{price: 1.50, when: 2025-01-01T10:30:00.000Z, sym: abc, data: {{aGVsbG8=}}, text: {{"hi"}}, expr: (+ 1 2), missing: null.int}"#
+    )]
+    #[case::annotations(
+        r#"{age: years::4, multi: a::b::5, spaced: 'has space'::1, price: usd::1.00}"#
+    )]
+    #[case::repeated_field_names(r#"{a: 1, a: 2}"#)]
+    #[case::merge_key_field_names(r#"{'<<': {a: 1}, b: 2} ['<<', {c: {'<<': [{d: 3}]}}]"#)]
+    #[case::unknown_symbols_and_dollar_zero_text(r#"'$0' $0 {'$0': 1, $0: 2} {$0: 3} '$0'::$0::x"#)]
+    #[case::multiple_documents(r#"first::{a: [1, 2]} "second" third::[]"#)]
+    /// Tests that Ion survives a round trip through YAML unchanged
+    fn test_ion_yaml_ion_roundtrip(#[case] original_ion: &str) -> Result<()> {
+        let mut to_yaml_cmd = Command::cargo_bin("ion")?;
+        to_yaml_cmd
+            .args(["to", "-X", "yaml"])
+            .timeout(Duration::new(5, 0))
+            .write_stdin(original_ion.as_bytes());
+        let yaml_output = to_yaml_cmd.assert().success().get_output().stdout.clone();
+
+        let mut from_yaml_cmd = Command::cargo_bin("ion")?;
+        from_yaml_cmd
+            .args(["from", "-X", "yaml"])
+            .timeout(Duration::new(5, 0))
+            .write_stdin(yaml_output);
+        let final_output = from_yaml_cmd.assert().success().get_output().stdout.clone();
+
+        let original_elements = Element::read_all(original_ion.as_bytes())?;
+        let final_elements = Element::read_all(&final_output)?;
+        assert_eq!(original_elements, final_elements);
+        Ok(())
+    }
+
+    #[test]
+    /// Tests that YAML tags become annotations and that merge keys are resolved
+    fn test_from_yaml_tags_and_merge_keys() -> Result<()> {
+        let yaml = "base: &base {a: 1}\nderived:\n  <<: *base\n  b: !custom [2]\n---\n3\n";
+        let mut cmd = Command::cargo_bin("ion")?;
+        cmd.args(["from", "-X", "yaml"])
+            .timeout(Duration::new(5, 0))
+            .write_stdin(yaml);
+        let output = cmd.assert().success().get_output().stdout.clone();
+        let expected = Element::read_all("{base: {a: 1}, derived: {b: custom::[2], a: 1}} 3")?;
+        assert_eq!(expected, Element::read_all(&output)?);
+        Ok(())
+    }
+}
+
+mod cbor_tests {
+    use super::*;
+
+    #[rstest]
+    #[case::scalars(r#"{name: "x", count: -3, ratio: 2.5e0, ok: true, nothing: null}"#)]
+    #[case::big_ints("123456789012345678901234567890 -123456789012345678901234567890")]
+    #[case::decimals("1.50 -2.5d-3 0.")]
+    #[case::timestamps("2025-01-01T10:30:00.000Z 2025-01-01T10:30:00+01:00 2025-01-31T")]
+    #[case::blobs_and_nested_containers(r#"{data: {{aGVsbG8=}}, items: [1, [2, {a: 3}]]}"#)]
+    #[case::tags_as_annotations(r#"'37'::"x" '55799'::'24'::{a: 1}"#)]
+    #[case::type_tags_as_annotations(
+        r#"'1'::5 '4'::[1, 2] '0'::"x" '2'::{{AQ==}} '1004'::'55799'::2025-01-31T '4'::1.5"#
+    )]
+    /// Tests that Ion survives a round trip through CBOR unchanged
+    fn test_ion_cbor_ion_roundtrip(#[case] original_ion: &str) -> Result<()> {
+        let mut to_cbor_cmd = Command::cargo_bin("ion")?;
+        to_cbor_cmd
+            .args(["to", "-X", "cbor"])
+            .timeout(Duration::new(5, 0))
+            .write_stdin(original_ion.as_bytes());
+        let cbor_output = to_cbor_cmd.assert().success().get_output().stdout.clone();
+
+        let mut from_cbor_cmd = Command::cargo_bin("ion")?;
+        from_cbor_cmd
+            .args(["from", "-X", "cbor"])
+            .timeout(Duration::new(5, 0))
+            .write_stdin(cbor_output);
+        let final_output = from_cbor_cmd.assert().success().get_output().stdout.clone();
+
+        let original_elements = Element::read_all(original_ion.as_bytes())?;
+        let final_elements = Element::read_all(&final_output)?;
+        assert_eq!(original_elements, final_elements);
+        Ok(())
+    }
+
+    #[test]
+    /// Tests that only timestamps with second precision and a known offset use tag 0, and that
+    /// coarser timestamps use tag 1004 or become text
+    fn test_to_cbor_timestamp_precisions() -> Result<()> {
+        let input = "2025-01-31T10:30:00+01:00 2025-01-31T 2025T 2025-01-31T10:30+01:00 \
+            2025-01-31T10:30:00-00:00";
+        let mut cmd = Command::cargo_bin("ion")?;
+        cmd.args(["to", "-X", "cbor"])
+            .timeout(Duration::new(5, 0))
+            .write_stdin(input);
+        let output = cmd.assert().success().get_output().stdout.clone();
+        let mut expected = vec![0xc0, 0x78, 25];
+        expected.extend_from_slice(b"2025-01-31T10:30:00+01:00");
+        expected.extend_from_slice(&[0xd9, 0x03, 0xec, 0x6a]);
+        expected.extend_from_slice(b"2025-01-31");
+        expected.push(0x65);
+        expected.extend_from_slice(b"2025T");
+        expected.push(0x76);
+        expected.extend_from_slice(b"2025-01-31T10:30+01:00");
+        expected.extend_from_slice(&[0x78, 25]);
+        expected.extend_from_slice(b"2025-01-31T10:30:00-00:00");
+        assert_eq!(expected, output);
+        Ok(())
+    }
+
+    #[test]
+    /// Tests that epoch-based date/times become timestamps and that integer map keys become text
+    fn test_from_cbor_epoch_timestamps_and_int_keys() -> Result<()> {
+        // 1(1363896240), 1(1363896240.5), {1: 2}
+        let cbor: &[u8] = &[
+            0xc1, 0x1a, 0x51, 0x4b, 0x67, 0xb0, 0xc1, 0xfb, 0x41, 0xd4, 0x52, 0xd9, 0xec, 0x20,
+            0x00, 0x00, 0xa1, 0x01, 0x02,
+        ];
+        let mut cmd = Command::cargo_bin("ion")?;
+        cmd.args(["from", "-X", "cbor"])
+            .timeout(Duration::new(5, 0))
+            .write_stdin(cbor);
+        let output = cmd.assert().success().get_output().stdout.clone();
+        let expected =
+            Element::read_all("2013-03-21T20:04:00Z 2013-03-21T20:04:00.500000000Z {'1': 2}")?;
+        assert_eq!(expected, Element::read_all(&output)?);
+        Ok(())
+    }
+}
+
+mod msgpack_tests {
+    use super::*;
+
+    #[rstest]
+    #[case::scalars(r#"{name: "x", count: -3, big: 18446744073709551615, ratio: 2.5e0, ok: true, nothing: null}"#)]
+    #[case::blobs_and_ext_types(r#"{{aGVsbG8=}} '5'::{{AQI=}} '127'::{{}}"#)]
+    #[case::timestamps(
+        "1970-01-01T00:00:00Z 2025-01-01T10:30:00.123456789Z 1900-01-01T00:00:00.500000000Z"
+    )]
+    #[case::nested_containers(r#"{items: [1, [2, {a: 3}]], empty: {}}"#)]
+    /// Tests that Ion survives a round trip through MessagePack unchanged
+    fn test_ion_msgpack_ion_roundtrip(#[case] original_ion: &str) -> Result<()> {
+        let mut to_msgpack_cmd = Command::cargo_bin("ion")?;
+        to_msgpack_cmd
+            .args(["to", "-X", "msgpack"])
+            .timeout(Duration::new(5, 0))
+            .write_stdin(original_ion.as_bytes());
+        let msgpack_output = to_msgpack_cmd
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone();
+
+        let mut from_msgpack_cmd = Command::cargo_bin("ion")?;
+        from_msgpack_cmd
+            .args(["from", "-X", "msgpack"])
+            .timeout(Duration::new(5, 0))
+            .write_stdin(msgpack_output);
+        let final_output = from_msgpack_cmd
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone();
+
+        let original_elements = Element::read_all(original_ion.as_bytes())?;
+        let final_elements = Element::read_all(&final_output)?;
+        assert_eq!(original_elements, final_elements);
+        Ok(())
+    }
+
+    #[rstest]
+    #[case::application_type("0", &[0xd4, 0x00, 0x2a])]
+    #[case::timestamp_type("-1", &[0xc4, 0x01, 0x2a])]
+    #[case::reserved_type("-128", &[0xc4, 0x01, 0x2a])]
+    #[case::not_a_type("128", &[0xc4, 0x01, 0x2a])]
+    /// Tests that only annotations from 0 to 127 turn blobs into extension values
+    fn test_to_msgpack_ext_types(#[case] annotation: &str, #[case] expected: &[u8]) -> Result<()> {
+        let mut cmd = Command::cargo_bin("ion")?;
+        cmd.args(["to", "-X", "msgpack"])
+            .timeout(Duration::new(5, 0))
+            .write_stdin(format!("'{annotation}'::{{{{Kg==}}}}"));
+        let output = cmd.assert().success().get_output().stdout.clone();
+        assert_eq!(expected, output.as_slice());
+        Ok(())
+    }
+
+    #[rstest]
+    #[case::ext("ext", "2025-01-01T03:30:00Z")]
+    #[case::string("string", r#""2025-01-01T10:30+07:00""#)]
+    /// Tests each of the `--timestamps` mappings
+    fn test_msgpack_timestamp_mapping(#[case] mapping: &str, #[case] expected: &str) -> Result<()> {
+        let mut to_msgpack_cmd = Command::cargo_bin("ion")?;
+        to_msgpack_cmd
+            .args(["to", "-X", "msgpack", "--timestamps", mapping])
+            .timeout(Duration::new(5, 0))
+            .write_stdin("2025-01-01T10:30+07:00");
+        let msgpack_output = to_msgpack_cmd
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone();
+
+        let mut from_msgpack_cmd = Command::cargo_bin("ion")?;
+        from_msgpack_cmd
+            .args(["from", "-X", "msgpack"])
+            .timeout(Duration::new(5, 0))
+            .write_stdin(msgpack_output);
+        let final_output = from_msgpack_cmd
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone();
+
+        assert_eq!(
+            Element::read_all(expected)?,
+            Element::read_all(&final_output)?
+        );
+        Ok(())
+    }
+}
+
 mod code_gen_tests {
     use super::*;
     use std::fs;
@@ -351,3 +916,1484 @@ mod code_gen_tests {
         Ok(())
     }
 }
+
+mod diff_tests {
+    use super::*;
+
+    /// Writes `left` and `right` to files and runs `ion diff` on them with the given arguments.
+    fn run_diff(args: &[&str], left: &str, right: &str) -> Result<String> {
+        let temp_dir = TempDir::new()?;
+        let left_path = temp_dir.path().join("left.ion");
+        let right_path = temp_dir.path().join("right.ion");
+        File::create(&left_path)?.write_all(left.as_bytes())?;
+        File::create(&right_path)?.write_all(right.as_bytes())?;
+        let mut cmd = Command::cargo_bin("ion")?;
+        cmd.args(["-X", "diff"])
+            .args(args)
+            .arg(&left_path)
+            .arg(&right_path)
+            .timeout(Duration::new(5, 0));
+        let output = cmd.assert().success().get_output().stdout.clone();
+        Ok(String::from_utf8(output)?)
+    }
+
+    #[rstest]
+    #[case::identical("{a: 1, b: [1, 2]}", "{b: [1, 2], a: 1}", "")]
+    #[case::changed_field("{a: 1, b: 2}", "{a: 1, b: 3}", "{op: set, path: [0, b], value: 3}")]
+    #[case::removed_field("{a: 1, b: 2}", "{a: 1}", "{op: remove, path: [0, b]}")]
+    #[case::inserted_element("[1, 3]", "[1, 2, 3]", "{op: insert, path: [0, 1], value: 2}")]
+    #[case::top_level_values(
+        "1 2 3",
+        "2 3 4",
+        "{op: remove, path: [0]} {op: insert, path: [2], value: 4}"
+    )]
+    #[case::annotations(
+        "a::[1]",
+        "b::[2]",
+        "{op: set_annotations, path: [0], annotations: [b]} {op: set, path: [0, 0], value: 2}"
+    )]
+    #[case::type_change("{a: 1}", "{a: \"1\"}", "{op: set, path: [0, a], value: \"1\"}")]
+    /// Tests the patch operations written by `ion diff --patch`
+    fn test_diff_patch(
+        #[case] left: &str,
+        #[case] right: &str,
+        #[case] expected: &str,
+    ) -> Result<()> {
+        let output = run_diff(&["--patch", "-f", "lines"], left, right)?;
+        assert_eq!(Element::read_all(output)?, Element::read_all(expected)?);
+        Ok(())
+    }
+
+    #[test]
+    /// Tests the human-readable summary written by `ion diff`
+    fn test_diff_summary() -> Result<()> {
+        let output = run_diff(&[], "{a: 1, b: [1, 2]} 5", "{a: 2, b: [1, 2, 3]} 5 x")?;
+        assert_eq!(output, "~ [0].a: 1 => 2\n+ [0].b[2]: 3\n+ [2]: x\n");
+        Ok(())
+    }
+
+    #[test]
+    /// Tests that field names with unknown text are shown in paths by their symbol IDs
+    fn test_diff_unknown_field_names() -> Result<()> {
+        let symbols = "$ion_symbol_table::{symbols: [null, null]} ";
+        let left = format!("{symbols}{{$10: 1, a: [{{$11: x}}]}}");
+        let right = format!("{symbols}{{$10: 2, a: [{{$11: y}}]}}");
+        let output = run_diff(&[], &left, &right)?;
+        assert_eq!(output, "~ [0].$10: 1 => 2\n~ [0].a[0].$11: x => y\n");
+        Ok(())
+    }
+
+    #[test]
+    /// Tests that sequences too long to align are compared by position, with a warning that is
+    /// part of the summary but is kept out of the patch
+    fn test_diff_too_many_to_align() -> Result<()> {
+        let left: String = (0..10_001).map(|i| format!("{i} ")).collect();
+        let right: String = (0..10_001).map(|i| format!("a{i} ")).collect();
+        let summary = run_diff(&[], &left, &right)?;
+        assert!(summary.starts_with("~ [0]: 0 => a0\n"));
+        assert!(summary.ends_with(
+            "WARNING: the 10001 and 10001 values that differ at the top level are too many to \
+            align, so they were compared by position\n"
+        ));
+
+        let patch = run_diff(&["--patch", "-f", "lines"], &left, &right)?;
+        assert_eq!(patch.lines().count(), 10_001);
+        assert!(!patch.contains("WARNING"));
+        Ok(())
+    }
+
+    #[test]
+    /// Tests that `ion diff` rejects anything other than two inputs
+    fn test_diff_requires_two_inputs() -> Result<()> {
+        let mut cmd = Command::cargo_bin("ion")?;
+        cmd.args(["-X", "diff"])
+            .timeout(Duration::new(5, 0))
+            .write_stdin("1");
+        cmd.assert().failure();
+        Ok(())
+    }
+}
+
+mod patch_tests {
+    use super::*;
+
+    /// Runs `ion patch` with the given patch on `input` (read from STDIN) and returns its output.
+    fn run_patch(patch: &str, input: &str) -> Result<Vec<Element>> {
+        let temp_dir = TempDir::new()?;
+        let patch_path = temp_dir.path().join("patch.ion");
+        File::create(&patch_path)?.write_all(patch.as_bytes())?;
+        let mut cmd = Command::cargo_bin("ion")?;
+        cmd.args(["-X", "patch", "-f", "lines", "-p"])
+            .arg(&patch_path)
+            .timeout(Duration::new(5, 0))
+            .write_stdin(input);
+        let output = cmd.assert().success().get_output().stdout.clone();
+        Ok(Element::read_all(output)?.into_iter().collect())
+    }
+
+    #[rstest]
+    #[case::set("{op: set, path: [0, a], value: 2}", "{a: 1}", "{a: 2}")]
+    #[case::set_new_field("{op: set, path: \"[0].b\", value: 2}", "{a: 1}", "{a: 1, b: 2}")]
+    #[case::insert_element("{op: insert, path: [0, 1], value: 2}", "[1, 3]", "[1, 2, 3]")]
+    #[case::insert_top_level("{op: insert, path: [1], value: x}", "1 2", "1 x 2")]
+    #[case::remove_field("{op: remove, path: [0, a]}", "{a: 1, b: 2, a: 3}", "{b: 2}")]
+    #[case::remove_top_level("{op: remove, path: [0]}", "1 2", "2")]
+    #[case::annotations(
+        "{op: add_annotations, path: [0, 0], annotations: [b, c]} {op: remove_annotations, path: [0, 0], annotations: [a]}",
+        "(a::1)",
+        "(b::c::1)"
+    )]
+    #[case::set_annotations("{op: set_annotations, path: [0], annotations: []}", "a::b::1", "1")]
+    #[case::change_type_to_string(
+        "{op: change_type, path: [0], type: string}",
+        "a::2024T",
+        "a::\"2024T\""
+    )]
+    #[case::change_type_from_string("{op: change_type, path: [0], type: decimal}", "\"1\"", "1.")]
+    #[case::change_type_to_sexp("{op: change_type, path: [0], type: sexp}", "[1, 2]", "(1 2)")]
+    #[case::merge(
+        "{op: merge, path: [0], value: {a: null, b: {c: 1}, d: 4}}",
+        "{a: 1, b: {e: 2}}",
+        "{b: {e: 2, c: 1}, d: 4}"
+    )]
+    /// Tests each of the patch operations
+    fn test_patch(#[case] patch: &str, #[case] input: &str, #[case] expected: &str) -> Result<()> {
+        let expected: Vec<_> = Element::read_all(expected)?.into_iter().collect();
+        assert_eq!(run_patch(patch, input)?, expected);
+        Ok(())
+    }
+
+    #[rstest]
+    #[case::missing_field("{op: remove, path: [0, b]}", "{a: 1}")]
+    #[case::index_out_of_range("{op: set, path: [0, 2], value: 1}", "[1]")]
+    #[case::impossible_conversion("{op: change_type, path: [0], type: int}", "1.5")]
+    #[case::unknown_operation("{op: frobnicate, path: [0]}", "1")]
+    /// Tests that patch operations that can't be applied are reported as errors
+    fn test_patch_errors(#[case] patch: &str, #[case] input: &str) -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let patch_path = temp_dir.path().join("patch.ion");
+        File::create(&patch_path)?.write_all(patch.as_bytes())?;
+        let mut cmd = Command::cargo_bin("ion")?;
+        cmd.args(["-X", "patch", "-p"])
+            .arg(&patch_path)
+            .timeout(Duration::new(5, 0))
+            .write_stdin(input);
+        cmd.assert().failure();
+        Ok(())
+    }
+
+    #[test]
+    /// Tests that applying the output of `ion diff --patch` to the first input produces the second
+    fn test_patch_applies_diff() -> Result<()> {
+        let left = "{a: 1, b: [1, 2, 3], c: x::y} 5 {r: 1, r: 2}";
+        let right = "{a: 2, b: [0, 1, 3], d: z::y} {r: 2, r: 3} 6";
+        let temp_dir = TempDir::new()?;
+        let left_path = temp_dir.path().join("left.ion");
+        let right_path = temp_dir.path().join("right.ion");
+        File::create(&left_path)?.write_all(left.as_bytes())?;
+        File::create(&right_path)?.write_all(right.as_bytes())?;
+        let mut cmd = Command::cargo_bin("ion")?;
+        cmd.args(["-X", "diff", "--patch"])
+            .arg(&left_path)
+            .arg(&right_path)
+            .timeout(Duration::new(5, 0));
+        let patch = cmd.assert().success().get_output().stdout.clone();
+        let patched = run_patch(std::str::from_utf8(&patch)?, left)?;
+        let expected: Vec<_> = Element::read_all(right)?.into_iter().collect();
+        assert_eq!(IonData::from(patched), IonData::from(expected));
+        Ok(())
+    }
+}
+
+mod sort_tests {
+    use super::*;
+
+    #[rstest]
+    #[case::whole_values(&[], "3 1.5 \"a\" null 2e0 [1] 2024T 0", "null 0 1.5 2e0 3 2024T \"a\" [1]")]
+    #[case::reverse(&["-r"], "1 3 2", "3 2 1")]
+    #[case::numbers_of_mixed_types(&[], "10 2.5 1e1 -1 9.99", "-1 2.5 9.99 10 1e1")]
+    #[case::timestamps_by_instant(
+        &["--key", ".t"],
+        "{t: 2000-01-01T00:30:00Z} {t: 2000-01-01T01:00:00+01:00} {t: 1999T}",
+        "{t: 1999T} {t: 2000-01-01T01:00:00+01:00} {t: 2000-01-01T00:30:00Z}"
+    )]
+    #[case::missing_keys_first(&["-k", ".a"], "{a: 2} {b: 1} {a: 1}", "{b: 1} {a: 1} {a: 2}")]
+    #[case::multiple_keys(
+        &["-k", ".a", "-k", ".b[0]"],
+        "{a: 1, b: [3]} {a: 0, b: [9]} {a: 1, b: [2]}",
+        "{a: 0, b: [9]} {a: 1, b: [2]} {a: 1, b: [3]}"
+    )]
+    #[case::stable(&["-k", ".a"], "{a: 1, n: 1} {a: 0} {a: 1, n: 2}", "{a: 0} {a: 1, n: 1} {a: 1, n: 2}")]
+    #[case::stable_descending(&["-r", "-k", ".a"], "{a: 1, n: 1} {a: 0} {a: 1, n: 2}", "{a: 1, n: 1} {a: 1, n: 2} {a: 0}")]
+    #[case::merged_chunks(
+        &["--chunk-size", "2", "-k", ".a"],
+        "{a: 5} {a: 1, n: 1} {a: 4} {a: 1, n: 2} {a: 3} {a: 2} {a: 1, n: 3}",
+        "{a: 1, n: 1} {a: 1, n: 2} {a: 1, n: 3} {a: 2} {a: 3} {a: 4} {a: 5}"
+    )]
+    #[case::merged_in_levels(
+        &["--chunk-size", "1", "--merge-width", "2", "-k", ".a"],
+        "{a: 5} {a: 1, n: 1} {a: 4} {a: 1, n: 2} {a: 3} {a: 2} {a: 1, n: 3} {a: 0} {a: 1, n: 4} {a: 6}",
+        "{a: 0} {a: 1, n: 1} {a: 1, n: 2} {a: 1, n: 3} {a: 1, n: 4} {a: 2} {a: 3} {a: 4} {a: 5} {a: 6}"
+    )]
+    /// Tests sorting by whole values and by keys, in memory and in chunks
+    fn test_sort(#[case] args: &[&str], #[case] input: &str, #[case] expected: &str) -> Result<()> {
+        let mut cmd = Command::cargo_bin("ion")?;
+        cmd.args(["-X", "sort", "-f", "lines"])
+            .args(args)
+            .timeout(Duration::new(5, 0))
+            .write_stdin(input);
+        let output = cmd.assert().success().get_output().stdout.clone();
+        assert_eq!(Element::read_all(output)?, Element::read_all(expected)?);
+        Ok(())
+    }
+}
+
+mod uniq_tests {
+    use super::*;
+
+    #[rstest]
+    #[case::whole_values(&[], "1 2 1 {a: 1, b: 2} {b: 2, a: 1} a::1 2.0 2", "1 2 {a: 1, b: 2} a::1 2.0")]
+    #[case::keys(&["-k", ".id"], "{id: 1, n: 1} {id: 2} {id: 1, n: 2} {n: 3} {n: 4}", "{id: 1, n: 1} {id: 2} {n: 3}")]
+    #[case::null_key_differs_from_missing_key(&["-k", ".id"], "{id: null} {n: 1}", "{id: null} {n: 1}")]
+    #[case::repeated(&["-d"], "3 1 2 1 3 1", "1 3")]
+    #[case::repeated_keys(&["-d", "-k", ".id"], "{id: 1, n: 1} {id: 2} {id: 1, n: 2} {id: 1, n: 3}", "{id: 1, n: 2}")]
+    #[case::counts(&["-c"], "b a b", "{count: 2, value: b} {count: 1, value: a}")]
+    #[case::repeated_counts(&["-c", "-d", "-k", ".id"], "{id: 1, n: 1} {id: 2} {id: 1, n: 2}", "{count: 2, value: {id: 1, n: 1}}")]
+    #[case::other_hash(&["--hash", "sha3-512"], "x y x", "x y")]
+    /// Tests removing duplicates by value and by key, and reporting duplicates and counts
+    fn test_uniq(#[case] args: &[&str], #[case] input: &str, #[case] expected: &str) -> Result<()> {
+        let mut cmd = Command::cargo_bin("ion")?;
+        cmd.args(["-X", "uniq", "-f", "lines"])
+            .args(args)
+            .timeout(Duration::new(5, 0))
+            .write_stdin(input);
+        let output = cmd.assert().success().get_output().stdout.clone();
+        assert_eq!(Element::read_all(output)?, Element::read_all(expected)?);
+        Ok(())
+    }
+}
+
+mod tail_tests {
+    use super::*;
+
+    #[rstest]
+    #[case::fewer_than_available("3", "1 2 3 4 5", "3 4 5")]
+    #[case::more_than_available("10", "1 2", "1 2")]
+    #[case::zero("0", "1 2", "")]
+    /// Tests `tail` on text and binary files and on STDIN
+    fn test_tail(#[case] count: &str, #[case] input: &str, #[case] expected: &str) -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let text_path = temp_dir.path().join("input.ion");
+        File::create(&text_path)?.write_all(input.as_bytes())?;
+        let mut cmd = Command::cargo_bin("ion")?;
+        cmd.args(["cat", "-f", "binary", "-o"])
+            .arg(temp_dir.path().join("input.10n"))
+            .arg(&text_path)
+            .assert()
+            .success();
+        for file_name in ["input.ion", "input.10n"] {
+            let mut cmd = Command::cargo_bin("ion")?;
+            cmd.args(["-X", "tail", "-f", "lines", "-n", count])
+                .arg(temp_dir.path().join(file_name))
+                .timeout(Duration::new(5, 0));
+            let output = cmd.assert().success().get_output().stdout.clone();
+            assert_eq!(Element::read_all(output)?, Element::read_all(expected)?);
+        }
+        let mut cmd = Command::cargo_bin("ion")?;
+        cmd.args(["-X", "tail", "-f", "lines", "-n", count])
+            .timeout(Duration::new(5, 0))
+            .write_stdin(input);
+        let output = cmd.assert().success().get_output().stdout.clone();
+        assert_eq!(Element::read_all(output)?, Element::read_all(expected)?);
+        Ok(())
+    }
+
+    #[test]
+    /// Tests that `tail --follow` prints values that are appended to a binary file after it starts
+    fn test_tail_follow() -> Result<()> {
+        let to_binary = |text: &str| -> Result<Vec<u8>> {
+            let mut cmd = Command::cargo_bin("ion")?;
+            cmd.args(["cat", "-f", "binary"]).write_stdin(text);
+            Ok(cmd.assert().success().get_output().stdout.clone())
+        };
+        let temp_dir = TempDir::new()?;
+        let input_path = temp_dir.path().join("input.10n");
+        File::create(&input_path)?.write_all(&to_binary("1 2 3")?)?;
+        let appended = to_binary("{a: 4} [5]")?;
+        let appender_path = input_path.clone();
+        let appender = std::thread::spawn(move || -> std::io::Result<()> {
+            std::thread::sleep(Duration::from_millis(500));
+            let mut file = File::options().append(true).open(appender_path)?;
+            file.write_all(&appended)
+        });
+        let mut cmd = Command::cargo_bin("ion")?;
+        cmd.args(["-X", "tail", "-f", "lines", "-n", "1", "--follow"])
+            .arg(&input_path)
+            .timeout(Duration::new(2, 0));
+        // The command only stops when the timeout expires.
+        let output = cmd.output()?;
+        appender.join().unwrap()?;
+        assert_eq!(
+            Element::read_all(output.stdout)?,
+            Element::read_all("3 {a: 4} [5]")?
+        );
+        Ok(())
+    }
+
+    #[rstest]
+    #[case::no_input(&[])]
+    #[case::dash(&["-"])]
+    /// Tests that `--follow` can't be used with STDIN
+    fn test_tail_follow_requires_a_file(#[case] inputs: &[&str]) -> Result<()> {
+        let mut cmd = Command::cargo_bin("ion")?;
+        cmd.args(["-X", "tail", "--follow"])
+            .args(inputs)
+            .timeout(Duration::new(5, 0))
+            .write_stdin("1");
+        cmd.assert().failure();
+        Ok(())
+    }
+}
+
+mod slice_tests {
+    use super::*;
+
+    #[rstest]
+    #[case::everything("..", "1", "0 1 2 3 4 5 6 7 8 9")]
+    #[case::exclusive("2..5", "1", "2 3 4")]
+    #[case::inclusive("2..=5", "1", "2 3 4 5")]
+    #[case::open_end("7..", "1", "7 8 9")]
+    #[case::open_start("..2", "1", "0 1")]
+    #[case::single("4", "1", "4")]
+    #[case::from_end("-3..", "1", "7 8 9")]
+    #[case::both_from_end("-4..-1", "1", "6 7 8")]
+    #[case::last("-1", "1", "9")]
+    #[case::past_the_end("8..20", "1", "8 9")]
+    #[case::step("1..", "3", "1 4 7")]
+    #[case::empty("5..2", "1", "")]
+    /// Tests `slice` on text and binary files and on STDIN
+    fn test_slice(#[case] range: &str, #[case] step: &str, #[case] expected: &str) -> Result<()> {
+        let input = "0 1 2 3 4 5 6 7 8 9";
+        let temp_dir = TempDir::new()?;
+        let text_path = temp_dir.path().join("input.ion");
+        File::create(&text_path)?.write_all(input.as_bytes())?;
+        let mut cmd = Command::cargo_bin("ion")?;
+        cmd.args(["cat", "-f", "binary", "-o"])
+            .arg(temp_dir.path().join("input.10n"))
+            .arg(&text_path)
+            .assert()
+            .success();
+        for file_name in ["input.ion", "input.10n"] {
+            let mut cmd = Command::cargo_bin("ion")?;
+            cmd.args(["-X", "slice", "-f", "lines", "-r", range, "-s", step])
+                .arg(temp_dir.path().join(file_name))
+                .timeout(Duration::new(5, 0));
+            let output = cmd.assert().success().get_output().stdout.clone();
+            assert_eq!(Element::read_all(output)?, Element::read_all(expected)?);
+        }
+        let mut cmd = Command::cargo_bin("ion")?;
+        cmd.args(["-X", "slice", "-f", "lines", "-r", range, "-s", step])
+            .timeout(Duration::new(5, 0))
+            .write_stdin(input);
+        let output = cmd.assert().success().get_output().stdout.clone();
+        assert_eq!(Element::read_all(output)?, Element::read_all(expected)?);
+        Ok(())
+    }
+
+    #[test]
+    /// Tests that `--positions` gives the location of each value's encoding in the input
+    fn test_slice_positions() -> Result<()> {
+        let input = "abc [1, 2] {a: b}";
+        let mut cmd = Command::cargo_bin("ion")?;
+        cmd.args(["-X", "slice", "-f", "lines", "-r", "1..", "--positions"])
+            .timeout(Duration::new(5, 0))
+            .write_stdin(input);
+        let output = cmd.assert().success().get_output().stdout.clone();
+        let expected = "{index: 1, offset: 4, length: 6, value: [1, 2]} \
+                        {index: 2, offset: 11, length: 6, value: {a: b}}";
+        assert_eq!(Element::read_all(output)?, Element::read_all(expected)?);
+        Ok(())
+    }
+
+    #[rstest]
+    #[case::not_a_number("1..x")]
+    #[case::inclusive_without_end("1..=")]
+    #[case::empty("")]
+    /// Tests that malformed ranges are rejected
+    fn test_slice_invalid_range(#[case] range: &str) -> Result<()> {
+        let mut cmd = Command::cargo_bin("ion")?;
+        cmd.args(["-X", "slice", "-r", range])
+            .timeout(Duration::new(5, 0))
+            .write_stdin("1");
+        cmd.assert().failure();
+        Ok(())
+    }
+}
+
+mod split_tests {
+    use super::*;
+
+    /// Returns the names of the files in `dir` in order.
+    fn file_names(dir: &Path) -> Result<Vec<String>> {
+        let mut names = std::fs::read_dir(dir)?
+            .map(|entry| Ok(entry?.file_name().to_string_lossy().into_owned()))
+            .collect::<Result<Vec<_>>>()?;
+        names.sort();
+        Ok(names)
+    }
+
+    #[rstest]
+    #[case::binary("binary", &["chunk-0000.10n", "chunk-0001.10n", "chunk-0002.10n"])]
+    #[case::text("lines", &["chunk-0000.ion", "chunk-0001.ion", "chunk-0002.ion"])]
+    /// Tests that `--values` writes chunks of the given number of values
+    fn test_split_values(#[case] format: &str, #[case] expected_files: &[&str]) -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let mut cmd = Command::cargo_bin("ion")?;
+        cmd.args(["-X", "split", "-f", format, "-n", "2"])
+            .current_dir(temp_dir.path())
+            .timeout(Duration::new(5, 0))
+            .write_stdin("a b {c: d} e f::g");
+        cmd.assert().success();
+        assert_eq!(file_names(temp_dir.path())?, expected_files);
+        let expected_chunks = ["a b", "{c: d} e", "f::g"];
+        for (file_name, expected) in expected_files.iter().zip(expected_chunks) {
+            let chunk = std::fs::read(temp_dir.path().join(file_name))?;
+            assert_eq!(Element::read_all(chunk)?, Element::read_all(expected)?);
+        }
+        Ok(())
+    }
+
+    #[test]
+    /// Tests that `--bytes` writes chunks of about the given size that together hold the input
+    fn test_split_bytes() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let input = (0..100)
+            .map(|i| format!("\"value {i}\" "))
+            .collect::<String>();
+        let mut cmd = Command::cargo_bin("ion")?;
+        cmd.args(["-X", "split", "-f", "binary", "-b", "100", "-t"])
+            .arg(temp_dir.path().join("out/part-{n}.10n"))
+            .timeout(Duration::new(5, 0))
+            .write_stdin(input.as_str());
+        cmd.assert().success();
+        let out_dir = temp_dir.path().join("out");
+        let file_names = file_names(&out_dir)?;
+        assert!(file_names.len() > 1);
+        let mut values = Vec::new();
+        for file_name in file_names {
+            let chunk = std::fs::read(out_dir.join(file_name))?;
+            // Each chunk is a complete binary stream with its own version marker.
+            assert_eq!(chunk[..4], [0xE0, 0x01, 0x00, 0xEA]);
+            assert!(chunk.len() < 200);
+            values.extend(Element::read_all(chunk)?);
+        }
+        assert_eq!(
+            values,
+            Element::read_all(input.as_str())?
+                .into_iter()
+                .collect::<Vec<_>>()
+        );
+        Ok(())
+    }
+
+    #[rstest]
+    #[case::gzip("gzip", "gz")]
+    #[case::zstd("zstd", "zst")]
+    /// Tests that `--key` writes a (compressed) chunk for each distinct key
+    fn test_split_key(#[case] compression: &str, #[case] extension: &str) -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let mut cmd = Command::cargo_bin("ion")?;
+        cmd.args([
+            "-X",
+            "split",
+            "-f",
+            "lines",
+            "-k",
+            ".kind",
+            "-z",
+            compression,
+        ])
+        .current_dir(temp_dir.path())
+        .timeout(Duration::new(5, 0))
+        .write_stdin(r#"{kind: a, v: 1} {kind: "b/c", v: 2} {v: 3} {kind: a, v: 4}"#);
+        cmd.assert().success();
+        let expected_chunks = [
+            ("a", "{kind: a, v: 1} {kind: a, v: 4}"),
+            ("b_c", r#"{kind: "b/c", v: 2}"#),
+            ("missing", "{v: 3}"),
+        ];
+        let expected_files = expected_chunks
+            .iter()
+            .map(|(key, _)| format!("chunk-{key}.ion.{extension}"))
+            .collect::<Vec<_>>();
+        assert_eq!(file_names(temp_dir.path())?, expected_files);
+        for (file_name, (_, expected)) in expected_files.iter().zip(expected_chunks) {
+            let mut cmd = Command::cargo_bin("ion")?;
+            cmd.args(["cat", "-f", "lines"])
+                .arg(temp_dir.path().join(file_name))
+                .timeout(Duration::new(5, 0));
+            let output = cmd.assert().success().get_output().stdout.clone();
+            assert_eq!(Element::read_all(output)?, Element::read_all(expected)?);
+        }
+        Ok(())
+    }
+
+    #[rstest]
+    #[case::number(
+        "part-{n}.ion",
+        &[],
+        &[
+            ("part-0000.ion", "{k: a, v: 1} {k: a, v: 3}"),
+            ("part-0001.ion", "{k: b, v: 2}"),
+        ]
+    )]
+    #[case::number_and_key(
+        "chunk-{n}-{key}.ion",
+        &[],
+        &[
+            ("chunk-0000-a.ion", "{k: a, v: 1} {k: a, v: 3}"),
+            ("chunk-0001-b.ion", "{k: b, v: 2}"),
+        ]
+    )]
+    #[case::colliding_keys(
+        "{key}.ion",
+        &[r#"{k: "a/b", v: 4}"#, "{k: a_b, v: 5}", "{k: 1, v: 6}", r#"{k: "1", v: 7}"#],
+        &[
+            ("1-2.ion", r#"{k: "1", v: 7}"#),
+            ("1.ion", "{k: 1, v: 6}"),
+            ("a.ion", "{k: a, v: 1} {k: a, v: 3}"),
+            ("a_b-2.ion", "{k: a_b, v: 5}"),
+            ("a_b.ion", r#"{k: "a/b", v: 4}"#),
+            ("b.ion", "{k: b, v: 2}"),
+        ]
+    )]
+    /// Tests that each key's values go to one chunk, and that distinct keys get distinct files
+    fn test_split_key_template(
+        #[case] template: &str,
+        #[case] more_values: &[&str],
+        #[case] expected_chunks: &[(&str, &str)],
+    ) -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let mut input = "{k: a, v: 1} {k: b, v: 2} {k: a, v: 3} ".to_owned();
+        input.push_str(&more_values.join(" "));
+        let mut cmd = Command::cargo_bin("ion")?;
+        cmd.args(["-X", "split", "-f", "lines", "-k", ".k", "-t", template])
+            .current_dir(temp_dir.path())
+            .timeout(Duration::new(5, 0))
+            .write_stdin(input);
+        cmd.assert().success();
+        let expected_files: Vec<&str> = expected_chunks.iter().map(|(name, _)| *name).collect();
+        assert_eq!(file_names(temp_dir.path())?, expected_files);
+        for (file_name, expected) in expected_chunks {
+            let chunk = std::fs::read(temp_dir.path().join(file_name))?;
+            assert_eq!(Element::read_all(chunk)?, Element::read_all(*expected)?);
+        }
+        Ok(())
+    }
+
+    #[rstest]
+    #[case::binary(&["-f", "binary"])]
+    #[case::gzip(&["-f", "binary", "-z", "gzip"])]
+    #[case::zstd(&["-f", "lines", "-z", "zstd"])]
+    /// Tests that chunks closed to stay under `--max-open-chunks` are reopened and appended to
+    fn test_split_key_max_open_chunks(#[case] args: &[&str]) -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let input = (0..30)
+            .map(|i| format!("{{k: k{}, v: {i}}} ", i % 3))
+            .collect::<String>();
+        let mut cmd = Command::cargo_bin("ion")?;
+        cmd.args(["-X", "split", "-k", ".k", "--max-open-chunks", "2"])
+            .args(args)
+            .current_dir(temp_dir.path())
+            .timeout(Duration::new(5, 0))
+            .write_stdin(input.as_str());
+        cmd.assert().success();
+        let file_names = file_names(temp_dir.path())?;
+        assert_eq!(file_names.len(), 3);
+        for (k, file_name) in file_names.iter().enumerate() {
+            let mut cmd = Command::cargo_bin("ion")?;
+            cmd.args(["cat", "-f", "lines"])
+                .arg(temp_dir.path().join(file_name))
+                .timeout(Duration::new(5, 0));
+            let output = cmd.assert().success().get_output().stdout.clone();
+            let expected = (0..30)
+                .filter(|i| i % 3 == k)
+                .map(|i| format!("{{k: k{k}, v: {i}}} "))
+                .collect::<String>();
+            assert_eq!(Element::read_all(output)?, Element::read_all(expected)?);
+        }
+        Ok(())
+    }
+
+    #[rstest]
+    #[case::no_mode(&[])]
+    #[case::two_modes(&["-n", "1", "-b", "10"])]
+    #[case::template_without_placeholder(&["-n", "1", "-t", "chunk.ion"])]
+    #[case::invalid_size(&["-b", "10X"])]
+    #[case::max_open_chunks_without_key(&["-n", "1", "--max-open-chunks", "2"])]
+    /// Tests that invalid arguments are rejected
+    fn test_split_invalid_args(#[case] args: &[&str]) -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let mut cmd = Command::cargo_bin("ion")?;
+        cmd.args(["-X", "split"])
+            .args(args)
+            .current_dir(temp_dir.path())
+            .timeout(Duration::new(5, 0))
+            .write_stdin("1");
+        cmd.assert().failure();
+        Ok(())
+    }
+}
+
+mod merge_tests {
+    use super::*;
+
+    /// Writes `text` to `path` as binary Ion and returns the encoded bytes.
+    fn write_binary(path: &Path, text: &str) -> Result<Vec<u8>> {
+        let mut cmd = Command::cargo_bin("ion")?;
+        cmd.args(["cat", "-f", "binary"]).write_stdin(text);
+        let bytes = cmd.assert().success().get_output().stdout.clone();
+        File::create(path)?.write_all(&bytes)?;
+        Ok(bytes)
+    }
+
+    #[test]
+    /// Tests that binary inputs with their own symbol tables are copied to a binary output as-is
+    fn test_merge_splices_binary_inputs() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let (a_path, b_path) = (temp_dir.path().join("a.10n"), temp_dir.path().join("b.10n"));
+        let a = write_binary(&a_path, "{alpha: 1, beta: x}")?;
+        let b = write_binary(&b_path, "{gamma: 2, delta: y} zeta::3")?;
+        let mut cmd = Command::cargo_bin("ion")?;
+        cmd.args(["-X", "merge", "-f", "binary"])
+            .arg(&a_path)
+            .arg(&b_path)
+            .timeout(Duration::new(5, 0));
+        let output = cmd.assert().success().get_output().stdout.clone();
+        assert_eq!(output, [a, b].concat());
+        assert_eq!(
+            Element::read_all(output)?,
+            Element::read_all("{alpha: 1, beta: x} {gamma: 2, delta: y} zeta::3")?
+        );
+        Ok(())
+    }
+
+    #[rstest]
+    #[case::binary("binary")]
+    #[case::text("lines")]
+    /// Tests concatenating a mix of text and binary inputs
+    fn test_merge_mixed_inputs(#[case] format: &str) -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let binary_path = temp_dir.path().join("a.10n");
+        write_binary(&binary_path, "{alpha: 1, beta: x}")?;
+        let text_path = temp_dir.path().join("b.ion");
+        File::create(&text_path)?.write_all(b"{gamma: 2} delta::3")?;
+        let mut cmd = Command::cargo_bin("ion")?;
+        cmd.args(["-X", "merge", "-f", format])
+            .arg(&binary_path)
+            .arg(&text_path)
+            .arg(&binary_path)
+            .timeout(Duration::new(5, 0));
+        let output = cmd.assert().success().get_output().stdout.clone();
+        assert_eq!(
+            Element::read_all(output)?,
+            Element::read_all("{alpha: 1, beta: x} {gamma: 2} delta::3 {alpha: 1, beta: x}")?
+        );
+        Ok(())
+    }
+
+    #[rstest]
+    #[case::whole_values(&[], "1 3 5", "2 3 4 10", "1 2 3 3 4 5 10")]
+    #[case::descending(&["-r"], "5 3 1", "10 4 3 2", "10 5 4 3 3 2 1")]
+    #[case::key(&["-k", ".k"], "{k: 1, a: x} {k: 5}", "{k: 2} {k: 5, a: y}", "{k: 1, a: x} {k: 2} {k: 5} {k: 5, a: y}")]
+    /// Tests that `--sorted` interleaves the values of sorted inputs
+    fn test_merge_sorted(
+        #[case] args: &[&str],
+        #[case] first: &str,
+        #[case] second: &str,
+        #[case] expected: &str,
+    ) -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let first_path = temp_dir.path().join("first.ion");
+        File::create(&first_path)?.write_all(first.as_bytes())?;
+        let second_path = temp_dir.path().join("second.10n");
+        write_binary(&second_path, second)?;
+        let mut cmd = Command::cargo_bin("ion")?;
+        cmd.args(["-X", "merge", "-f", "lines", "--sorted"])
+            .args(args)
+            .arg(&first_path)
+            .arg(&second_path)
+            .timeout(Duration::new(5, 0));
+        let output = cmd.assert().success().get_output().stdout.clone();
+        assert_eq!(Element::read_all(output)?, Element::read_all(expected)?);
+        Ok(())
+    }
+
+    #[test]
+    /// Tests that `--key` can only be used with `--sorted`
+    fn test_merge_key_requires_sorted() -> Result<()> {
+        let mut cmd = Command::cargo_bin("ion")?;
+        cmd.args(["-X", "merge", "-k", ".a"])
+            .timeout(Duration::new(5, 0))
+            .write_stdin("1");
+        cmd.assert().failure();
+        Ok(())
+    }
+}
+
+mod grep_tests {
+    use super::*;
+
+    const INPUT: &str = r#"
+        {status: error, code: 503, user: {id: "xABBCy"}}
+        {status: ok, code: 200, user: {id: "abc"}}
+        retry::{status: "error", code: 500.0}
+        {status: ok}
+    "#;
+
+    #[rstest]
+    #[case::equal(&[".status == error"], "{status: error, code: 503, user: {id: \"xABBCy\"}} retry::{status: \"error\", code: 500.0}")]
+    #[case::inverted(&["-v", ".status == error"], "{status: ok, code: 200, user: {id: \"abc\"}} {status: ok}")]
+    #[case::number(&[".code >= 500 and .code < 503"], "retry::{status: \"error\", code: 500.0}")]
+    #[case::regex(&[".user.id =~ /^ab/"], "{status: ok, code: 200, user: {id: \"abc\"}}")]
+    #[case::regex_ignoring_case(&[".user.id =~ /ab+c/i"], "{status: error, code: 503, user: {id: \"xABBCy\"}} {status: ok, code: 200, user: {id: \"abc\"}}")]
+    #[case::has(&["not has(.code)"], "{status: ok}")]
+    #[case::annotation(&["@retry"], "retry::{status: \"error\", code: 500.0}")]
+    #[case::count(&["-c", ".status == ok"], "2")]
+    #[case::count_inverted(&["-c", "-v", ".status == ok"], "2")]
+    #[case::no_matches(&[".status == unknown"], "")]
+    /// Tests filtering values with `grep`
+    fn test_grep(#[case] args: &[&str], #[case] expected: &str) -> Result<()> {
+        let mut cmd = Command::cargo_bin("ion")?;
+        cmd.args(["-X", "grep", "-f", "lines"])
+            .args(args)
+            .timeout(Duration::new(5, 0))
+            .write_stdin(INPUT);
+        let output = cmd.assert().success().get_output().stdout.clone();
+        assert_eq!(Element::read_all(output)?, Element::read_all(expected)?);
+        Ok(())
+    }
+
+    #[test]
+    /// Tests that `-l` prints the names of the inputs that have a match
+    fn test_grep_files_with_matches() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let matching_path = temp_dir.path().join("matching.ion");
+        File::create(&matching_path)?.write_all(INPUT.as_bytes())?;
+        let other_path = temp_dir.path().join("other.ion");
+        File::create(&other_path)?.write_all(b"{status: ok}")?;
+        let mut cmd = Command::cargo_bin("ion")?;
+        cmd.args(["-X", "grep", "-f", "lines", "-l", ".status == error"])
+            .arg(&other_path)
+            .arg(&matching_path)
+            .timeout(Duration::new(5, 0));
+        let output = cmd.assert().success().get_output().stdout.clone();
+        let expected = Element::string(matching_path.to_string_lossy().as_ref());
+        assert_eq!(Element::read_all(output)?, vec![expected].into());
+        Ok(())
+    }
+
+    #[rstest]
+    #[case::missing_operator(".status")]
+    #[case::not_a_path("status == error")]
+    #[case::unterminated_regex(".a =~ /abc")]
+    #[case::invalid_regex(".a =~ /(/")]
+    /// Tests that malformed predicates are rejected
+    fn test_grep_invalid_predicate(#[case] predicate: &str) -> Result<()> {
+        let mut cmd = Command::cargo_bin("ion")?;
+        cmd.args(["-X", "grep", predicate])
+            .timeout(Duration::new(5, 0))
+            .write_stdin("1");
+        cmd.assert().failure();
+        Ok(())
+    }
+}
+
+mod count_tests {
+    use super::*;
+
+    const INPUT: &str = r#"
+        {status: error, bytes: 100, time: 2024-01-01T00:00:00Z, user: a}
+        {status: ok, bytes: 50, time: 2024-01-03T00:00:00Z, user: b}
+        {status: error, bytes: 1.5, time: 2024-01-02T00:00:00Z, user: a}
+        {status: ok, bytes: 20, user: c}
+        {bytes: 2e0}
+    "#;
+
+    #[rstest]
+    #[case::total(&[], "{count: 5}")]
+    #[case::grouped(&["-g", ".status"], "{count: 1} {status: error, count: 2} {status: ok, count: 2}")]
+    #[case::sums(&["-g", ".status", "--sum", ".bytes"], "{sum: {bytes: 2e0}, count: 1} {status: error, count: 2, sum: {bytes: 101.5}} {status: ok, count: 2, sum: {bytes: 70}}")]
+    #[case::means(&["--mean", ".bytes", "--mean", ".time"], "{count: 5, mean: {bytes: 34.7e0, time: 2024-01-02T00:00:00Z}}")]
+    #[case::extremes(&["--min", ".time", "--max", ".time"], "{count: 5, min: {time: 2024-01-01T00:00:00Z}, max: {time: 2024-01-03T00:00:00Z}}")]
+    #[case::distinct(&["-g", ".status", "--distinct", ".user"], "{count: 1, distinct: {user: 0}} {status: error, count: 2, distinct: {user: 1}} {status: ok, count: 2, distinct: {user: 2}}")]
+    #[case::two_keys(&["-g", ".status", "-g", ".user"], "{count: 1} {status: error, user: a, count: 2} {status: ok, user: b, count: 1} {status: ok, user: c, count: 1}")]
+    /// Tests counting and aggregating values with `count`
+    fn test_count(#[case] args: &[&str], #[case] expected: &str) -> Result<()> {
+        let mut cmd = Command::cargo_bin("ion")?;
+        cmd.args(["-X", "count", "-f", "lines"])
+            .args(args)
+            .timeout(Duration::new(5, 0))
+            .write_stdin(INPUT);
+        let output = cmd.assert().success().get_output().stdout.clone();
+        assert_eq!(
+            IonData::from(Element::read_all(output)?),
+            IonData::from(Element::read_all(expected)?)
+        );
+        Ok(())
+    }
+
+    #[rstest]
+    #[case::ints("1 2 3 4", "{count: 4, sum: {value: 10}, mean: {value: 2.5}}")]
+    #[case::decimals("1.5 2.5", "{count: 2, sum: {value: 4.0}, mean: {value: 2.0}}")]
+    #[case::repeating(
+        "1 1 1 0 0 0 0 0 0",
+        "{count: 9, sum: {value: 3}, mean: {value: 0.3333333333333333333333333333333333}}"
+    )]
+    #[case::no_numbers("a b", "{count: 2, sum: {value: 0}, mean: {value: null}}")]
+    /// Tests the types of sums and means
+    fn test_count_sum_and_mean(#[case] input: &str, #[case] expected: &str) -> Result<()> {
+        let mut cmd = Command::cargo_bin("ion")?;
+        cmd.args(["-X", "count", "-f", "lines", "--sum", ".", "--mean", "."])
+            .timeout(Duration::new(5, 0))
+            .write_stdin(input);
+        let output = cmd.assert().success().get_output().stdout.clone();
+        assert_eq!(
+            IonData::from(Element::read_all(output)?),
+            IonData::from(Element::read_all(expected)?)
+        );
+        Ok(())
+    }
+}
+
+mod flatten_tests {
+    use super::*;
+
+    const INPUT: &str = r#"
+        {a: x::{b: [1, y::{c: 2}], e: {}, f: null.list}, g: z::3}
+        w::[1, [2]]
+        5
+        (a b)
+    "#;
+
+    #[rstest]
+    #[case::brackets(&[], r#"{'a.b[0]': 1, 'a.b[1].c': 2, 'a.e': {}, 'a.f': null.list, g: z::3} w::{'[0]': 1, '[1][0]': 2} 5 (a b)"#)]
+    #[case::separator(&["-s", "/", "--index-style", "separator"], r#"{'a/b/0': 1, 'a/b/1/c': 2, 'a/e': {}, 'a/f': null.list, g: z::3} w::{'0': 1, '1/0': 2} 5 (a b)"#)]
+    #[case::annotations(&["-a", "_ann"], r#"{'a.b[0]': 1, 'a.b[1].c': 2, 'a.e': {}, 'a.f': null.list, g: 3, _ann: {a: [x], 'a.b[1]': [y], g: [z]}} w::{'[0]': 1, '[1][0]': 2} 5 (a b)"#)]
+    /// Tests flattening values, then that unflattening them with the same options restores them
+    fn test_flatten(#[case] args: &[&str], #[case] expected: &str) -> Result<()> {
+        let mut cmd = Command::cargo_bin("ion")?;
+        cmd.args(["-X", "flatten", "-f", "lines"])
+            .args(args)
+            .timeout(Duration::new(5, 0))
+            .write_stdin(INPUT);
+        let flattened = cmd.assert().success().get_output().stdout.clone();
+        assert_eq!(
+            IonData::from(Element::read_all(&flattened)?),
+            IonData::from(Element::read_all(expected)?)
+        );
+
+        let mut cmd = Command::cargo_bin("ion")?;
+        cmd.args(["-X", "unflatten", "-f", "lines"])
+            .args(args)
+            .timeout(Duration::new(5, 0))
+            .write_stdin(flattened);
+        let output = cmd.assert().success().get_output().stdout.clone();
+        let expected = if !args.contains(&"-a") {
+            // Without a sidecar field, the annotations of nested containers are dropped.
+            r#"{a: {b: [1, {c: 2}], e: {}, f: null.list}, g: z::3} w::[1, [2]] 5 (a b)"#
+        } else {
+            INPUT
+        };
+        assert_eq!(
+            IonData::from(Element::read_all(output)?),
+            IonData::from(Element::read_all(expected)?)
+        );
+        Ok(())
+    }
+
+    #[rstest]
+    #[case::missing_indexes("{'[2]': x, '[0].a': y, '[0].b': z}", "[{a: y, b: z}, null, x]")]
+    #[case::repeated_fields("{'a.b': 1, 'a.b': 2}", "{a: {b: 1, b: 2}}")]
+    #[case::not_flattened("{} [1] 2", "{} [1] 2")]
+    /// Tests unflattening structs that `flatten` would not write
+    fn test_unflatten(#[case] input: &str, #[case] expected: &str) -> Result<()> {
+        let mut cmd = Command::cargo_bin("ion")?;
+        cmd.args(["-X", "unflatten", "-f", "lines"])
+            .timeout(Duration::new(5, 0))
+            .write_stdin(input);
+        let output = cmd.assert().success().get_output().stdout.clone();
+        assert_eq!(
+            IonData::from(Element::read_all(output)?),
+            IonData::from(Element::read_all(expected)?)
+        );
+        Ok(())
+    }
+
+    #[rstest]
+    #[case::leaf_and_struct("{a: 1, 'a.b': 2}")]
+    #[case::list_and_struct("{'a[0]': 1, 'a.b': 2}")]
+    #[case::repeated_index("{'[0]': 1, '[0]': 2}")]
+    #[case::index_out_of_range("{'a[99999999999]': 1}")]
+    #[case::index_beyond_field_count("{'[2]': x, '[0]': y}")]
+    /// Tests that paths which conflict with each other or have out-of-range indexes are rejected
+    fn test_unflatten_conflicts(#[case] input: &str) -> Result<()> {
+        let mut cmd = Command::cargo_bin("ion")?;
+        cmd.args(["-X", "unflatten"])
+            .timeout(Duration::new(5, 0))
+            .write_stdin(input);
+        cmd.assert().failure();
+        Ok(())
+    }
+}
+
+mod sample_tests {
+    use super::*;
+
+    fn sample(args: &[&str], input: &str) -> Result<Vec<Element>> {
+        let mut cmd = Command::cargo_bin("ion")?;
+        cmd.args(["-X", "sample", "-f", "lines", "--seed", "42"])
+            .args(args)
+            .timeout(Duration::new(5, 0))
+            .write_stdin(input.to_owned());
+        let output = cmd.assert().success().get_output().stdout.clone();
+        Ok(Element::read_all(output)?.into_iter().collect())
+    }
+
+    fn numbers(count: i64) -> String {
+        (0..count).map(|n| format!("{n} ")).collect()
+    }
+
+    #[rstest]
+    #[case::reservoir(&["-n", "10"])]
+    #[case::rate(&["-r", "0.1"])]
+    #[case::stratified(&["-n", "2", "-k", "."])]
+    /// Tests that samples with the same seed are the same, and are in the order of the input
+    fn test_sample_is_repeatable(#[case] args: &[&str]) -> Result<()> {
+        let input = numbers(1000);
+        let first = sample(args, &input)?;
+        assert_eq!(first, sample(args, &input)?);
+        let positions: Vec<i64> = first
+            .iter()
+            .map(|element| element.as_i64().unwrap())
+            .collect();
+        assert!(positions.windows(2).all(|pair| pair[0] < pair[1]));
+        Ok(())
+    }
+
+    #[rstest]
+    #[case::fewer_values_than_wanted(&["-n", "10"], 5, 5)]
+    #[case::reservoir(&["-n", "10"], 1000, 10)]
+    #[case::rate_zero(&["-r", "0"], 1000, 0)]
+    #[case::rate_one(&["-r", "1"], 1000, 1000)]
+    /// Tests the number of values kept by `sample`
+    fn test_sample_size(
+        #[case] args: &[&str],
+        #[case] values: i64,
+        #[case] expected: usize,
+    ) -> Result<()> {
+        assert_eq!(sample(args, &numbers(values))?.len(), expected);
+        Ok(())
+    }
+
+    #[test]
+    /// Tests that stratified sampling keeps values for every key, however rare
+    fn test_sample_stratified() -> Result<()> {
+        let mut input: String = (0..100)
+            .map(|n| format!("{{k: common, n: {n}}} "))
+            .collect();
+        input.push_str("{k: rare, n: 100} {n: 101}");
+        let samples = sample(&["-n", "3", "-k", ".k"], &input)?;
+        let count_of = |key: Option<&str>| {
+            samples
+                .iter()
+                .filter(|element| {
+                    let value = element.as_struct().unwrap().get("k");
+                    value.and_then(Element::as_text) == key
+                })
+                .count()
+        };
+        assert_eq!(count_of(Some("common")), 3);
+        assert_eq!(count_of(Some("rare")), 1);
+        assert_eq!(count_of(None), 1);
+        Ok(())
+    }
+
+    #[rstest]
+    #[case::no_mode(&[])]
+    #[case::both_modes(&["-n", "2", "-r", "0.5"])]
+    #[case::key_without_values(&["-r", "0.5", "-k", ".k"])]
+    #[case::rate_too_large(&["-r", "1.5"])]
+    /// Tests that invalid sampling options are rejected
+    fn test_sample_invalid_args(#[case] args: &[&str]) -> Result<()> {
+        let mut cmd = Command::cargo_bin("ion")?;
+        cmd.args(["-X", "sample"])
+            .args(args)
+            .timeout(Duration::new(5, 0))
+            .write_stdin("1 2 3");
+        cmd.assert().failure();
+        Ok(())
+    }
+}
+
+mod symtab_extract_tests {
+    use super::*;
+
+    #[rstest]
+    #[case::by_frequency(&[], r#"["x", "a", "b", "q", "y", "z"]"#)]
+    #[case::min_count(&["-m", "2"], r#"["x", "a", "b"]"#)]
+    #[case::min_count_above_all(&["-m", "100"], "[]")]
+    /// Tests the symbols that `symtab extract` includes, and their order
+    fn test_symtab_extract(#[case] args: &[&str], #[case] expected_symbols: &str) -> Result<()> {
+        let mut cmd = Command::cargo_bin("ion")?;
+        cmd.args(["symtab", "-X", "extract", "-n", "com.example", "-t", "2"])
+            .args(args)
+            .timeout(Duration::new(5, 0))
+            .write_stdin("{a: x, b: [y::z, (x x)], name: 1} {a: q, b: 2.0}");
+        let output = cmd.assert().success().get_output().stdout.clone();
+        let expected = format!(
+            r#"$ion_shared_symbol_table::{{name: "com.example", version: 2, symbols: {expected_symbols}}}"#
+        );
+        assert_eq!(
+            IonData::from(Element::read_all(output)?),
+            IonData::from(Element::read_all(expected)?)
+        );
+        Ok(())
+    }
+
+    #[test]
+    /// Tests that the symbols of every input are counted together
+    fn test_symtab_extract_multiple_inputs() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let first = temp_dir.path().join("first.10n");
+        let second = temp_dir.path().join("second.ion");
+        let mut cmd = Command::cargo_bin("ion")?;
+        cmd.args(["cat", "-f", "binary", "-o", first.to_str().unwrap()])
+            .write_stdin("{b: c} {b: d}");
+        cmd.assert().success();
+        File::create(&second)?.write_all(b"{a: c} {a: c} {a: c}")?;
+
+        let mut cmd = Command::cargo_bin("ion")?;
+        cmd.args(["symtab", "-X", "extract", "-n", "t"])
+            .args([&first, &second])
+            .timeout(Duration::new(5, 0));
+        let output = cmd.assert().success().get_output().stdout.clone();
+        let expected =
+            r#"$ion_shared_symbol_table::{name: "t", version: 1, symbols: ["c", "a", "b", "d"]}"#;
+        assert_eq!(
+            IonData::from(Element::read_all(output)?),
+            IonData::from(Element::read_all(expected)?)
+        );
+        Ok(())
+    }
+}
+
+mod catalog_tests {
+    use super::*;
+
+    const TABLES: &str = r#"
+        $ion_shared_symbol_table::{name: "t", version: 1, symbols: ["a", "b"]}
+        $ion_shared_symbol_table::{name: "t", version: 2, symbols: ["a", "b", "c"]}
+        $ion_shared_symbol_table::{name: "r", version: 1, symbols: ["a", "name", "a", null, "d"]}
+        not_a_table
+    "#;
+
+    const DATA: &str = "{a: b, c: [a, d]} c::1";
+
+    #[rstest]
+    #[case::latest_version("t", &["-f", "binary"])]
+    #[case::exact_version("t:1", &["-f", "binary"])]
+    #[case::binary_by_default("t", &[])]
+    #[case::repeated_and_unknown_symbols("r", &[])]
+    /// Tests that binary output written with `--import` can only be read back with the catalog
+    fn test_import_round_trip(#[case] import: &str, #[case] args: &[&str]) -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let catalog_dir = temp_dir.path().join("catalog");
+        std::fs::create_dir(&catalog_dir)?;
+        let tables = catalog_dir.join("tables.ion");
+        let encoded = temp_dir.path().join("data.10n");
+        File::create(&tables)?.write_all(TABLES.as_bytes())?;
+
+        let mut cmd = Command::cargo_bin("ion")?;
+        cmd.args(["cat", "--import", import])
+            .args(args)
+            .arg("--catalog")
+            .arg(&tables)
+            .args(["-o", encoded.to_str().unwrap()])
+            .timeout(Duration::new(5, 0))
+            .write_stdin(DATA);
+        cmd.assert().success();
+
+        // A directory of tables works as well as a single file.
+        let mut cmd = Command::cargo_bin("ion")?;
+        cmd.args(["cat", "--catalog"])
+            .args([&catalog_dir, &encoded])
+            .timeout(Duration::new(5, 0));
+        let output = cmd.assert().success().get_output().stdout.clone();
+        assert_eq!(
+            IonData::from(Element::read_all(output)?),
+            IonData::from(Element::read_all(DATA)?)
+        );
+
+        let mut cmd = Command::cargo_bin("ion")?;
+        cmd.arg("cat").arg(&encoded).timeout(Duration::new(5, 0));
+        cmd.assert().failure();
+        Ok(())
+    }
+
+    #[test]
+    /// Tests that the chunks written by `split --import` import the table as well
+    fn test_split_import() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let tables = temp_dir.path().join("tables.ion");
+        File::create(&tables)?.write_all(TABLES.as_bytes())?;
+
+        let mut cmd = Command::cargo_bin("ion")?;
+        cmd.args(["-X", "split", "-n", "1", "--import", "t:2", "--catalog"])
+            .arg(&tables)
+            .current_dir(temp_dir.path())
+            .timeout(Duration::new(5, 0))
+            .write_stdin(DATA);
+        cmd.assert().success();
+
+        let expected = ["{a: b, c: [a, d]}", "c::1"];
+        for (index, expected) in expected.iter().enumerate() {
+            let chunk = temp_dir.path().join(format!("chunk-{index:04}.10n"));
+            let mut cmd = Command::cargo_bin("ion")?;
+            cmd.args(["cat", "--catalog"])
+                .args([&tables, &chunk])
+                .timeout(Duration::new(5, 0));
+            let output = cmd.assert().success().get_output().stdout.clone();
+            assert_eq!(Element::read_all(output)?, Element::read_all(*expected)?);
+
+            let mut cmd = Command::cargo_bin("ion")?;
+            cmd.arg("cat").arg(&chunk).timeout(Duration::new(5, 0));
+            cmd.assert().failure();
+        }
+        Ok(())
+    }
+
+    #[rstest]
+    #[case::unknown_table(&["--import", "u", "-f", "binary"])]
+    #[case::unknown_version(&["--import", "t:3", "-f", "binary"])]
+    #[case::text_output(&["--import", "t:1", "-f", "pretty"])]
+    #[case::ion_1_1_output(&["-X", "--import", "t:1", "-i", "1.1"])]
+    /// Tests that `--import` fails when the table is missing or the output is not binary Ion 1.0
+    fn test_import_errors(#[case] args: &[&str]) -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let tables = temp_dir.path().join("tables.ion");
+        File::create(&tables)?.write_all(TABLES.as_bytes())?;
+        let mut cmd = Command::cargo_bin("ion")?;
+        cmd.arg("cat")
+            .arg("--catalog")
+            .arg(&tables)
+            .args(args)
+            .timeout(Duration::new(5, 0))
+            .write_stdin(DATA);
+        cmd.assert().failure();
+        Ok(())
+    }
+
+    #[test]
+    /// Tests that commands that do not write Ion do not accept `--import`
+    fn test_import_requires_ion_output() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let tables = temp_dir.path().join("tables.ion");
+        File::create(&tables)?.write_all(TABLES.as_bytes())?;
+        let mut cmd = Command::cargo_bin("ion")?;
+        cmd.args(["to", "json", "--import", "t", "--catalog"])
+            .arg(&tables)
+            .timeout(Duration::new(5, 0))
+            .write_stdin(DATA);
+        cmd.assert().failure();
+        Ok(())
+    }
+}
+
+mod symtab_list_tests {
+    use super::*;
+
+    const CATALOG: &str =
+        r#"$ion_shared_symbol_table::{name: "t", version: 2, symbols: ["x", "y"]}"#;
+
+    // The symbol tables start at offsets 0, 37 and 111, and the stream ends at offset 164.
+    const STREAM: &str = concat!(
+        r#"$ion_symbol_table::{symbols:["a"]} a "#,
+        r#"$ion_symbol_table::{imports:$ion_symbol_table,symbols:["b",1]} b "#,
+        r#"$ion_1_0 $ion_symbol_table::{imports:[{name:"t",version:2}]} c"#,
+    );
+
+    fn symtab_list(temp_dir: &TempDir, args: &[&str]) -> Result<Command> {
+        let catalog = temp_dir.path().join("catalog.ion");
+        let input = temp_dir.path().join("input.ion");
+        File::create(&catalog)?.write_all(CATALOG.as_bytes())?;
+        File::create(&input)?.write_all(STREAM.as_bytes())?;
+        let mut cmd = Command::cargo_bin("ion")?;
+        cmd.args(["symtab", "-X", "list", "--catalog"])
+            .args([&catalog, &input])
+            .args(args)
+            .timeout(Duration::new(5, 0));
+        Ok(cmd)
+    }
+
+    #[test]
+    /// Tests that each local symbol table is listed with the symbol IDs it defines
+    fn test_symtab_list() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let mut cmd = symtab_list(&temp_dir, &[])?;
+        let output = cmd.assert().success().get_output().stdout.clone();
+        let expected = r#"
+            {offset: 0, append: false, imports: [], symbols: ["a"], first_id: 10, max_id: 10}
+            {offset: 37, append: true, imports: [], symbols: ["b", null.string], first_id: 11, max_id: 12}
+            {
+              offset: 111,
+              append: false,
+              imports: [{name: "t", version: 2, first_id: 10, max_id: 11}],
+              symbols: [],
+              first_id: 12,
+              max_id: 11,
+            }
+        "#;
+        assert_eq!(
+            IonData::from(Element::read_all(output)?),
+            IonData::from(Element::read_all(expected)?)
+        );
+        Ok(())
+    }
+
+    #[rstest]
+    #[case::system(&["-r", "4"], r#"{id: 4, offset: 164, text: "name", source: system}"#)]
+    #[case::import(
+        &["--resolve", "$11"],
+        r#"{id: 11, offset: 164, text: "y", source: import, table_offset: 111, import: {name: "t", version: 2, id: 2}}"#
+    )]
+    #[case::local(
+        &["-r", "10", "--offset", "100"],
+        r#"{id: 10, offset: 100, text: "a", source: local, table_offset: 0}"#
+    )]
+    #[case::appended(
+        &["-r", "11", "--offset", "100"],
+        r#"{id: 11, offset: 100, text: "b", source: local, table_offset: 37}"#
+    )]
+    #[case::unknown_text(
+        &["-r", "12", "--offset", "100"],
+        r#"{id: 12, offset: 100, text: null.string, source: local, table_offset: 37}"#
+    )]
+    #[case::before_table(
+        &["-r", "0", "--offset", "37"],
+        r#"{id: 0, offset: 37, text: null.string, source: system}"#
+    )]
+    /// Tests how `symtab list --resolve` explains symbol IDs
+    fn test_symtab_list_resolve(#[case] args: &[&str], #[case] expected: &str) -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let mut cmd = symtab_list(&temp_dir, args)?;
+        let output = cmd.assert().success().get_output().stdout.clone();
+        assert_eq!(
+            IonData::from(Element::read_all(output)?),
+            IonData::from(Element::read_all(expected)?)
+        );
+        Ok(())
+    }
+
+    #[rstest]
+    #[case::not_yet_defined(&["-r", "11", "--offset", "37"])]
+    #[case::reset_by_version_marker(&["-r", "12", "--offset", "111"])]
+    #[case::beyond_max_id(&["-r", "12"])]
+    #[case::not_a_symbol_id(&["-r", "a"])]
+    #[case::offset_without_resolve(&["--offset", "10"])]
+    /// Tests that `symtab list --resolve` fails for symbol IDs that are invalid or not defined
+    fn test_symtab_list_resolve_errors(#[case] args: &[&str]) -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let mut cmd = symtab_list(&temp_dir, args)?;
+        cmd.assert().failure();
+        Ok(())
+    }
+}
+
+mod symtab_optimize_tests {
+    use super::*;
+
+    const DATA: &str = "{a: b, c: [b, b]} x::{a: c}";
+
+    #[test]
+    /// Tests that `symtab optimize` keeps the values and defines only the symbols they use, most
+    /// used first
+    fn test_symtab_optimize() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let input = temp_dir.path().join("input.10n");
+        let optimized = temp_dir.path().join("optimized.10n");
+        let shared_table = temp_dir.path().join("table.ion");
+        // Two binary streams, each with its own symbol table.
+        let mut encoded = Vec::new();
+        for data in ["{a: b, c: [b, b]}", "x::{a: c}"] {
+            let mut cmd = Command::cargo_bin("ion")?;
+            cmd.args(["cat", "-f", "binary"]).write_stdin(data);
+            encoded.extend(cmd.assert().success().get_output().stdout.clone());
+        }
+        File::create(&input)?.write_all(&encoded)?;
+
+        let mut cmd = Command::cargo_bin("ion")?;
+        cmd.args(["symtab", "-X", "optimize", "-n", "t", "-t", "3", "-s"])
+            .args([&shared_table, &input])
+            .args(["-o", optimized.to_str().unwrap()])
+            .timeout(Duration::new(5, 0));
+        cmd.assert().success();
+        assert!(std::fs::metadata(&optimized)?.len() < encoded.len() as u64);
+
+        let mut cmd = Command::cargo_bin("ion")?;
+        cmd.arg("cat").arg(&optimized).timeout(Duration::new(5, 0));
+        let output = cmd.assert().success().get_output().stdout.clone();
+        assert_eq!(
+            IonData::from(Element::read_all(output)?),
+            IonData::from(Element::read_all(DATA)?)
+        );
+
+        let mut cmd = Command::cargo_bin("ion")?;
+        cmd.args(["symtab", "-X", "list"])
+            .arg(&optimized)
+            .timeout(Duration::new(5, 0));
+        let output = cmd.assert().success().get_output().stdout.clone();
+        let expected = r#"{
+            offset: 4,
+            append: false,
+            imports: [],
+            symbols: ["b", "a", "c", "x"],
+            first_id: 10,
+            max_id: 13,
+        }"#;
+        assert_eq!(
+            IonData::from(Element::read_all(output)?),
+            IonData::from(Element::read_all(expected)?)
+        );
+
+        let expected = r#"$ion_shared_symbol_table::{
+            name: "t",
+            version: 3,
+            symbols: ["b", "a", "c", "x"],
+        }"#;
+        assert_eq!(
+            IonData::from(Element::read_all(std::fs::read(&shared_table)?)?),
+            IonData::from(Element::read_all(expected)?)
+        );
+        Ok(())
+    }
+
+    #[rstest]
+    #[case::name_without_table(&["-n", "t"])]
+    #[case::table_without_name(&["-s", "table.ion"])]
+    /// Tests that `--shared-table` and `--name` must be used together
+    fn test_symtab_optimize_shared_table_args(#[case] args: &[&str]) -> Result<()> {
+        let mut cmd = Command::cargo_bin("ion")?;
+        cmd.args(["symtab", "-X", "optimize"])
+            .args(args)
+            .timeout(Duration::new(5, 0))
+            .write_stdin(DATA);
+        cmd.assert().failure();
+        Ok(())
+    }
+}
+
+mod symtab_inline_tests {
+    use super::*;
+
+    const CATALOG: &str =
+        r#"$ion_shared_symbol_table::{name: "t", version: 1, symbols: ["a", "b"]}"#;
+
+    const DATA: &str = "{a: b, c: [a, d]} c::1";
+
+    #[test]
+    /// Tests that binary Ion that imports a shared table can be read without the catalog once its
+    /// imports are inlined
+    fn test_symtab_inline_binary() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let catalog = temp_dir.path().join("catalog.ion");
+        let imported = temp_dir.path().join("imported.10n");
+        let inlined = temp_dir.path().join("inlined.10n");
+        File::create(&catalog)?.write_all(CATALOG.as_bytes())?;
+        let mut cmd = Command::cargo_bin("ion")?;
+        cmd.args(["cat", "-f", "binary", "--import", "t:1", "--catalog"])
+            .args([&catalog])
+            .args(["-o", imported.to_str().unwrap()])
+            .write_stdin(DATA);
+        cmd.assert().success();
+
+        let mut cmd = Command::cargo_bin("ion")?;
+        cmd.args(["symtab", "-X", "inline", "--catalog"])
+            .args([&catalog, &imported])
+            .args(["-o", inlined.to_str().unwrap()])
+            .timeout(Duration::new(5, 0));
+        cmd.assert().success();
+
+        let mut cmd = Command::cargo_bin("ion")?;
+        cmd.arg("cat").arg(&inlined).timeout(Duration::new(5, 0));
+        let output = cmd.assert().success().get_output().stdout.clone();
+        assert_eq!(
+            IonData::from(Element::read_all(output)?),
+            IonData::from(Element::read_all(DATA)?)
+        );
+        Ok(())
+    }
+
+    #[rstest]
+    #[case::whole_table(
+        r#"$ion_symbol_table::{imports: [{name: "t", version: 1}], symbols: ["c"]} $10 $12"#,
+        r#"["a", "b", "c"]"#
+    )]
+    #[case::partial_table(
+        r#"$ion_symbol_table::{imports: [{name: "t", version: 1, max_id: 1}]} $10"#,
+        r#"["a"]"#
+    )]
+    #[case::beyond_table(
+        r#"$ion_symbol_table::{imports: [{name: "t", version: 1, max_id: 3}], symbols: ["c"]} $10 $13"#,
+        r#"["a", "b", null.string, "c"]"#
+    )]
+    #[case::no_imports(r#"$ion_symbol_table::{symbols: ["c"]} $10"#, r#"["c"]"#)]
+    /// Tests the symbols that `symtab inline` defines in place of imports
+    fn test_symtab_inline_text(#[case] input: &str, #[case] expected_symbols: &str) -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let catalog = temp_dir.path().join("catalog.ion");
+        File::create(&catalog)?.write_all(CATALOG.as_bytes())?;
+        let mut cmd = Command::cargo_bin("ion")?;
+        cmd.args(["symtab", "-X", "inline", "--catalog"])
+            .arg(&catalog)
+            .timeout(Duration::new(5, 0))
+            .write_stdin(input);
+        let inlined = cmd.assert().success().get_output().stdout.clone();
+
+        // The values are the same when read without the catalog.
+        let mut cmd = Command::cargo_bin("ion")?;
+        cmd.arg("cat").write_stdin(inlined.clone());
+        let output = cmd.assert().success().get_output().stdout.clone();
+        let mut cmd = Command::cargo_bin("ion")?;
+        cmd.args(["cat", "--catalog"])
+            .arg(&catalog)
+            .write_stdin(input);
+        let expected = cmd.assert().success().get_output().stdout.clone();
+        assert_eq!(
+            IonData::from(Element::read_all(output)?),
+            IonData::from(Element::read_all(expected)?)
+        );
+
+        let mut cmd = Command::cargo_bin("ion")?;
+        cmd.args(["symtab", "-X", "filter", "--lift"])
+            .write_stdin(inlined);
+        let output = cmd.assert().success().get_output().stdout.clone();
+        let expected = format!("{{symbols: {expected_symbols}}}");
+        assert_eq!(
+            IonData::from(Element::read_all(output)?),
+            IonData::from(Element::read_all(expected)?)
+        );
+        Ok(())
+    }
+
+    #[test]
+    /// Tests that `symtab inline` fails when an imported table is not in the catalog
+    fn test_symtab_inline_missing_table() -> Result<()> {
+        let mut cmd = Command::cargo_bin("ion")?;
+        cmd.args(["symtab", "-X", "inline"])
+            .timeout(Duration::new(5, 0))
+            .write_stdin(r#"$ion_symbol_table::{imports: [{name: "t", version: 1}]} $10"#);
+        cmd.assert().failure();
+        Ok(())
+    }
+}